@@ -0,0 +1,551 @@
+use crate::estatistica::{ln_gama, regressao_linear};
+use crate::experiencia::Experiencia;
+use crate::interface::TabuaInterface;
+use crate::lei_mortalidade::{parametros_validos, LeiMortalidade, ParametrosLei};
+use crate::otimizacao::nelder_mead;
+use crate::Tabua;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetodoAjuste {
+    MinimosQuadradosPonderados,
+    VerossimilhancaPoisson,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TipoLei {
+    Gompertz,
+    Makeham,
+    Weibull,
+    HeligmanPollard,
+    Perks,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultadoAjuste {
+    pub lei: LeiMortalidade,
+    pub valor_objetivo: f64,
+    pub log_verossimilhanca: f64,
+    pub deviance: f64,
+    pub qui_quadrado: f64,
+    pub aic: f64,
+    pub bic: f64,
+    pub obitos_esperados: Vec<f64>,
+    pub residuos: Vec<f64>,
+    pub iteracoes: usize,
+    pub convergiu: bool,
+}
+
+fn logit(p: f64) -> f64 {
+    return (p / (1.0 - p)).ln();
+}
+
+fn logistica(z: f64) -> f64 {
+    return 1.0 / (1.0 + (-z).exp());
+}
+
+// Os parâmetros são otimizados numa escala irrestrita, para que o Nelder-Mead não precise lidar
+// com as restrições de sinal de cada lei. Os parâmetros que podem ser nulos (a de Makeham, a e d
+// de Perks, b e d de Heligman-Pollard) valem -inf nessa escala quando iniciados em zero.
+fn para_escala_irrestrita(parametros: &ParametrosLei) -> Vec<f64> {
+    return match *parametros {
+        ParametrosLei::Gompertz { b, c } => vec![b.ln(), (c - 1.0).ln()],
+        ParametrosLei::Makeham { a, b, c } => vec![a.ln(), b.ln(), (c - 1.0).ln()],
+        ParametrosLei::Weibull { k, n } => vec![k.ln(), (n + 1.0).ln()],
+        ParametrosLei::HeligmanPollard {
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            g,
+            h,
+        } => vec![
+            logit(a),
+            b.ln(),
+            c.ln(),
+            d.ln(),
+            e.ln(),
+            f.ln(),
+            g.ln(),
+            h.ln(),
+        ],
+        ParametrosLei::Perks { a, b, c, d } => vec![a.ln(), b.ln(), (c - 1.0).ln(), d.ln()],
+    };
+}
+
+fn da_escala_irrestrita(modelo: &ParametrosLei, z: &Vec<f64>) -> ParametrosLei {
+    return match modelo {
+        ParametrosLei::Gompertz { .. } => ParametrosLei::Gompertz {
+            b: z[0].exp(),
+            c: 1.0 + z[1].exp(),
+        },
+        ParametrosLei::Makeham { .. } => ParametrosLei::Makeham {
+            a: z[0].exp(),
+            b: z[1].exp(),
+            c: 1.0 + z[2].exp(),
+        },
+        ParametrosLei::Weibull { .. } => ParametrosLei::Weibull {
+            k: z[0].exp(),
+            n: z[1].exp() - 1.0,
+        },
+        ParametrosLei::HeligmanPollard { .. } => ParametrosLei::HeligmanPollard {
+            a: logistica(z[0]),
+            b: z[1].exp(),
+            c: z[2].exp(),
+            d: z[3].exp(),
+            e: z[4].exp(),
+            f: z[5].exp(),
+            g: z[6].exp(),
+            h: z[7].exp(),
+        },
+        ParametrosLei::Perks { .. } => ParametrosLei::Perks {
+            a: z[0].exp(),
+            b: z[1].exp(),
+            c: 1.0 + z[2].exp(),
+            d: z[3].exp(),
+        },
+    };
+}
+
+fn forca_por_idade(lei: &LeiMortalidade, experiencia: &Experiencia) -> Vec<f64> {
+    let periodos = experiencia.periodicidade().quantidade_periodos_1_ano() as f64;
+    return experiencia
+        .idades()
+        .iter()
+        .map(|idade| {
+            lei.forca_acumulada_entre(*idade as f64 / periodos, (*idade as f64 + 1.0) / periodos)
+        })
+        .collect();
+}
+
+fn funcao_objetivo(lei: &LeiMortalidade, experiencia: &Experiencia, metodo: &MetodoAjuste) -> f64 {
+    let forcas = forca_por_idade(lei, experiencia);
+    let dados = forcas
+        .iter()
+        .zip(experiencia.obitos().iter())
+        .zip(experiencia.expostos().iter())
+        .filter(|(_, expostos)| **expostos > 0.0);
+
+    return match metodo {
+        MetodoAjuste::VerossimilhancaPoisson => {
+            dados.fold(0.0, |acc, ((forca, obitos), expostos)| {
+                let esperado = expostos * forca;
+                if *obitos > 0.0 {
+                    acc + esperado - obitos * esperado.ln()
+                } else {
+                    acc + esperado
+                }
+            })
+        }
+        // Qui-quadrado mínimo: cada resíduo de qx é ponderado pelo inverso da variância binomial
+        // da taxa ajustada.
+        MetodoAjuste::MinimosQuadradosPonderados => {
+            dados.fold(0.0, |acc, ((forca, obitos), expostos)| {
+                let qx_ajustado = 1.0 - (-forca).exp();
+                let qx_observado = 1.0 - (-obitos / expostos).exp();
+                acc + expostos * (qx_observado - qx_ajustado).powi(2)
+                    / (qx_ajustado * (1.0 - qx_ajustado))
+            })
+        }
+    };
+}
+
+fn parametros_iniciais(experiencia: &Experiencia, tipo: &TipoLei) -> ParametrosLei {
+    let periodos = experiencia.periodicidade().quantidade_periodos_1_ano() as f64;
+    let (idades, forcas): (Vec<f64>, Vec<f64>) = experiencia
+        .idades()
+        .iter()
+        .zip(experiencia.taxas_centrais().iter())
+        .filter(|(_, taxa)| **taxa > 0.0)
+        .map(|(idade, taxa)| ((*idade as f64 + 0.5) / periodos, taxa * periodos))
+        .unzip();
+
+    if idades.is_empty() && *tipo != TipoLei::HeligmanPollard {
+        panic!("A experiência não possui óbitos para estimar os parâmetros iniciais.");
+    }
+
+    let pesos = vec![1.0; idades.len()];
+    let ln_forcas = forcas.iter().map(|f| f.ln()).collect();
+    let gompertz = || {
+        let (intercepto, inclinacao) = regressao_linear(&idades, &ln_forcas, &pesos);
+        if inclinacao > 0.0 {
+            (intercepto.exp(), inclinacao.exp())
+        } else {
+            let media_idade = idades.iter().sum::<f64>() / idades.len() as f64;
+            (intercepto.exp() / 1.1_f64.powf(media_idade), 1.1)
+        }
+    };
+    let minimo = || forcas.iter().fold(f64::INFINITY, |acc, f| acc.min(*f));
+
+    return match tipo {
+        TipoLei::Gompertz => {
+            let (b, c) = gompertz();
+            ParametrosLei::Gompertz { b, c }
+        }
+        TipoLei::Makeham => {
+            let (b, c) = gompertz();
+            ParametrosLei::Makeham {
+                a: 0.5 * minimo(),
+                b,
+                c,
+            }
+        }
+        TipoLei::Perks => {
+            let (b, c) = gompertz();
+            ParametrosLei::Perks {
+                a: 0.5 * minimo(),
+                b,
+                c,
+                d: b,
+            }
+        }
+        TipoLei::Weibull => {
+            let ln_idades = idades.iter().map(|i| i.ln()).collect();
+            let (intercepto, inclinacao) = regressao_linear(&ln_idades, &ln_forcas, &pesos);
+            ParametrosLei::Weibull {
+                k: intercepto.exp(),
+                n: if inclinacao > -1.0 { inclinacao } else { 1.0 },
+            }
+        }
+        TipoLei::HeligmanPollard => ParametrosLei::HeligmanPollard {
+            a: 0.0005,
+            b: 0.01,
+            c: 0.1,
+            d: 0.001,
+            e: 10.0,
+            f: 20.0,
+            g: 0.00005,
+            h: 1.1,
+        },
+    };
+}
+
+fn calcular_resultado(
+    lei: LeiMortalidade,
+    experiencia: &Experiencia,
+    valor_objetivo: f64,
+    iteracoes: usize,
+    convergiu: bool,
+) -> ResultadoAjuste {
+    let quantidade_parametros = para_escala_irrestrita(lei.parametros())
+        .iter()
+        .filter(|z| z.is_finite())
+        .count() as f64;
+    let obitos_esperados: Vec<f64> = forca_por_idade(&lei, experiencia)
+        .iter()
+        .zip(experiencia.expostos().iter())
+        .map(|(forca, expostos)| forca * expostos)
+        .collect();
+
+    let mut log_verossimilhanca = 0.0;
+    let mut deviance = 0.0;
+    let mut qui_quadrado = 0.0;
+    let mut residuos = Vec::new();
+    let mut quantidade_observacoes: f64 = 0.0;
+    for (obitos, esperado) in experiencia.obitos().iter().zip(obitos_esperados.iter()) {
+        if *esperado <= 0.0 {
+            residuos.push(0.0);
+            continue;
+        }
+        quantidade_observacoes += 1.0;
+        log_verossimilhanca += obitos * esperado.ln() - esperado - ln_gama(obitos + 1.0);
+        deviance += if *obitos > 0.0 {
+            2.0 * (obitos * (obitos / esperado).ln() - (obitos - esperado))
+        } else {
+            2.0 * esperado
+        };
+        qui_quadrado += (obitos - esperado).powi(2) / esperado;
+        residuos.push((obitos - esperado) / esperado.sqrt());
+    }
+    if quantidade_observacoes == 0.0 {
+        panic!("Nenhuma idade possui óbitos esperados positivos para as estatísticas do ajuste.");
+    }
+
+    return ResultadoAjuste {
+        lei,
+        valor_objetivo,
+        log_verossimilhanca,
+        deviance,
+        qui_quadrado,
+        aic: 2.0 * quantidade_parametros - 2.0 * log_verossimilhanca,
+        bic: quantidade_parametros * quantidade_observacoes.ln() - 2.0 * log_verossimilhanca,
+        obitos_esperados,
+        residuos,
+        iteracoes,
+        convergiu,
+    };
+}
+
+pub fn ajustar_lei_com_parametros_iniciais(
+    experiencia: &Experiencia,
+    parametros_iniciais: ParametrosLei,
+    metodo: MetodoAjuste,
+) -> ResultadoAjuste {
+    if !parametros_validos(&parametros_iniciais) {
+        panic!("Parâmetros iniciais inválidos para a lei de mortalidade: {parametros_iniciais:?}.");
+    }
+    if !experiencia
+        .expostos()
+        .iter()
+        .any(|expostos| *expostos > 0.0)
+    {
+        panic!("A experiência deve possuir pelo menos uma idade com exposição positiva.");
+    }
+    // Parâmetros iniciados em zero ficam fixos em zero; o Nelder-Mead otimiza apenas os demais.
+    let z_inicial = para_escala_irrestrita(&parametros_iniciais);
+    let livres: Vec<usize> = (0..z_inicial.len())
+        .filter(|i| z_inicial[*i].is_finite())
+        .collect();
+    let completar = |z_livres: &Vec<f64>| {
+        let mut z = z_inicial.clone();
+        for (i, valor) in livres.iter().zip(z_livres.iter()) {
+            z[*i] = *valor;
+        }
+        z
+    };
+    let periodicidade = experiencia.periodicidade().clone();
+    let criar_lei = |z_livres: &Vec<f64>| {
+        let parametros = da_escala_irrestrita(&parametros_iniciais, &completar(z_livres));
+        if parametros_validos(&parametros) {
+            Some(LeiMortalidade::new(parametros, periodicidade.clone()))
+        } else {
+            None
+        }
+    };
+
+    let otimo = nelder_mead(
+        |z| match criar_lei(z) {
+            Some(lei) => funcao_objetivo(&lei, experiencia, &metodo),
+            None => f64::INFINITY,
+        },
+        livres.iter().map(|i| z_inicial[*i]).collect(),
+        0.5,
+        1e-12,
+        20000,
+    );
+
+    let lei = criar_lei(&otimo.x).expect("O ponto ótimo deveria possuir parâmetros válidos.");
+    return calcular_resultado(
+        lei,
+        experiencia,
+        otimo.valor,
+        otimo.iteracoes,
+        otimo.convergiu,
+    );
+}
+
+pub fn ajustar_lei(
+    experiencia: &Experiencia,
+    tipo: TipoLei,
+    metodo: MetodoAjuste,
+) -> ResultadoAjuste {
+    let parametros = parametros_iniciais(experiencia, &tipo);
+    return ajustar_lei_com_parametros_iniciais(experiencia, parametros, metodo);
+}
+
+// Ajusta a lei aos qx da tábua entre as idades informadas, com exposição unitária em cada idade.
+// Idades com qx = 1 não possuem taxa central finita e não podem ser usadas.
+pub fn ajustar_lei_a_tabua(
+    tabua: &Tabua,
+    idades: Vec<u16>,
+    tipo: TipoLei,
+    metodo: MetodoAjuste,
+) -> ResultadoAjuste {
+    for idade in idades.iter() {
+        if tabua.qx(&vec![*idade], 0) >= 1.0 {
+            panic!("A idade {idade} possui qx = 1 e não pode ser usada no ajuste da lei.");
+        }
+    }
+    let expostos = vec![1.0; idades.len()];
+    let experiencia = Experiencia::a_partir_da_tabua(tabua, idades, expostos);
+    return ajustar_lei(&experiencia, tipo, metodo);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Periodicidade;
+    use approx;
+
+    fn criar_experiencia(lei: &LeiMortalidade, idades: Vec<u16>) -> Experiencia {
+        let tabua = lei.gerar_tabua(120);
+        let expostos = vec![10000.0; idades.len()];
+        return Experiencia::a_partir_da_tabua(&tabua, idades, expostos);
+    }
+
+    #[test]
+    fn ajuste_poisson_recupera_parametros_de_gompertz() {
+        let lei = LeiMortalidade::gompertz(0.00005, 1.1, Periodicidade::Anual);
+        let experiencia = criar_experiencia(&lei, (30..100).collect());
+
+        let resultado = ajustar_lei(
+            &experiencia,
+            TipoLei::Gompertz,
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+
+        match resultado.lei.parametros() {
+            ParametrosLei::Gompertz { b, c } => {
+                approx::assert_relative_eq!(*b, 0.00005, max_relative = 1e-4);
+                approx::assert_relative_eq!(*c, 1.1, max_relative = 1e-5);
+            }
+            _ => panic!("O ajuste deveria retornar uma lei de Gompertz."),
+        }
+        approx::assert_abs_diff_eq!(resultado.deviance, 0.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn ajuste_por_minimos_quadrados_recupera_parametros_de_makeham() {
+        let lei = LeiMortalidade::makeham(0.0008, 0.00003, 1.1, Periodicidade::Anual);
+        let experiencia = criar_experiencia(&lei, (20..100).collect());
+
+        let resultado = ajustar_lei(
+            &experiencia,
+            TipoLei::Makeham,
+            MetodoAjuste::MinimosQuadradosPonderados,
+        );
+
+        match resultado.lei.parametros() {
+            ParametrosLei::Makeham { a, b, c } => {
+                approx::assert_relative_eq!(*a, 0.0008, max_relative = 1e-3);
+                approx::assert_relative_eq!(*b, 0.00003, max_relative = 1e-3);
+                approx::assert_relative_eq!(*c, 1.1, max_relative = 1e-4);
+            }
+            _ => panic!("O ajuste deveria retornar uma lei de Makeham."),
+        }
+    }
+
+    #[test]
+    fn ajuste_de_heligman_pollard_aproxima_a_tabua() {
+        let lei = LeiMortalidade::new(
+            ParametrosLei::HeligmanPollard {
+                a: 0.0008,
+                b: 0.02,
+                c: 0.12,
+                d: 0.0008,
+                e: 12.0,
+                f: 22.0,
+                g: 0.00004,
+                h: 1.1,
+            },
+            Periodicidade::Anual,
+        );
+        let tabua = lei.gerar_tabua(100);
+
+        let resultado = ajustar_lei_a_tabua(
+            &tabua,
+            (0..100).collect(),
+            TipoLei::HeligmanPollard,
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+
+        for x in [1, 20, 40, 60, 80, 99] {
+            approx::assert_relative_eq!(
+                resultado.lei.qx(&vec![x], 0),
+                tabua.qx(&vec![x], 0),
+                max_relative = 0.05
+            );
+        }
+    }
+
+    #[test]
+    fn estatisticas_de_aderencia_sao_consistentes() {
+        let experiencia = Experiencia::new(
+            vec![60, 61, 62, 63],
+            vec![12.0, 9.0, 20.0, 15.0],
+            vec![1000.0, 900.0, 800.0, 700.0],
+            Periodicidade::Anual,
+        );
+
+        let resultado = ajustar_lei(
+            &experiencia,
+            TipoLei::Gompertz,
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+
+        assert_eq!(resultado.residuos.len(), 4);
+        approx::assert_relative_eq!(
+            resultado.qui_quadrado,
+            resultado.residuos.iter().map(|r| r * r).sum::<f64>(),
+            epsilon = 1e-10
+        );
+        approx::assert_relative_eq!(
+            resultado.obitos_esperados.iter().sum::<f64>(),
+            experiencia.obitos().iter().sum::<f64>(),
+            max_relative = 1e-4
+        );
+        approx::assert_relative_eq!(
+            resultado.aic,
+            4.0 - 2.0 * resultado.log_verossimilhanca,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn parametros_iniciados_em_zero_ficam_fixos() {
+        let lei = LeiMortalidade::gompertz(0.00005, 1.1, Periodicidade::Anual);
+        let experiencia = criar_experiencia(&lei, (30..100).collect());
+
+        let resultado = ajustar_lei_com_parametros_iniciais(
+            &experiencia,
+            ParametrosLei::Makeham {
+                a: 0.0,
+                b: 0.0001,
+                c: 1.05,
+            },
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+
+        match resultado.lei.parametros() {
+            ParametrosLei::Makeham { a, b, c } => {
+                assert_eq!(*a, 0.0);
+                approx::assert_relative_eq!(*b, 0.00005, max_relative = 1e-3);
+                approx::assert_relative_eq!(*c, 1.1, max_relative = 1e-4);
+            }
+            _ => panic!("O ajuste deveria retornar uma lei de Makeham."),
+        }
+        approx::assert_relative_eq!(
+            resultado.aic,
+            4.0 - 2.0 * resultado.log_verossimilhanca,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A idade 3 possui qx = 1")]
+    fn ajuste_a_tabua_da_erro_com_idade_de_qx_1() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+
+        ajustar_lei_a_tabua(
+            &tabua,
+            (0..4).collect(),
+            TipoLei::Gompertz,
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "pelo menos uma idade com exposição positiva")]
+    fn ajuste_da_erro_sem_exposicao() {
+        let experiencia = Experiencia::new(vec![60], vec![0.0], vec![0.0], Periodicidade::Anual);
+
+        ajustar_lei_com_parametros_iniciais(
+            &experiencia,
+            ParametrosLei::Gompertz { b: 0.0001, c: 1.1 },
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Parâmetros iniciais inválidos")]
+    fn ajuste_da_erro_com_parametros_iniciais_invalidos() {
+        let experiencia = Experiencia::new(vec![60], vec![1.0], vec![100.0], Periodicidade::Anual);
+
+        ajustar_lei_com_parametros_iniciais(
+            &experiencia,
+            ParametrosLei::Gompertz { b: -1.0, c: 1.1 },
+            MetodoAjuste::VerossimilhancaPoisson,
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// Gerador SplitMix64. É simples, rápido e reprodutível a partir da semente, o que basta para a
+// simulação de cenários de mortalidade.
+#[derive(Debug, Clone)]
+pub(crate) struct GeradorAleatorio {
+    estado: u64,
+}
+
+impl GeradorAleatorio {
+    pub(crate) fn new(semente: u64) -> Self {
+        return GeradorAleatorio { estado: semente };
+    }
+
+    fn proximo(&mut self) -> u64 {
+        self.estado = self.estado.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.estado;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        return z ^ (z >> 31);
+    }
+
+    // Uniforme em [0, 1) com 53 bits de precisão.
+    pub(crate) fn uniforme(&mut self) -> f64 {
+        return (self.proximo() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    // Normal padrão por Box-Muller.
+    pub(crate) fn normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.uniforme();
+        let u2 = self.uniforme();
+        return (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesma_semente_gera_mesma_sequencia() {
+        let mut a = GeradorAleatorio::new(42);
+        let mut b = GeradorAleatorio::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.normal(), b.normal());
+        }
+    }
+
+    #[test]
+    fn normal_possui_media_zero_e_variancia_um() {
+        let mut gerador = GeradorAleatorio::new(7);
+        let amostra: Vec<f64> = (0..20000).map(|_| gerador.normal()).collect();
+        let media = amostra.iter().sum::<f64>() / amostra.len() as f64;
+        let variancia =
+            amostra.iter().map(|v| (v - media).powi(2)).sum::<f64>() / amostra.len() as f64;
+        assert!(media.abs() < 0.03);
+        assert!((variancia - 1.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,94 @@
+// Eliminação de Gauss com pivotamento parcial. As matrizes usadas na crate são pequenas (uma linha
+// por idade ou por covariável), então a versão densa é suficiente.
+pub(crate) fn resolver_sistema(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    if a.len() != n || a.iter().any(|linha| linha.len() != n) {
+        panic!(
+            "O sistema linear deve ser quadrado e compatível com o vetor de termos independentes."
+        );
+    }
+
+    for coluna in 0..n {
+        let pivo = (coluna..n)
+            .max_by(|i, j| a[*i][coluna].abs().total_cmp(&a[*j][coluna].abs()))
+            .expect("Deveria existir pelo menos uma linha para o pivô.");
+        if a[pivo][coluna].abs() < 1e-300 {
+            panic!("O sistema linear é singular.");
+        }
+        a.swap(coluna, pivo);
+        b.swap(coluna, pivo);
+
+        for linha in (coluna + 1)..n {
+            let fator = a[linha][coluna] / a[coluna][coluna];
+            if fator == 0.0 {
+                continue;
+            }
+            let linha_pivo = a[coluna].clone();
+            for (valor, valor_pivo) in a[linha][coluna..].iter_mut().zip(&linha_pivo[coluna..]) {
+                *valor -= fator * valor_pivo;
+            }
+            b[linha] -= fator * b[coluna];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for linha in (0..n).rev() {
+        let soma: f64 = ((linha + 1)..n).map(|k| a[linha][k] * x[k]).sum();
+        x[linha] = (b[linha] - soma) / a[linha][linha];
+    }
+    return x;
+}
+
+pub(crate) fn inverter_matriz(a: &Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = a.len();
+    let colunas: Vec<Vec<f64>> = (0..n)
+        .map(|j| {
+            let mut e = vec![0.0; n];
+            e[j] = 1.0;
+            resolver_sistema(a.clone(), e)
+        })
+        .collect();
+    return (0..n)
+        .map(|i| colunas.iter().map(|coluna| coluna[i]).collect())
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn resolver_sistema_funciona() {
+        let a = vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ];
+        let b = vec![8.0, -11.0, -3.0];
+
+        let x = resolver_sistema(a, b);
+
+        approx::assert_relative_eq!(x[0], 2.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(x[1], 3.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(x[2], -1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn inverter_matriz_funciona() {
+        let a = vec![vec![4.0, 7.0], vec![2.0, 6.0]];
+
+        let inversa = inverter_matriz(&a);
+
+        approx::assert_relative_eq!(inversa[0][0], 0.6, epsilon = 1e-12);
+        approx::assert_relative_eq!(inversa[0][1], -0.7, epsilon = 1e-12);
+        approx::assert_relative_eq!(inversa[1][0], -0.2, epsilon = 1e-12);
+        approx::assert_relative_eq!(inversa[1][1], 0.4, epsilon = 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "O sistema linear é singular.")]
+    fn resolver_sistema_da_erro_com_matriz_singular() {
+        resolver_sistema(vec![vec![1.0, 2.0], vec![2.0, 4.0]], vec![1.0, 2.0]);
+    }
+}
@@ -1,16 +1,24 @@
+// Cada período é dividido em `fator` subperíodos com a mesma probabilidade de sobrevivência:
+// qx' = 1 - (1 - qx)^(1 / fator).
 fn aumentar_periodicidade(x: Vec<f64>, fator: usize) -> Vec<f64> {
     return x
         .into_iter()
         .flat_map(|v| std::iter::repeat(v).take(fator))
-        .map(|v| alterar_qx(v, fator as f64))
+        .map(|v| alterar_qx(v, 1.0 / (fator as f64)))
         .collect();
 }
 
+// Cada novo período acumula a sobrevivência de `fator` períodos consecutivos. Se o último grupo
+// estiver incompleto, ele é completado com o último qx, que vale para as idades seguintes.
 fn reduzir_periodicidade(x: Vec<f64>, fator: usize) -> Vec<f64> {
-    x.into_iter()
-        .step_by(fator)
-        .map(|v| alterar_qx(v, 1.0 / (fator as f64)))
-        .collect()
+    let ultimo = x[x.len() - 1];
+    return x
+        .chunks(fator)
+        .map(|grupo| {
+            let px: f64 = grupo.iter().map(|v| 1.0 - v).product();
+            1.0 - px * (1.0 - ultimo).powi((fator - grupo.len()) as i32)
+        })
+        .collect();
 }
 
 fn alterar_qx(qx: f64, fator: f64) -> f64 {
@@ -39,6 +47,22 @@ pub fn alterar_periodicidade(
     }
 }
 
+// A idade inicial está em períodos da periodicidade atual. Ao reduzir a periodicidade, ela deve
+// corresponder a um período completo da nova periodicidade.
+pub fn alterar_idade_inicial(
+    idade_inicial: u16,
+    periodicidade_atual: usize,
+    nova_periodicidade: usize,
+) -> u16 {
+    let idade = idade_inicial as usize * nova_periodicidade;
+    if !idade.is_multiple_of(periodicidade_atual) {
+        panic!(
+            "A idade inicial da tábua não corresponde a um período completo da nova periodicidade."
+        );
+    }
+    return (idade / periodicidade_atual) as u16;
+}
+
 pub fn agravar_qx(qx: Vec<f64>, fator: f64) -> Vec<f64> {
     if fator == 0.0 {
         return qx;
@@ -51,6 +75,14 @@ pub fn agravar_qx(qx: Vec<f64>, fator: f64) -> Vec<f64> {
         .collect();
 }
 
+// Multiplica a taxa central -ln(1 - qx) pelo fator, ou seja, qx' = 1 - (1 - qx)^fator.
+pub fn agravar_forca_qx(qx: Vec<f64>, fator: f64) -> Vec<f64> {
+    if fator < 0.0 {
+        panic!("O fator deve ser maior que ou igual a zero.");
+    }
+    return qx.iter().map(|v| alterar_qx(*v, fator)).collect();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,12 +91,12 @@ mod tests {
     fn aumentar_periodicidade_funciona() {
         let qx_original = vec![0.1, 0.5, 0.7, 1.0];
         let qx_esperado = vec![
-            0.18999999999999995,
-            0.18999999999999995,
-            0.75,
-            0.75,
-            0.9099999999999999,
-            0.9099999999999999,
+            0.05131670194948623,
+            0.05131670194948623,
+            0.2928932188134524,
+            0.2928932188134524,
+            0.4522774424948338,
+            0.4522774424948338,
             1.0,
             1.0,
         ];
@@ -76,12 +108,12 @@ mod tests {
     #[test]
     fn reduzir_periodicidade_funciona() {
         let qx_original = vec![
-            0.18999999999999995,
-            0.18999999999999995,
-            0.75,
-            0.75,
-            0.9099999999999999,
-            0.9099999999999999,
+            0.05131670194948623,
+            0.05131670194948623,
+            0.2928932188134524,
+            0.2928932188134524,
+            0.4522774424948338,
+            0.4522774424948338,
             1.0,
             1.0,
         ];
@@ -98,16 +130,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reduzir_periodicidade_acumula_qx_diferentes_no_periodo() {
+        let qx_original = vec![0.1, 0.2, 0.5];
+        let qx_esperado = vec![0.28, 0.75];
+
+        let qx_obtido = reduzir_periodicidade(qx_original, 2);
+
+        assert_eq!(
+            qx_esperado,
+            qx_obtido
+                .iter()
+                .map(|v| (v * 1000.0).round() / 1000.0)
+                .collect::<Vec<f64>>()
+        );
+    }
+
     #[test]
     fn alterar_periodicidade_funciona_quando_aumenta() {
         let qx_original = vec![0.1, 0.5, 0.7, 1.0];
         let qx_esperado = vec![
-            0.18999999999999995,
-            0.18999999999999995,
-            0.75,
-            0.75,
-            0.9099999999999999,
-            0.9099999999999999,
+            0.05131670194948623,
+            0.05131670194948623,
+            0.2928932188134524,
+            0.2928932188134524,
+            0.4522774424948338,
+            0.4522774424948338,
             1.0,
             1.0,
         ];
@@ -119,12 +167,12 @@ mod tests {
     #[test]
     fn alterar_periodicidade_funciona_quando_reduz() {
         let qx_original = vec![
-            0.18999999999999995,
-            0.18999999999999995,
-            0.75,
-            0.75,
-            0.9099999999999999,
-            0.9099999999999999,
+            0.05131670194948623,
+            0.05131670194948623,
+            0.2928932188134524,
+            0.2928932188134524,
+            0.4522774424948338,
+            0.4522774424948338,
             1.0,
             1.0,
         ];
@@ -147,6 +195,18 @@ mod tests {
         alterar_periodicidade(qx_original, 2, 3);
     }
 
+    #[test]
+    fn alterar_idade_inicial_converte_entre_periodicidades() {
+        assert_eq!(alterar_idade_inicial(18, 1, 12), 216);
+        assert_eq!(alterar_idade_inicial(216, 12, 1), 18);
+    }
+
+    #[test]
+    #[should_panic(expected = "período completo")]
+    fn alterar_idade_inicial_panic_quando_idade_nao_eh_periodo_completo() {
+        alterar_idade_inicial(7, 2, 1);
+    }
+
     #[test]
     fn agravar_qx_nao_gera_qx_com_valores_acima_de_1() {
         let qx_original = vec![0.1, 0.5, 0.7, 1.0];
@@ -165,6 +225,15 @@ mod tests {
         assert_eq!(qx_esperado, qx_obtido);
     }
 
+    #[test]
+    fn agravar_forca_qx_multiplica_a_taxa_central() {
+        let qx_obtido = agravar_forca_qx(vec![0.1, 0.5, 1.0], 2.0);
+
+        approx::assert_abs_diff_eq!(qx_obtido[0], 0.19, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(qx_obtido[1], 0.75, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(qx_obtido[2], 1.0, epsilon = 1e-12);
+    }
+
     #[test]
     #[should_panic]
     fn agravar_qx_panic_quando_fator_negativo() {
@@ -0,0 +1,293 @@
+use crate::expectativa::sobrevivencias;
+use crate::interface::{JurosInterface, TabuaInterface};
+use crate::JurosConstante;
+use crate::Periodicidade;
+use crate::Tabua;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AproximacaoFracionada {
+    // ä(m) = ä - (m - 1) / 2m
+    WoolhouseDoisTermos,
+    // ä(m) = ä - (m - 1) / 2m - (m² - 1) / 12m² (μx + δ)
+    WoolhouseTresTermos,
+    // ä(m) = α(m) ä - β(m), com distribuição uniforme das mortes em cada ano.
+    Udd,
+}
+
+fn validar(tabua: &Tabua, juros: &JurosConstante) {
+    if tabua.periodicidade() != &Periodicidade::Anual
+        || juros.periodicidade() != &Periodicidade::Anual
+    {
+        panic!("As aproximações fracionadas partem de tábua e juros anuais.");
+    }
+}
+
+// Taxas nominais i(m) e d(m) convertidas m vezes ao ano.
+fn taxas_nominais(i: f64, m: f64) -> (f64, f64) {
+    let i_m = m * ((1.0 + i).powf(1.0 / m) - 1.0);
+    let d_m = m * (1.0 - (1.0 + i).powf(-1.0 / m));
+    return (i_m, d_m);
+}
+
+pub fn alfa_udd(i: f64, fracionamento: &Periodicidade) -> f64 {
+    let (i_m, d_m) = taxas_nominais(i, fracionamento.quantidade_periodos_1_ano() as f64);
+    let d = i / (1.0 + i);
+    return i * d / (i_m * d_m);
+}
+
+pub fn beta_udd(i: f64, fracionamento: &Periodicidade) -> f64 {
+    let (i_m, d_m) = taxas_nominais(i, fracionamento.quantidade_periodos_1_ano() as f64);
+    return (i - i_m) / (i_m * d_m);
+}
+
+// ä_x anual vitalícia, truncada pela tolerância quando a tábua não tem idade máxima.
+pub fn anuidade_antecipada(tabua: &Tabua, x: u16, juros: &JurosConstante, tolerancia: f64) -> f64 {
+    validar(tabua, juros);
+    return sobrevivencias(tabua, &vec![x], tolerancia)
+        .iter()
+        .enumerate()
+        .map(|(t, tpx)| juros.taxa_desconto(t as u16) * tpx)
+        .sum();
+}
+
+// A_x anual vitalício, com pagamento no fim do ano da morte.
+pub fn seguro_vida_inteira(tabua: &Tabua, x: u16, juros: &JurosConstante, tolerancia: f64) -> f64 {
+    validar(tabua, juros);
+    let tpx = sobrevivencias(tabua, &vec![x], tolerancia);
+    return (0..tpx.len())
+        .map(|t| juros.taxa_desconto(t as u16 + 1) * tpx[t] * tabua.qx(&vec![x], t as u16))
+        .sum();
+}
+
+// ä_x^(m): pagamentos de 1/m no início de cada fração do ano, calculados sem converter a tábua.
+pub fn anuidade_fracionada(
+    tabua: &Tabua,
+    x: u16,
+    juros: &JurosConstante,
+    fracionamento: &Periodicidade,
+    aproximacao: AproximacaoFracionada,
+    tolerancia: f64,
+) -> f64 {
+    validar(tabua, juros);
+    let m = fracionamento.quantidade_periodos_1_ano() as f64;
+    let i = juros.taxa_juros(0);
+    let anuidade = anuidade_antecipada(tabua, x, juros, tolerancia);
+    return match aproximacao {
+        AproximacaoFracionada::WoolhouseDoisTermos => anuidade - (m - 1.0) / (2.0 * m),
+        AproximacaoFracionada::WoolhouseTresTermos => {
+            // μx ≈ -(ln p_{x-1} + ln p_x) / 2. Na idade inicial da tábua usa-se apenas -ln p_x e,
+            // onde qx = 1, apenas -ln p_{x-1}; sem nenhum dos dois o termo de μx é desprezado.
+            let ln_px = |idade: u16| {
+                let px = 1.0 - tabua.qx(&vec![idade], 0);
+                if px > 0.0 {
+                    Some(px.ln())
+                } else {
+                    None
+                }
+            };
+            let ln_px_anterior = if x > tabua.idade_inicial() {
+                ln_px(x - 1)
+            } else {
+                None
+            };
+            let mu = match (ln_px_anterior, ln_px(x)) {
+                (Some(anterior), Some(atual)) => -0.5 * (anterior + atual),
+                (Some(anterior), None) => -anterior,
+                (None, Some(atual)) => -atual,
+                (None, None) => 0.0,
+            };
+            let delta = (1.0 + i).ln();
+            anuidade - (m - 1.0) / (2.0 * m) - (m * m - 1.0) / (12.0 * m * m) * (mu + delta)
+        }
+        AproximacaoFracionada::Udd => {
+            alfa_udd(i, fracionamento) * anuidade - beta_udd(i, fracionamento)
+        }
+    };
+}
+
+// A_x^(m): pagamento no fim da fração do ano da morte. Sob UDD, A(m) = i / i(m) A; nas
+// aproximações de Woolhouse, A(m) = 1 - d(m) ä(m).
+pub fn seguro_fracionado(
+    tabua: &Tabua,
+    x: u16,
+    juros: &JurosConstante,
+    fracionamento: &Periodicidade,
+    aproximacao: AproximacaoFracionada,
+    tolerancia: f64,
+) -> f64 {
+    validar(tabua, juros);
+    let m = fracionamento.quantidade_periodos_1_ano() as f64;
+    let i = juros.taxa_juros(0);
+    let (i_m, d_m) = taxas_nominais(i, m);
+    return match aproximacao {
+        AproximacaoFracionada::Udd => i / i_m * seguro_vida_inteira(tabua, x, juros, tolerancia),
+        _ => {
+            1.0 - d_m * anuidade_fracionada(tabua, x, juros, fracionamento, aproximacao, tolerancia)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    fn criar_tabua() -> Tabua {
+        Tabua::new(vec![0.05, 0.1, 0.2, 0.4, 1.0], Periodicidade::Anual)
+    }
+
+    #[test]
+    fn udd_coincide_com_soma_mensal_interpolada() {
+        let tabua = criar_tabua();
+        let juros = JurosConstante::new(0.06, Periodicidade::Anual);
+
+        // Soma exata dos pagamentos mensais com tpx interpolado linearmente em cada ano.
+        let mut esperado = 0.0;
+        for k in 0..5 {
+            let kpx = tabua.tpx(&vec![0], k);
+            let q = tabua.qx(&vec![0], k);
+            for j in 0..12 {
+                let s = j as f64 / 12.0;
+                esperado += 1.06_f64.powf(-(k as f64 + s)) * kpx * (1.0 - s * q) / 12.0;
+            }
+        }
+
+        let anuidade = anuidade_fracionada(
+            &tabua,
+            0,
+            &juros,
+            &Periodicidade::Mensal,
+            AproximacaoFracionada::Udd,
+            1e-12,
+        );
+
+        approx::assert_relative_eq!(anuidade, esperado, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn relacao_entre_seguro_e_anuidade_sob_udd() {
+        let tabua = criar_tabua();
+        let juros = JurosConstante::new(0.06, Periodicidade::Anual);
+        let (_, d_m) = taxas_nominais(0.06, 4.0);
+
+        let anuidade = anuidade_fracionada(
+            &tabua,
+            1,
+            &juros,
+            &Periodicidade::Trimestral,
+            AproximacaoFracionada::Udd,
+            1e-12,
+        );
+        let seguro = seguro_fracionado(
+            &tabua,
+            1,
+            &juros,
+            &Periodicidade::Trimestral,
+            AproximacaoFracionada::Udd,
+            1e-12,
+        );
+
+        approx::assert_relative_eq!(seguro, 1.0 - d_m * anuidade, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn woolhouse_aproxima_udd() {
+        let qx: Vec<f64> = (0..80).map(|x| 0.0005 * 1.09_f64.powi(x)).collect();
+        let tabua = Tabua::new(qx, Periodicidade::Anual);
+        let juros = JurosConstante::new(0.05, Periodicidade::Anual);
+        let calcular = |aproximacao| {
+            anuidade_fracionada(
+                &tabua,
+                40,
+                &juros,
+                &Periodicidade::Mensal,
+                aproximacao,
+                1e-12,
+            )
+        };
+
+        let udd = calcular(AproximacaoFracionada::Udd);
+        let dois_termos = calcular(AproximacaoFracionada::WoolhouseDoisTermos);
+        let tres_termos = calcular(AproximacaoFracionada::WoolhouseTresTermos);
+
+        approx::assert_abs_diff_eq!(
+            dois_termos,
+            anuidade_antecipada(&tabua, 40, &juros, 1e-12) - 11.0 / 24.0,
+            epsilon = 1e-12
+        );
+        assert!((tres_termos - udd).abs() < (dois_termos - udd).abs());
+        approx::assert_abs_diff_eq!(tres_termos, udd, epsilon = 5e-3);
+    }
+
+    #[test]
+    fn woolhouse_tres_termos_na_ultima_idade_usa_a_idade_anterior() {
+        let tabua = criar_tabua();
+        let juros = JurosConstante::new(0.06, Periodicidade::Anual);
+
+        let anuidade = anuidade_fracionada(
+            &tabua,
+            4,
+            &juros,
+            &Periodicidade::Mensal,
+            AproximacaoFracionada::WoolhouseTresTermos,
+            1e-12,
+        );
+
+        let mu = -(0.6_f64.ln());
+        let delta = 1.06_f64.ln();
+        approx::assert_abs_diff_eq!(
+            anuidade,
+            1.0 - 11.0 / 24.0 - 143.0 / 1728.0 * (mu + delta),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn aproximacoes_reconciliam_com_a_conversao_da_tabua() {
+        let qx: Vec<f64> = (0..80).map(|x| 0.0005 * 1.09_f64.powi(x)).collect();
+        let tabua = Tabua::new(qx, Periodicidade::Anual);
+        let juros = JurosConstante::new(0.05, Periodicidade::Anual);
+        let tabua_mensal = tabua.alterar_periodicidade(Periodicidade::Mensal);
+        let juros_mensal = juros.alterar_periodicidade(Periodicidade::Mensal);
+
+        // ä_x^(12) com a tábua e os juros convertidos para mensal, ou seja, com força de
+        // mortalidade constante em cada ano.
+        let convertida: f64 = sobrevivencias(&tabua_mensal, &vec![40 * 12], 1e-12)
+            .iter()
+            .enumerate()
+            .map(|(t, tpx)| juros_mensal.taxa_desconto(t as u16) * tpx / 12.0)
+            .sum();
+        let calcular = |aproximacao| {
+            anuidade_fracionada(
+                &tabua,
+                40,
+                &juros,
+                &Periodicidade::Mensal,
+                aproximacao,
+                1e-12,
+            )
+        };
+
+        let dois_termos = calcular(AproximacaoFracionada::WoolhouseDoisTermos);
+        let tres_termos = calcular(AproximacaoFracionada::WoolhouseTresTermos);
+        let udd = calcular(AproximacaoFracionada::Udd);
+
+        assert!((tres_termos - convertida).abs() < (dois_termos - convertida).abs());
+        approx::assert_abs_diff_eq!(tres_termos, convertida, epsilon = 5e-3);
+        approx::assert_abs_diff_eq!(udd, convertida, epsilon = 5e-3);
+    }
+
+    #[test]
+    fn alfa_e_beta_anuais_sao_triviais() {
+        approx::assert_relative_eq!(alfa_udd(0.05, &Periodicidade::Anual), 1.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(beta_udd(0.05, &Periodicidade::Anual), 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "As aproximações fracionadas partem de tábua e juros anuais.")]
+    fn tabua_deve_ser_anual() {
+        let tabua = Tabua::new(vec![0.1], Periodicidade::Mensal);
+        let juros = JurosConstante::new(0.05, Periodicidade::Anual);
+        anuidade_antecipada(&tabua, 0, &juros, 1e-6);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::interface::{validar_idades_tabuas, JurosInterface, TabuaInterface};
+use crate::{StatusVidasConjuntas, TabuaMultiplasVidas};
+use infinitable::Infinitable::{Finite, Infinity, NegativeInfinity};
+
+// Benefícios sobre duas vidas independentes, x (primeira tábua) e y (segunda tábua), usando as
+// curvas individuais de cada vida, que podem vir de tábuas diferentes. A ordem das mortes é
+// definida por cada benefício, por isso a tábua de múltiplas vidas deve ter o status First, o de
+// vidas conjuntas. Pagamentos no fim do período; dentro do período supõe-se UDD em cada vida.
+
+struct Curvas {
+    tpx: Vec<f64>,
+    qx: Vec<f64>,
+    tpy: Vec<f64>,
+    qy: Vec<f64>,
+    desconto: Vec<f64>,
+}
+
+// tpx, qx e fatores de desconto para t = 0, 1, ... até as duas vidas se extinguirem ou, com
+// tempo futuro máximo infinito, até as duas sobrevivências ficarem abaixo da tolerância.
+fn calcular_curvas<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> Curvas {
+    if tabua.numero_vidas() != 2 {
+        panic!("Os benefícios reversíveis e contingentes exigem exatamente duas vidas.");
+    }
+    if *tabua.status_vidas_conjuntas() != StatusVidasConjuntas::First {
+        panic!(
+            "Os benefícios reversíveis e contingentes exigem a tábua de vidas conjuntas (First), \
+             obtido = {:?}.",
+            tabua.status_vidas_conjuntas()
+        );
+    }
+    validar_idades_tabuas(x, tabua.numero_decrementos(), tabua.numero_vidas());
+    if juros.periodicidade() != tabua.periodicidade() {
+        panic!("A tábua e os juros devem possuir a mesma periodicidade.");
+    }
+    if !(tolerancia > 0.0 && tolerancia < 1.0) {
+        panic!("A tolerância deve estar entre 0 e 1, obtido = {tolerancia}.");
+    }
+    let tabuas = tabua.obter_tabuas_base();
+    let limite = match tabuas[0]
+        .tempo_futuro_maximo(x[0])
+        .max(tabuas[1].tempo_futuro_maximo(x[1]))
+    {
+        Finite(tempo) => Some(tempo),
+        Infinity => None,
+        NegativeInfinity => panic!("O tempo futuro máximo não pode ser NegativeInfinity."),
+    };
+
+    let mut curvas = Curvas {
+        tpx: Vec::new(),
+        qx: Vec::new(),
+        tpy: Vec::new(),
+        qy: Vec::new(),
+        desconto: Vec::new(),
+    };
+    let mut t: u16 = 0;
+    loop {
+        let tpx = tabuas[0].tpx(x[0], t);
+        let tpy = tabuas[1].tpx(x[1], t);
+        let fim = match limite {
+            Some(limite) => t >= limite,
+            None => tpx < tolerancia && tpy < tolerancia,
+        };
+        if fim {
+            break;
+        }
+        if t == u16::MAX {
+            panic!("A sobrevivência não ficou abaixo da tolerância {tolerancia}.");
+        }
+        curvas.tpx.push(tpx);
+        curvas.qx.push(tabuas[0].qx(x[0], t));
+        curvas.tpy.push(tpy);
+        curvas.qy.push(tabuas[1].qx(x[1], t));
+        curvas.desconto.push(juros.taxa_desconto(t + 1));
+        t += 1;
+    }
+    return curvas;
+}
+
+fn contingente_primeira_morte(curvas: &Curvas) -> f64 {
+    return (0..curvas.tpx.len())
+        .map(|t| {
+            curvas.desconto[t]
+                * curvas.tpx[t]
+                * curvas.tpy[t]
+                * curvas.qx[t]
+                * (1.0 - 0.5 * curvas.qy[t])
+        })
+        .sum();
+}
+
+// a_{x|y}: anuidade paga a y ao fim de cada período em que y está viva e x já morreu.
+pub fn anuidade_reversivel<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> f64 {
+    let curvas = calcular_curvas(tabua, x, juros, tolerancia);
+    return (0..curvas.tpx.len())
+        .map(|t| {
+            let tpx_seguinte = curvas.tpx[t] * (1.0 - curvas.qx[t]);
+            let tpy_seguinte = curvas.tpy[t] * (1.0 - curvas.qy[t]);
+            curvas.desconto[t] * tpy_seguinte * (1.0 - tpx_seguinte)
+        })
+        .sum();
+}
+
+// A¹_{xy}: paga na morte de x se y ainda estiver viva, com
+// P[x morre em (t, t+1] antes de y] = tp_xy q_{x+t} (1 - q_{y+t} / 2).
+pub fn seguro_contingente_primeira_morte<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> f64 {
+    let curvas = calcular_curvas(tabua, x, juros, tolerancia);
+    return contingente_primeira_morte(&curvas);
+}
+
+// A²_{xy}: paga na morte de x se y já tiver morrido, ou seja, A_x - A¹_{xy}.
+pub fn seguro_contingente_segunda_morte<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> f64 {
+    let curvas = calcular_curvas(tabua, x, juros, tolerancia);
+    let seguro_x: f64 = (0..curvas.tpx.len())
+        .map(|t| curvas.desconto[t] * curvas.tpx[t] * curvas.qx[t])
+        .sum();
+    return seguro_x - contingente_primeira_morte(&curvas);
+}
+
+// A_{xy}: paga na primeira morte, qualquer que seja a ordem.
+pub fn seguro_primeira_morte<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> f64 {
+    let curvas = calcular_curvas(tabua, x, juros, tolerancia);
+    return (0..curvas.tpx.len())
+        .map(|t| {
+            let q_conjunta = 1.0 - (1.0 - curvas.qx[t]) * (1.0 - curvas.qy[t]);
+            curvas.desconto[t] * curvas.tpx[t] * curvas.tpy[t] * q_conjunta
+        })
+        .sum();
+}
+
+// A_{x̄ȳ}: paga na segunda morte, qualquer que seja a ordem.
+pub fn seguro_segunda_morte<J: JurosInterface>(
+    tabua: &TabuaMultiplasVidas,
+    x: &Vec<u16>,
+    juros: &J,
+    tolerancia: f64,
+) -> f64 {
+    let curvas = calcular_curvas(tabua, x, juros, tolerancia);
+    return (0..curvas.tpx.len())
+        .map(|t| {
+            let tpx_seguinte = curvas.tpx[t] * (1.0 - curvas.qx[t]);
+            let tpy_seguinte = curvas.tpy[t] * (1.0 - curvas.qy[t]);
+            let sobrevivente = curvas.tpx[t] + curvas.tpy[t] - curvas.tpx[t] * curvas.tpy[t];
+            let sobrevivente_seguinte = tpx_seguinte + tpy_seguinte - tpx_seguinte * tpy_seguinte;
+            curvas.desconto[t] * (sobrevivente - sobrevivente_seguinte)
+        })
+        .sum();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JurosConstante, Periodicidade, Tabua};
+    use approx;
+
+    fn criar_tabuas() -> (Tabua, Tabua) {
+        let qx_x = (0..100)
+            .map(|x| (0.0005 * 1.1_f64.powi(x)).min(1.0))
+            .collect();
+        let qx_y = (0..100)
+            .map(|x| (0.0003 * 1.09_f64.powi(x)).min(0.9))
+            .collect();
+        (
+            Tabua::new(qx_x, Periodicidade::Anual),
+            Tabua::new(qx_y, Periodicidade::Anual),
+        )
+    }
+
+    // As duas ordens das vidas: (x, y) e (y, x).
+    fn criar_tabuas_vidas() -> (TabuaMultiplasVidas, TabuaMultiplasVidas) {
+        let (tabua_x, tabua_y) = criar_tabuas();
+        (
+            TabuaMultiplasVidas::new(vec![tabua_x.clone(), tabua_y.clone()], crate::First),
+            TabuaMultiplasVidas::new(vec![tabua_y, tabua_x], crate::First),
+        )
+    }
+
+    #[test]
+    fn seguros_contingentes_repartem_o_seguro_de_primeira_morte() {
+        let (tabua, trocada) = criar_tabuas_vidas();
+        let juros = JurosConstante::new(0.04, Periodicidade::Anual);
+
+        let a1_xy = seguro_contingente_primeira_morte(&tabua, &vec![60, 55], &juros, 1e-12);
+        let a1_yx = seguro_contingente_primeira_morte(&trocada, &vec![55, 60], &juros, 1e-12);
+        let a_xy = seguro_primeira_morte(&tabua, &vec![60, 55], &juros, 1e-12);
+
+        approx::assert_relative_eq!(a1_xy + a1_yx, a_xy, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn seguros_de_segunda_morte_sao_complementares() {
+        let (tabua, trocada) = criar_tabuas_vidas();
+        let juros = JurosConstante::new(0.04, Periodicidade::Anual);
+        let x = vec![60, 55];
+
+        let a2_xy = seguro_contingente_segunda_morte(&tabua, &x, &juros, 1e-12);
+        let a2_yx = seguro_contingente_segunda_morte(&trocada, &vec![55, 60], &juros, 1e-12);
+        let segunda = seguro_segunda_morte(&tabua, &x, &juros, 1e-12);
+        let primeira = seguro_primeira_morte(&tabua, &x, &juros, 1e-12);
+        let a1_xy = seguro_contingente_primeira_morte(&tabua, &x, &juros, 1e-12);
+        let a1_yx = seguro_contingente_primeira_morte(&trocada, &vec![55, 60], &juros, 1e-12);
+
+        approx::assert_relative_eq!(a2_xy + a2_yx, segunda, epsilon = 1e-12);
+        // A_x + A_y = A_xy + A_x̄ȳ.
+        approx::assert_relative_eq!(
+            a1_xy + a2_xy + a1_yx + a2_yx,
+            primeira + segunda,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn anuidade_reversivel_eh_a_diferenca_entre_anuidades() {
+        let (tabua, _) = criar_tabuas_vidas();
+        let juros = JurosConstante::new(0.05, Periodicidade::Anual);
+        let tabuas = tabua.obter_tabuas_base();
+        let x = vec![65, 60];
+
+        // a_{x|y} = a_y - a_xy.
+        let (a_y, a_xy) = (1..150).fold((0.0, 0.0), |(a_y, a_xy), t| {
+            let v = juros.taxa_desconto(t);
+            let tpy = tabuas[1].tpx(x[1], t);
+            (a_y + v * tpy, a_xy + v * tpy * tabuas[0].tpx(x[0], t))
+        });
+
+        approx::assert_relative_eq!(
+            anuidade_reversivel(&tabua, &x, &juros, 1e-14),
+            a_y - a_xy,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn vidas_identicas_dividem_igualmente_a_primeira_morte() {
+        let tabua_vida = Tabua::new(vec![0.1, 0.2, 0.5, 1.0], Periodicidade::Anual);
+        let tabua = TabuaMultiplasVidas::new(vec![tabua_vida.clone(), tabua_vida], crate::First);
+        let juros = JurosConstante::new(0.03, Periodicidade::Anual);
+
+        let a1 = seguro_contingente_primeira_morte(&tabua, &vec![1, 1], &juros, 1e-12);
+        let a_xy = seguro_primeira_morte(&tabua, &vec![1, 1], &juros, 1e-12);
+
+        approx::assert_relative_eq!(a1, a_xy / 2.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "exigem a tábua de vidas conjuntas (First), obtido = Last.")]
+    fn beneficios_exigem_status_de_vidas_conjuntas() {
+        let (tabua_x, tabua_y) = criar_tabuas();
+        let tabua = TabuaMultiplasVidas::new(vec![tabua_x, tabua_y], crate::Last);
+        let juros = JurosConstante::new(0.03, Periodicidade::Anual);
+        seguro_segunda_morte(&tabua, &vec![60, 55], &juros, 1e-6);
+    }
+
+    #[test]
+    #[should_panic(expected = "exigem exatamente duas vidas")]
+    fn beneficios_exigem_duas_vidas() {
+        let tabua_vida = Tabua::new(vec![0.1], Periodicidade::Anual);
+        let tabua = TabuaMultiplasVidas::new(vec![tabua_vida; 3], crate::First);
+        let juros = JurosConstante::new(0.03, Periodicidade::Anual);
+        anuidade_reversivel(&tabua, &vec![0, 0, 0], &juros, 1e-6);
+    }
+}
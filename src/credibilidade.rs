@@ -0,0 +1,488 @@
+use crate::estatistica::quantil_normal;
+use crate::experiencia::Experiencia;
+use crate::interface::TabuaInterface;
+use crate::Tabua;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AplicacaoCredibilidade {
+    PorIdade,
+    MultiplicadorGlobal,
+}
+
+// Os multiplicadores são razões entre óbitos reais e esperados pela taxa central, por isso são
+// aplicados sobre a taxa central da tábua de referência: qx' = 1 - (1 - qx)^multiplicador.
+// Na aplicação global, razoes_observadas, fatores_credibilidade e multiplicadores possuem um
+// único elemento.
+#[derive(Debug, Clone)]
+pub struct ResultadoCredibilidade {
+    pub tabua: Tabua,
+    pub idades: Vec<u16>,
+    pub razoes_observadas: Vec<f64>,
+    pub fatores_credibilidade: Vec<f64>,
+    pub multiplicadores: Vec<f64>,
+    pub padrao_credibilidade_total: Option<f64>,
+    pub variancia_processo: Option<f64>,
+    pub variancia_hipoteses: Option<f64>,
+}
+
+fn validar_periodicidade(experiencia: &Experiencia, tabua: &Tabua) {
+    if experiencia.periodicidade() != tabua.periodicidade() {
+        panic!("A experiência e a tábua de referência devem possuir a mesma periodicidade.");
+    }
+}
+
+// Óbitos esperados pela taxa central da tábua de referência sobre a exposição central.
+fn obitos_esperados(experiencia: &Experiencia, tabua: &Tabua) -> Vec<f64> {
+    return experiencia
+        .idades()
+        .iter()
+        .zip(experiencia.expostos().iter())
+        .map(|(idade, expostos)| expostos * -(1.0 - tabua.qx(&vec![*idade], 0)).ln())
+        .collect();
+}
+
+fn razao(reais: f64, esperados: f64) -> f64 {
+    if esperados <= 0.0 {
+        return 1.0;
+    }
+    return reais / esperados;
+}
+
+fn aplicar_multiplicadores(tabua: &Tabua, idades: &Vec<u16>, multiplicadores: &Vec<f64>) -> Tabua {
+    let tabua_base = tabua.obter_tabua_base();
+    let idade_inicial = idades
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or(tabua.idade_inicial())
+        .min(tabua.idade_inicial());
+    let idade_limite = idades
+        .iter()
+        .map(|idade| *idade as usize + 1)
+        .max()
+        .unwrap_or(0)
+        .max(tabua_base.idade_limite());
+    let mut qx: Vec<f64> = (idade_inicial as usize..idade_limite)
+        .map(|idade| tabua.qx(&vec![idade as u16], 0))
+        .collect();
+    for (idade, multiplicador) in idades.iter().zip(multiplicadores.iter()) {
+        let indice = (idade - idade_inicial) as usize;
+        qx[indice] = 1.0 - (1.0 - qx[indice]).powf(*multiplicador);
+    }
+    return Tabua::a_partir_da_idade(
+        idade_inicial,
+        qx,
+        tabua.periodicidade().clone(),
+        tabua.regra_idade_inferior().clone(),
+    );
+}
+
+// Credibilidade total quando os óbitos atingem (z / k)^2, com z o quantil normal de (1 + p) / 2.
+// Abaixo disso, Z = sqrt(óbitos / padrão).
+pub fn credibilidade_flutuacao_limitada(
+    experiencia: &Experiencia,
+    tabua_referencia: &Tabua,
+    probabilidade: f64,
+    tolerancia: f64,
+    aplicacao: AplicacaoCredibilidade,
+) -> ResultadoCredibilidade {
+    validar_periodicidade(experiencia, tabua_referencia);
+    if !(probabilidade > 0.0 && probabilidade < 1.0) || tolerancia <= 0.0 {
+        panic!("A probabilidade deve estar entre 0 e 1 e a tolerância deve ser positiva.");
+    }
+    let padrao = (quantil_normal((1.0 + probabilidade) / 2.0) / tolerancia).powi(2);
+    let esperados = obitos_esperados(experiencia, tabua_referencia);
+    let fator = |obitos: f64| (obitos / padrao).sqrt().min(1.0);
+
+    let (razoes, fatores): (Vec<f64>, Vec<f64>) = match aplicacao {
+        AplicacaoCredibilidade::PorIdade => experiencia
+            .obitos()
+            .iter()
+            .zip(esperados.iter())
+            .map(|(reais, esperados)| (razao(*reais, *esperados), fator(*reais)))
+            .unzip(),
+        AplicacaoCredibilidade::MultiplicadorGlobal => {
+            let reais: f64 = experiencia.obitos().iter().sum();
+            (
+                vec![razao(reais, esperados.iter().sum())],
+                vec![fator(reais)],
+            )
+        }
+    };
+    let multiplicadores: Vec<f64> = razoes
+        .iter()
+        .zip(fatores.iter())
+        .map(|(r, z)| z * r + (1.0 - z))
+        .collect();
+
+    let tabua = match aplicacao {
+        AplicacaoCredibilidade::PorIdade => {
+            aplicar_multiplicadores(tabua_referencia, experiencia.idades(), &multiplicadores)
+        }
+        AplicacaoCredibilidade::MultiplicadorGlobal => {
+            tabua_referencia.agravar_forca(multiplicadores[0])
+        }
+    };
+
+    return ResultadoCredibilidade {
+        tabua,
+        idades: experiencia.idades().clone(),
+        razoes_observadas: razoes,
+        fatores_credibilidade: fatores,
+        multiplicadores,
+        padrao_credibilidade_total: Some(padrao),
+        variancia_processo: None,
+        variancia_hipoteses: None,
+    };
+}
+
+// Cada idade é tratada como um risco com razão A/E X = D / Ê e peso Ê. Supondo óbitos de
+// Poisson, a variância do processo é s² = 1 e a média coletiva é a própria tábua (μ = 1), de
+// modo que a = (sum(Ê * (X - 1)^2) - n) / sum(Ê) e Z = Ê / (Ê + s² / a).
+pub fn credibilidade_buhlmann_straub_por_idade(
+    experiencia: &Experiencia,
+    tabua_referencia: &Tabua,
+) -> ResultadoCredibilidade {
+    validar_periodicidade(experiencia, tabua_referencia);
+    let esperados = obitos_esperados(experiencia, tabua_referencia);
+    let razoes: Vec<f64> = experiencia
+        .obitos()
+        .iter()
+        .zip(esperados.iter())
+        .map(|(reais, esperados)| razao(*reais, *esperados))
+        .collect();
+
+    let peso_total: f64 = esperados.iter().sum();
+    if peso_total <= 0.0 {
+        panic!("A experiência não possui óbitos esperados pela tábua de referência.");
+    }
+    let quantidade = esperados.iter().filter(|e| **e > 0.0).count() as f64;
+    let dispersao: f64 = esperados
+        .iter()
+        .zip(razoes.iter())
+        .map(|(peso, x)| peso * (x - 1.0).powi(2))
+        .sum();
+    let variancia_processo = 1.0;
+    let variancia_hipoteses = ((dispersao - quantidade * variancia_processo) / peso_total).max(0.0);
+
+    let fatores: Vec<f64> = esperados
+        .iter()
+        .map(|peso| {
+            if variancia_hipoteses == 0.0 {
+                0.0
+            } else {
+                peso / (peso + variancia_processo / variancia_hipoteses)
+            }
+        })
+        .collect();
+    let multiplicadores: Vec<f64> = razoes
+        .iter()
+        .zip(fatores.iter())
+        .map(|(x, z)| z * x + (1.0 - z))
+        .collect();
+
+    return ResultadoCredibilidade {
+        tabua: aplicar_multiplicadores(tabua_referencia, experiencia.idades(), &multiplicadores),
+        idades: experiencia.idades().clone(),
+        razoes_observadas: razoes,
+        fatores_credibilidade: fatores,
+        multiplicadores,
+        padrao_credibilidade_total: None,
+        variancia_processo: Some(variancia_processo),
+        variancia_hipoteses: Some(variancia_hipoteses),
+    };
+}
+
+// Bühlmann-Straub entre blocos (carteiras, canais, anos), com as idades de cada bloco como
+// observações. s² e a são estimados de forma não paramétrica e a média coletiva é ponderada
+// pelos fatores de credibilidade. Cada bloco recebe um multiplicador global sobre a tábua.
+pub fn credibilidade_buhlmann_straub(
+    blocos: &Vec<Experiencia>,
+    tabua_referencia: &Tabua,
+) -> Vec<ResultadoCredibilidade> {
+    if blocos.len() < 2 {
+        panic!("Bühlmann-Straub entre blocos precisa de pelo menos dois blocos.");
+    }
+    let dados: Vec<(Vec<f64>, Vec<f64>)> = blocos
+        .iter()
+        .map(|bloco| {
+            validar_periodicidade(bloco, tabua_referencia);
+            let esperados = obitos_esperados(bloco, tabua_referencia);
+            let razoes = bloco
+                .obitos()
+                .iter()
+                .zip(esperados.iter())
+                .map(|(reais, esperados)| razao(*reais, *esperados))
+                .collect();
+            (esperados, razoes)
+        })
+        .collect();
+
+    let pesos_blocos: Vec<f64> = dados.iter().map(|(pesos, _)| pesos.iter().sum()).collect();
+    if pesos_blocos.iter().any(|peso| *peso <= 0.0) {
+        panic!("Todos os blocos devem possuir óbitos esperados pela tábua de referência.");
+    }
+    let medias_blocos: Vec<f64> = dados
+        .iter()
+        .zip(pesos_blocos.iter())
+        .map(|((pesos, razoes), total)| {
+            pesos
+                .iter()
+                .zip(razoes.iter())
+                .map(|(m, x)| m * x)
+                .sum::<f64>()
+                / total
+        })
+        .collect();
+    let peso_total: f64 = pesos_blocos.iter().sum();
+    let media_geral = pesos_blocos
+        .iter()
+        .zip(medias_blocos.iter())
+        .map(|(m, x)| m * x)
+        .sum::<f64>()
+        / peso_total;
+
+    let (soma_interna, graus_liberdade) = dados.iter().zip(medias_blocos.iter()).fold(
+        (0.0, 0.0),
+        |(soma, gl), ((pesos, razoes), media)| {
+            let observacoes = pesos.iter().filter(|m| **m > 0.0).count() as f64;
+            (
+                soma + pesos
+                    .iter()
+                    .zip(razoes.iter())
+                    .map(|(m, x)| m * (x - media).powi(2))
+                    .sum::<f64>(),
+                gl + (observacoes - 1.0).max(0.0),
+            )
+        },
+    );
+    if graus_liberdade == 0.0 {
+        panic!("Os blocos devem possuir mais de uma idade com óbitos esperados para estimar s².");
+    }
+    let variancia_processo = soma_interna / graus_liberdade;
+    let quantidade_blocos = blocos.len() as f64;
+    let dispersao_entre: f64 = pesos_blocos
+        .iter()
+        .zip(medias_blocos.iter())
+        .map(|(m, x)| m * (x - media_geral).powi(2))
+        .sum();
+    let denominador = peso_total - pesos_blocos.iter().map(|m| m * m).sum::<f64>() / peso_total;
+    let variancia_hipoteses =
+        ((dispersao_entre - (quantidade_blocos - 1.0) * variancia_processo) / denominador).max(0.0);
+
+    let fatores: Vec<f64> = pesos_blocos
+        .iter()
+        .map(|peso| {
+            if variancia_hipoteses == 0.0 {
+                0.0
+            } else {
+                peso / (peso + variancia_processo / variancia_hipoteses)
+            }
+        })
+        .collect();
+    let soma_fatores: f64 = fatores.iter().sum();
+    let media_coletiva = if soma_fatores > 0.0 {
+        fatores
+            .iter()
+            .zip(medias_blocos.iter())
+            .map(|(z, x)| z * x)
+            .sum::<f64>()
+            / soma_fatores
+    } else {
+        media_geral
+    };
+
+    return blocos
+        .iter()
+        .zip(fatores.iter().zip(medias_blocos.iter()))
+        .map(|(bloco, (z, x))| {
+            let multiplicador = z * x + (1.0 - z) * media_coletiva;
+            ResultadoCredibilidade {
+                tabua: tabua_referencia.agravar_forca(multiplicador),
+                idades: bloco.idades().clone(),
+                razoes_observadas: vec![*x],
+                fatores_credibilidade: vec![*z],
+                multiplicadores: vec![multiplicador],
+                padrao_credibilidade_total: None,
+                variancia_processo: Some(variancia_processo),
+                variancia_hipoteses: Some(variancia_hipoteses),
+            }
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Periodicidade;
+    use approx;
+
+    fn criar_tabua() -> Tabua {
+        Tabua::new(
+            (0..10).map(|x| 0.01 + 0.01 * x as f64).collect(),
+            Periodicidade::Anual,
+        )
+    }
+
+    fn criar_experiencia(fator: f64, expostos: f64) -> Experiencia {
+        let tabua = criar_tabua();
+        let idades: Vec<u16> = (2..8).collect();
+        let obitos = idades
+            .iter()
+            .map(|x| fator * expostos * -(1.0 - tabua.qx(&vec![*x], 0)).ln())
+            .collect();
+        Experiencia::new(idades, obitos, vec![expostos; 6], Periodicidade::Anual)
+    }
+
+    #[test]
+    fn flutuacao_limitada_usa_padrao_classico() {
+        let resultado = credibilidade_flutuacao_limitada(
+            &criar_experiencia(1.2, 100.0),
+            &criar_tabua(),
+            0.90,
+            0.05,
+            AplicacaoCredibilidade::MultiplicadorGlobal,
+        );
+
+        approx::assert_relative_eq!(
+            resultado.padrao_credibilidade_total.unwrap(),
+            1082.217,
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn flutuacao_limitada_global_combina_razao_e_tabua() {
+        let experiencia = criar_experiencia(1.2, 100.0);
+        let tabua = criar_tabua();
+
+        let resultado = credibilidade_flutuacao_limitada(
+            &experiencia,
+            &tabua,
+            0.90,
+            0.05,
+            AplicacaoCredibilidade::MultiplicadorGlobal,
+        );
+
+        let obitos: f64 = experiencia.obitos().iter().sum();
+        let z = (obitos / resultado.padrao_credibilidade_total.unwrap()).sqrt();
+        approx::assert_relative_eq!(resultado.fatores_credibilidade[0], z, epsilon = 1e-12);
+        approx::assert_relative_eq!(resultado.razoes_observadas[0], 1.2, epsilon = 1e-12);
+        approx::assert_relative_eq!(
+            resultado.tabua.qx(&vec![4], 0),
+            1.0 - (1.0 - tabua.qx(&vec![4], 0)).powf(z * 1.2 + 1.0 - z),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn flutuacao_limitada_da_credibilidade_total_a_experiencia_grande() {
+        let experiencia = criar_experiencia(1.2, 1e6);
+        let tabua = criar_tabua();
+
+        let resultado = credibilidade_flutuacao_limitada(
+            &experiencia,
+            &tabua,
+            0.90,
+            0.05,
+            AplicacaoCredibilidade::PorIdade,
+        );
+
+        assert!(resultado.fatores_credibilidade.iter().all(|z| *z == 1.0));
+        approx::assert_relative_eq!(
+            resultado.tabua.qx(&vec![7], 0),
+            1.0 - (1.0 - tabua.qx(&vec![7], 0)).powf(1.2),
+            epsilon = 1e-12
+        );
+        approx::assert_relative_eq!(resultado.tabua.qx(&vec![9], 0), tabua.qx(&vec![9], 0));
+    }
+
+    #[test]
+    fn multiplicador_eh_aplicado_a_taxa_central() {
+        let tabua = Tabua::new(vec![0.4, 0.6, 1.0], Periodicidade::Anual);
+        let idades = vec![0, 1];
+        let obitos = idades
+            .iter()
+            .map(|x| 2.0 * 1e6 * -(1.0 - tabua.qx(&vec![*x], 0)).ln())
+            .collect();
+        let experiencia = Experiencia::new(idades, obitos, vec![1e6; 2], Periodicidade::Anual);
+
+        let resultado = credibilidade_flutuacao_limitada(
+            &experiencia,
+            &tabua,
+            0.90,
+            0.05,
+            AplicacaoCredibilidade::PorIdade,
+        );
+
+        // Aplicado ao qx, o multiplicador 2 levaria as duas idades a 1.
+        approx::assert_relative_eq!(resultado.tabua.qx(&vec![0], 0), 0.64, epsilon = 1e-12);
+        approx::assert_relative_eq!(resultado.tabua.qx(&vec![1], 0), 0.84, epsilon = 1e-12);
+        approx::assert_relative_eq!(resultado.tabua.qx(&vec![2], 0), 1.0);
+    }
+
+    #[test]
+    fn buhlmann_straub_por_idade_sem_dispersao_mantem_a_tabua() {
+        let tabua = criar_tabua();
+
+        let resultado =
+            credibilidade_buhlmann_straub_por_idade(&criar_experiencia(1.0, 1000.0), &tabua);
+
+        assert_eq!(resultado.variancia_hipoteses, Some(0.0));
+        assert!(resultado.fatores_credibilidade.iter().all(|z| *z == 0.0));
+        approx::assert_relative_eq!(resultado.tabua.qx(&vec![3], 0), tabua.qx(&vec![3], 0));
+    }
+
+    #[test]
+    fn buhlmann_straub_por_idade_da_mais_credibilidade_a_mais_exposicao() {
+        let tabua = criar_tabua();
+        let experiencia = Experiencia::new(
+            vec![2, 3, 4],
+            vec![60.0, 20.0, 200.0],
+            vec![1000.0, 200.0, 3000.0],
+            Periodicidade::Anual,
+        );
+
+        let resultado = credibilidade_buhlmann_straub_por_idade(&experiencia, &tabua);
+
+        let z = &resultado.fatores_credibilidade;
+        assert!(z[2] > z[0] && z[0] > z[1]);
+        assert!(z.iter().all(|z| *z > 0.0 && *z < 1.0));
+    }
+
+    #[test]
+    fn buhlmann_straub_entre_blocos_puxa_blocos_pequenos_para_a_media() {
+        let tabua = criar_tabua();
+        let mut pequeno = criar_experiencia(1.5, 50.0);
+        let grande = criar_experiencia(0.9, 5000.0);
+        let medio = criar_experiencia(1.1, 800.0);
+        pequeno = Experiencia::new(
+            pequeno.idades().clone(),
+            pequeno
+                .obitos()
+                .iter()
+                .enumerate()
+                .map(|(i, d)| d * (1.0 + 0.3 * (i as f64 - 2.5) / 2.5))
+                .collect(),
+            pequeno.expostos().clone(),
+            Periodicidade::Anual,
+        );
+        let blocos = vec![pequeno, grande, medio];
+
+        let resultados = credibilidade_buhlmann_straub(&blocos, &tabua);
+
+        assert_eq!(resultados.len(), 3);
+        let z: Vec<f64> = resultados
+            .iter()
+            .map(|r| r.fatores_credibilidade[0])
+            .collect();
+        assert!(z[1] > z[2] && z[2] > z[0]);
+        assert!(resultados[0].multiplicadores[0] < resultados[0].razoes_observadas[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pelo menos dois blocos")]
+    fn buhlmann_straub_entre_blocos_precisa_de_dois_blocos() {
+        credibilidade_buhlmann_straub(&vec![criar_experiencia(1.0, 100.0)], &criar_tabua());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::graduacao::{suavizar_media_movel, suavizar_whittaker_henderson};
+use crate::interface::TabuaInterface;
+use crate::tabua_geracional::TabuaGeracional;
+use crate::Periodicidade;
+use crate::Tabua;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuavizacaoMelhoria {
+    Nenhuma,
+    WhittakerHenderson { ordem: usize, suavidade: f64 },
+    MediaMovel { pesos_janela: Vec<f64> },
+}
+
+// Taxas anuais de melhoria por idade, a partir da idade 0, com as idades em períodos da
+// periodicidade, como nas tábuas. Idades além da última informada usam a última taxa.
+#[derive(Debug, Clone)]
+pub struct EscalaMelhoria {
+    taxas: Vec<f64>,
+    periodicidade: Periodicidade,
+}
+
+impl EscalaMelhoria {
+    pub fn new(taxas: Vec<f64>, periodicidade: Periodicidade) -> Self {
+        if taxas.is_empty() {
+            panic!("A escala de melhoria deve possuir pelo menos uma taxa.");
+        }
+        if taxas.iter().any(|taxa| *taxa >= 1.0 || !taxa.is_finite()) {
+            panic!("As taxas de melhoria devem ser finitas e menores que 1.");
+        }
+        return EscalaMelhoria {
+            taxas,
+            periodicidade,
+        };
+    }
+
+    // Taxa implícita entre duas edições: q_posterior = q_anterior * (1 - AA)^(anos), ou seja,
+    // AA = 1 - (q_posterior / q_anterior)^(1 / anos). Idades com qx nulo ou igual a 1 em alguma
+    // das tábuas, ou anteriores às idades iniciais, ficam sem melhoria e não participam da
+    // suavização; com q_posterior nulo a taxa seria 1, que não pode ser projetada.
+    pub fn a_partir_de_tabuas(
+        tabua_anterior: &Tabua,
+        ano_anterior: i32,
+        tabua_posterior: &Tabua,
+        ano_posterior: i32,
+        suavizacao: SuavizacaoMelhoria,
+    ) -> Self {
+        if ano_posterior <= ano_anterior {
+            panic!("O ano base da tábua posterior deve ser maior que o da tábua anterior.");
+        }
+        if tabua_anterior.periodicidade() != tabua_posterior.periodicidade() {
+            panic!("Todas as tabuas devem possuir a mesma periodicidade.");
+        }
+        let anos = (ano_posterior - ano_anterior) as f64;
+        let idade_inicial = tabua_anterior
+            .idade_inicial()
+            .max(tabua_posterior.idade_inicial());
+        let idade_limite = tabua_anterior
+            .obter_tabua_base()
+            .idade_limite()
+            .max(tabua_posterior.obter_tabua_base().idade_limite());
+
+        // None nas idades sem melhoria, que não participam da suavização.
+        let taxas_calculadas: Vec<Option<f64>> = (0..idade_limite as u16)
+            .map(|x| {
+                if x < idade_inicial {
+                    return None;
+                }
+                let q_anterior = tabua_anterior.qx(&vec![x], 0);
+                let q_posterior = tabua_posterior.qx(&vec![x], 0);
+                if q_anterior <= 0.0
+                    || q_anterior >= 1.0
+                    || q_posterior <= 0.0
+                    || q_posterior >= 1.0
+                {
+                    None
+                } else {
+                    Some(1.0 - (q_posterior / q_anterior).powf(1.0 / anos))
+                }
+            })
+            .collect();
+        let taxas: Vec<f64> = taxas_calculadas.iter().map(|t| t.unwrap_or(0.0)).collect();
+        let pesos: Vec<f64> = taxas_calculadas
+            .iter()
+            .map(|t| if t.is_some() { 1.0 } else { 0.0 })
+            .collect();
+        let quantidade_taxas = taxas_calculadas.iter().filter(|t| t.is_some()).count();
+
+        let taxas = match suavizacao {
+            SuavizacaoMelhoria::Nenhuma => taxas,
+            SuavizacaoMelhoria::WhittakerHenderson { ordem, suavidade } => {
+                if ordem == 0 || ordem >= quantidade_taxas || suavidade < 0.0 {
+                    panic!(
+                        "Parâmetros de Whittaker-Henderson inválidos para a escala de melhoria."
+                    );
+                }
+                // Sem penalidade a solução são as próprias taxas, e as idades de peso zero
+                // tornariam o sistema singular.
+                if suavidade == 0.0 {
+                    taxas
+                } else {
+                    suavizar_whittaker_henderson(&taxas, &pesos, ordem, suavidade)
+                }
+            }
+            SuavizacaoMelhoria::MediaMovel { pesos_janela } => {
+                if pesos_janela.len().is_multiple_of(2) {
+                    panic!("A janela da média móvel deve possuir uma quantidade ímpar de pesos.");
+                }
+                suavizar_media_movel(&taxas, &pesos, &pesos_janela)
+            }
+        };
+        let taxas = taxas
+            .iter()
+            .zip(pesos.iter())
+            .map(|(taxa, peso)| if *peso > 0.0 { *taxa } else { 0.0 })
+            .collect();
+
+        return EscalaMelhoria::new(taxas, tabua_anterior.periodicidade().clone());
+    }
+
+    pub fn taxas(&self) -> &Vec<f64> {
+        return &self.taxas;
+    }
+
+    pub fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    pub fn taxa(&self, idade: u16) -> f64 {
+        return self.taxas[(idade as usize).min(self.taxas.len() - 1)];
+    }
+
+    // qx(x, ano) = qx_base(x) * (1 - AA(x))^(ano - ano_base), limitado a 1. Idades com qx igual
+    // a 1 na tábua base não são alteradas.
+    pub fn projetar(&self, tabua_base: &Tabua, anos_decorridos: i32) -> Tabua {
+        if tabua_base.periodicidade() != &self.periodicidade {
+            panic!("A tábua base e a escala de melhoria devem possuir a mesma periodicidade.");
+        }
+        let tabua_base = tabua_base.obter_tabua_base();
+        let qx = tabua_base
+            .qx
+            .iter()
+            .enumerate()
+            .map(|(indice, q)| {
+                let x = tabua_base.idade_inicial() + indice as u16;
+                if *q == 1.0 {
+                    *q
+                } else {
+                    (q * (1.0 - self.taxa(x)).powi(anos_decorridos)).min(1.0)
+                }
+            })
+            .collect();
+        return Tabua::a_partir_da_tabua_base(tabua_base.com_qx(qx), self.periodicidade.clone());
+    }
+
+    // Uma tábua de período por ano entre ano_base e ano_final; anos posteriores repetem a última.
+    pub fn tabua_geracional(
+        &self,
+        tabua_base: &Tabua,
+        ano_base: i32,
+        ano_final: i32,
+        ano_avaliacao: i32,
+    ) -> TabuaGeracional {
+        if ano_final < ano_base {
+            panic!("O ano final da projeção não pode ser anterior ao ano base.");
+        }
+        let tabuas = (ano_base..=ano_final)
+            .map(|ano| self.projetar(tabua_base, ano - ano_base))
+            .collect();
+        return TabuaGeracional::new(tabuas, ano_base, ano_avaliacao);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    fn criar_tabuas() -> (Tabua, Tabua) {
+        let anterior: Vec<f64> = (0..6).map(|x| 0.01 * (1.0 + x as f64)).collect();
+        let posterior = anterior
+            .iter()
+            .enumerate()
+            .map(|(x, q)| q * (1.0 - 0.01 * x as f64).powi(5))
+            .collect();
+        (
+            Tabua::new(anterior, Periodicidade::Anual),
+            Tabua::new(posterior, Periodicidade::Anual),
+        )
+    }
+
+    #[test]
+    fn taxas_implicitas_reproduzem_a_tabua_posterior() {
+        let (anterior, posterior) = criar_tabuas();
+
+        let escala = EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2010,
+            &posterior,
+            2015,
+            SuavizacaoMelhoria::Nenhuma,
+        );
+
+        for x in 0..6 {
+            approx::assert_abs_diff_eq!(escala.taxa(x), 0.01 * x as f64, epsilon = 1e-12);
+        }
+        let projetada = escala.projetar(&anterior, 5);
+        approx::assert_relative_eq!(
+            projetada.qx(&vec![4], 0),
+            posterior.qx(&vec![4], 0),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn qx_posterior_nulo_fica_sem_melhoria() {
+        let anterior = Tabua::new(vec![0.01, 0.02, 0.03], Periodicidade::Anual);
+        let posterior = Tabua::new(vec![0.0, 0.019, 0.03], Periodicidade::Anual);
+
+        let escala = EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2010,
+            &posterior,
+            2011,
+            SuavizacaoMelhoria::Nenhuma,
+        );
+
+        assert_eq!(escala.taxa(0), 0.0);
+        approx::assert_abs_diff_eq!(escala.taxa(1), 0.05, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn suavizacao_preserva_taxas_lineares() {
+        let (anterior, posterior) = criar_tabuas();
+
+        let escala = EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2010,
+            &posterior,
+            2015,
+            SuavizacaoMelhoria::WhittakerHenderson {
+                ordem: 2,
+                suavidade: 100.0,
+            },
+        );
+
+        approx::assert_abs_diff_eq!(escala.taxa(3), 0.03, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn suavizacao_ignora_idades_anteriores_a_idade_inicial() {
+        let anterior: Vec<f64> = (0..6).map(|i| 0.01 * (1.0 + i as f64)).collect();
+        let posterior = anterior
+            .iter()
+            .enumerate()
+            .map(|(i, q)| q * (1.0 - (0.02 + 0.01 * i as f64)).powi(5))
+            .collect();
+        let criar_tabua = |qx| {
+            Tabua::a_partir_da_idade(
+                18,
+                qx,
+                Periodicidade::Anual,
+                crate::RegraIdadeInferior::Erro,
+            )
+        };
+        let (anterior, posterior) = (criar_tabua(anterior), criar_tabua(posterior));
+        let escala = |suavizacao| {
+            EscalaMelhoria::a_partir_de_tabuas(&anterior, 2010, &posterior, 2015, suavizacao)
+        };
+
+        let whittaker_henderson = escala(SuavizacaoMelhoria::WhittakerHenderson {
+            ordem: 2,
+            suavidade: 100.0,
+        });
+        let media_movel = escala(SuavizacaoMelhoria::MediaMovel {
+            pesos_janela: vec![1.0, 1.0, 1.0],
+        });
+
+        assert_eq!(whittaker_henderson.taxa(17), 0.0);
+        approx::assert_abs_diff_eq!(whittaker_henderson.taxa(18), 0.02, epsilon = 1e-10);
+        assert_eq!(media_movel.taxa(17), 0.0);
+        approx::assert_abs_diff_eq!(media_movel.taxa(18), 0.025, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn media_movel_suaviza_taxas() {
+        let anterior = Tabua::new(vec![0.01; 3], Periodicidade::Anual);
+        let posterior = Tabua::new(vec![0.01, 0.01 * 0.9, 0.01], Periodicidade::Anual);
+
+        let escala = EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2010,
+            &posterior,
+            2011,
+            SuavizacaoMelhoria::MediaMovel {
+                pesos_janela: vec![1.0, 1.0, 1.0],
+            },
+        );
+
+        approx::assert_abs_diff_eq!(escala.taxa(0), 0.05, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(escala.taxa(1), 0.1 / 3.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn tabua_geracional_aplica_a_melhoria_pela_coorte() {
+        let (anterior, posterior) = criar_tabuas();
+        let escala = EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2010,
+            &posterior,
+            2015,
+            SuavizacaoMelhoria::Nenhuma,
+        );
+
+        let tabua = escala.tabua_geracional(&posterior, 2015, 2040, 2020);
+
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![2], 1),
+            posterior.qx(&vec![3], 0) * 0.97_f64.powi(6),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "O ano base da tábua posterior deve ser maior")]
+    fn anos_base_devem_ser_crescentes() {
+        let (anterior, posterior) = criar_tabuas();
+        EscalaMelhoria::a_partir_de_tabuas(
+            &anterior,
+            2015,
+            &posterior,
+            2010,
+            SuavizacaoMelhoria::Nenhuma,
+        );
+    }
+}
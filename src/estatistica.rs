@@ -0,0 +1,237 @@
+// Aproximação de Lanczos (g = 7, n = 9), com erro relativo da ordem de 1e-15.
+pub(crate) fn ln_gama(x: f64) -> f64 {
+    const COEFICIENTES: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gama(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let soma = COEFICIENTES[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFICIENTES[0], |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+    return 0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + soma.ln();
+}
+
+pub(crate) fn regressao_linear(x: &Vec<f64>, y: &Vec<f64>, pesos: &Vec<f64>) -> (f64, f64) {
+    let soma_pesos: f64 = pesos.iter().sum();
+    if soma_pesos <= 0.0 {
+        panic!("A regressão linear precisa de pelo menos um ponto com peso positivo.");
+    }
+    let media_x = x.iter().zip(pesos.iter()).map(|(x, w)| x * w).sum::<f64>() / soma_pesos;
+    let media_y = y.iter().zip(pesos.iter()).map(|(y, w)| y * w).sum::<f64>() / soma_pesos;
+    let (covariancia, variancia) =
+        x.iter()
+            .zip(y.iter())
+            .zip(pesos.iter())
+            .fold((0.0, 0.0), |(cov, var), ((x, y), w)| {
+                (
+                    cov + w * (x - media_x) * (y - media_y),
+                    var + w * (x - media_x).powi(2),
+                )
+            });
+    if variancia == 0.0 {
+        return (media_y, 0.0);
+    }
+    let inclinacao = covariancia / variancia;
+    return (media_y - inclinacao * media_x, inclinacao);
+}
+
+// Função gama incompleta inferior regularizada P(a, x), pela série para x < a + 1 e pela fração
+// contínua de Lentz caso contrário.
+pub(crate) fn gama_incompleta_regularizada(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x.is_infinite() {
+        return 1.0;
+    }
+    let ln_prefixo = a * x.ln() - x - ln_gama(a);
+    if x < a + 1.0 {
+        let mut termo = 1.0 / a;
+        let mut soma = termo;
+        let mut n = a;
+        for _ in 0..10000 {
+            n += 1.0;
+            termo *= x / n;
+            soma += termo;
+            if termo.abs() < soma.abs() * 1e-16 {
+                break;
+            }
+        }
+        return (soma * ln_prefixo.exp()).min(1.0);
+    }
+    let minimo = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / minimo;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..10000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < minimo {
+            d = minimo;
+        }
+        c = b + an / c;
+        if c.abs() < minimo {
+            c = minimo;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-16 {
+            break;
+        }
+    }
+    return (1.0 - ln_prefixo.exp() * h).max(0.0);
+}
+
+pub(crate) fn cdf_qui_quadrado(x: f64, graus_liberdade: f64) -> f64 {
+    return gama_incompleta_regularizada(graus_liberdade / 2.0, x / 2.0);
+}
+
+pub(crate) fn quantil_qui_quadrado(p: f64, graus_liberdade: f64) -> f64 {
+    if !(0.0..1.0).contains(&p) {
+        panic!("A probabilidade do quantil deve estar em [0, 1), obtido = {p}.");
+    }
+    if p == 0.0 {
+        return 0.0;
+    }
+    let mut inferior = 0.0;
+    let mut superior = graus_liberdade.max(1.0);
+    while cdf_qui_quadrado(superior, graus_liberdade) < p {
+        superior *= 2.0;
+    }
+    for _ in 0..200 {
+        let meio = 0.5 * (inferior + superior);
+        if cdf_qui_quadrado(meio, graus_liberdade) < p {
+            inferior = meio;
+        } else {
+            superior = meio;
+        }
+        if superior - inferior < 1e-12 * superior {
+            break;
+        }
+    }
+    return 0.5 * (inferior + superior);
+}
+
+pub(crate) fn cdf_normal(x: f64) -> f64 {
+    let erf = gama_incompleta_regularizada(0.5, x * x / 2.0);
+    return if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    };
+}
+
+pub(crate) fn quantil_normal(p: f64) -> f64 {
+    if !(0.0..=1.0).contains(&p) {
+        panic!("A probabilidade do quantil deve estar em [0, 1], obtido = {p}.");
+    }
+    if p == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut inferior = -40.0;
+    let mut superior = 40.0;
+    for _ in 0..200 {
+        let meio = 0.5 * (inferior + superior);
+        if cdf_normal(meio) < p {
+            inferior = meio;
+        } else {
+            superior = meio;
+        }
+    }
+    return 0.5 * (inferior + superior);
+}
+
+pub(crate) fn cdf_binomial(k: u64, n: u64, p: f64) -> f64 {
+    return (0..=k.min(n))
+        .map(|i| {
+            let (i, n) = (i as f64, n as f64);
+            (ln_gama(n + 1.0) - ln_gama(i + 1.0) - ln_gama(n - i + 1.0)
+                + i * p.ln()
+                + (n - i) * (1.0 - p).ln())
+            .exp()
+        })
+        .sum::<f64>()
+        .min(1.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn ln_gama_coincide_com_fatorial() {
+        approx::assert_relative_eq!(ln_gama(1.0), 0.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(ln_gama(5.0), 24.0_f64.ln(), epsilon = 1e-12);
+        approx::assert_relative_eq!(
+            ln_gama(0.5),
+            std::f64::consts::PI.sqrt().ln(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn regressao_linear_recupera_reta() {
+        let x = vec![1.0, 2.0, 3.0, 4.0];
+        let y = x.iter().map(|x| 2.0 + 0.5 * x).collect();
+
+        let (intercepto, inclinacao) = regressao_linear(&x, &y, &vec![1.0; 4]);
+
+        approx::assert_relative_eq!(intercepto, 2.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(inclinacao, 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn quantil_qui_quadrado_coincide_com_valores_tabelados() {
+        approx::assert_relative_eq!(
+            quantil_qui_quadrado(0.95, 1.0),
+            3.841458820694124,
+            epsilon = 1e-8
+        );
+        approx::assert_relative_eq!(
+            quantil_qui_quadrado(0.975, 10.0),
+            20.483177350807388,
+            epsilon = 1e-8
+        );
+        approx::assert_relative_eq!(
+            quantil_qui_quadrado(0.025, 2.0),
+            0.050635615968579795,
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn cdf_e_quantil_normal_funcionam() {
+        approx::assert_relative_eq!(cdf_normal(0.0), 0.5, epsilon = 1e-14);
+        approx::assert_relative_eq!(cdf_normal(1.959963984540054), 0.975, epsilon = 1e-12);
+        approx::assert_relative_eq!(quantil_normal(0.975), 1.959963984540054, epsilon = 1e-9);
+        approx::assert_relative_eq!(quantil_normal(0.1), -1.2815515655446004, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn cdf_binomial_funciona() {
+        approx::assert_relative_eq!(cdf_binomial(2, 4, 0.5), 11.0 / 16.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(cdf_binomial(4, 4, 0.5), 1.0, epsilon = 1e-12);
+    }
+}
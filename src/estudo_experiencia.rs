@@ -0,0 +1,555 @@
+use crate::experiencia::Experiencia;
+use crate::tabua::RegraIdadeInferior;
+use crate::HipoteseMdt;
+use crate::Periodicidade;
+use crate::Tabua;
+use crate::TabuaMDT;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Data {
+    ano: i32,
+    mes: u8,
+    dia: u8,
+}
+
+fn ano_bissexto(ano: i32) -> bool {
+    return (ano % 4 == 0 && ano % 100 != 0) || ano % 400 == 0;
+}
+
+fn dias_no_mes(ano: i32, mes: u8) -> u8 {
+    return match mes {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        _ => {
+            if ano_bissexto(ano) {
+                29
+            } else {
+                28
+            }
+        }
+    };
+}
+
+impl Data {
+    pub fn new(ano: i32, mes: u8, dia: u8) -> Self {
+        if !(1..=12).contains(&mes) || dia == 0 || dia > dias_no_mes(ano, mes) {
+            panic!("Data inválida: {ano}-{mes}-{dia}.");
+        }
+        return Data { ano, mes, dia };
+    }
+
+    // Quantidade de dias desde 1970-01-01 no calendário gregoriano proléptico.
+    pub fn dias_desde_epoca(&self) -> i64 {
+        let ano = if self.mes <= 2 {
+            self.ano as i64 - 1
+        } else {
+            self.ano as i64
+        };
+        let era = ano.div_euclid(400);
+        let ano_da_era = ano - era * 400;
+        let mes = self.mes as i64;
+        let dia_do_ano =
+            (153 * (if mes > 2 { mes - 3 } else { mes + 9 }) + 2) / 5 + self.dia as i64 - 1;
+        let dia_da_era = ano_da_era * 365 + ano_da_era / 4 - ano_da_era / 100 + dia_do_ano;
+        return era * 146097 + dia_da_era - 719468;
+    }
+
+    pub fn somar_meses(&self, meses: i64) -> Self {
+        let total = self.ano as i64 * 12 + (self.mes as i64 - 1) + meses;
+        let ano = total.div_euclid(12) as i32;
+        let mes = (total.rem_euclid(12) + 1) as u8;
+        return Data::new(ano, mes, self.dia.min(dias_no_mes(ano, mes)));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BaseIdade {
+    UltimoAniversario,
+    AniversarioMaisProximo,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetodoExposicao {
+    Central,
+    Inicial,
+}
+
+// Uma saída sem causa é censurada. As saídas com causa devem usar uma das causas do estudo.
+#[derive(Debug, Clone)]
+pub struct RegistroApolice {
+    pub nascimento: Data,
+    pub entrada: Data,
+    pub saida: Option<Data>,
+    pub causa_saida: Option<String>,
+}
+
+impl RegistroApolice {
+    pub fn new(
+        nascimento: Data,
+        entrada: Data,
+        saida: Option<Data>,
+        causa_saida: Option<String>,
+    ) -> Self {
+        if entrada < nascimento {
+            panic!("A data de entrada não pode ser anterior ao nascimento.");
+        }
+        if let Some(saida) = saida {
+            if saida < entrada {
+                panic!("A data de saída não pode ser anterior à entrada.");
+            }
+        }
+        if saida.is_none() && causa_saida.is_some() {
+            panic!("Registros sem data de saída não podem possuir causa de saída.");
+        }
+        return RegistroApolice {
+            nascimento,
+            entrada,
+            saida,
+            causa_saida,
+        };
+    }
+}
+
+// Exposições e eventos por idade. As idades são contadas em períodos da periodicidade do estudo:
+// a parte inteira é a quantidade de aniversários de período completados no calendário e a parte
+// fracionária é proporcional aos dias decorridos até o próximo aniversário.
+#[derive(Debug, Clone)]
+pub struct EstudoExperiencia {
+    idades: Vec<u16>,
+    causas: Vec<String>,
+    expostos_centrais: Vec<f64>,
+    expostos_iniciais: Vec<Vec<f64>>,
+    eventos: Vec<Vec<f64>>,
+    periodicidade: Periodicidade,
+}
+
+fn idade_em_periodos(nascimento: &Data, data: &Data, meses_por_periodo: i64) -> f64 {
+    let meses = (data.ano - nascimento.ano) as i64 * 12 + data.mes as i64 - nascimento.mes as i64;
+    let mut periodos = meses.div_euclid(meses_por_periodo);
+    let mut aniversario = nascimento.somar_meses(periodos * meses_por_periodo);
+    if aniversario > *data {
+        periodos -= 1;
+        aniversario = nascimento.somar_meses(periodos * meses_por_periodo);
+    }
+    let proximo_aniversario = nascimento.somar_meses((periodos + 1) * meses_por_periodo);
+    let decorrido = (data.dias_desde_epoca() - aniversario.dias_desde_epoca()) as f64;
+    let duracao = (proximo_aniversario.dias_desde_epoca() - aniversario.dias_desde_epoca()) as f64;
+    return periodos as f64 + decorrido / duracao;
+}
+
+impl EstudoExperiencia {
+    pub fn new(
+        registros: &Vec<RegistroApolice>,
+        inicio_estudo: Data,
+        fim_estudo: Data,
+        causas: Vec<String>,
+        base_idade: BaseIdade,
+        periodicidade: Periodicidade,
+    ) -> Self {
+        if fim_estudo <= inicio_estudo {
+            panic!("O fim do estudo deve ser posterior ao início.");
+        }
+        if causas.is_empty() {
+            panic!("O estudo deve possuir pelo menos uma causa de saída.");
+        }
+        for registro in registros.iter() {
+            if let Some(causa) = &registro.causa_saida {
+                if !causas.contains(causa) {
+                    panic!(
+                        "Causa de saída desconhecida: {causa}. Saídas censuradas devem ter \
+                         causa_saida None."
+                    );
+                }
+            }
+        }
+
+        let meses_por_periodo = 12 / periodicidade.quantidade_periodos_1_ano() as i64;
+        let deslocamento = match base_idade {
+            BaseIdade::UltimoAniversario => 0.0,
+            BaseIdade::AniversarioMaisProximo => 0.5,
+        };
+
+        // (idade inicial, idade final, índice da causa se a saída for um evento no período)
+        let intervalos: Vec<(f64, f64, Option<usize>)> = registros
+            .iter()
+            .filter_map(|registro| {
+                let inicio = registro.entrada.max(inicio_estudo);
+                let (fim, causa) = match registro.saida {
+                    Some(saida) if saida <= fim_estudo => (
+                        saida,
+                        registro.causa_saida.as_ref().map(|c| {
+                            causas
+                                .iter()
+                                .position(|causa| causa == c)
+                                .expect("As causas dos registros já foram validadas.")
+                        }),
+                    ),
+                    _ => (fim_estudo, None),
+                };
+                if fim < inicio || (fim == inicio && causa.is_none()) {
+                    return None;
+                }
+                let idade_inicio =
+                    idade_em_periodos(&registro.nascimento, &inicio, meses_por_periodo)
+                        + deslocamento;
+                let idade_fim =
+                    idade_em_periodos(&registro.nascimento, &fim, meses_por_periodo) + deslocamento;
+                Some((idade_inicio, idade_fim, causa))
+            })
+            .collect();
+
+        if intervalos.is_empty() {
+            panic!("Nenhum registro possui exposição dentro do período do estudo.");
+        }
+
+        let idade_minima = intervalos
+            .iter()
+            .map(|(inicio, _, _)| inicio.floor() as u16)
+            .min()
+            .expect("Deveria existir pelo menos um intervalo de exposição.");
+        // Uma saída censurada exatamente no aniversário não gera exposição na nova idade.
+        let idade_maxima = intervalos
+            .iter()
+            .map(|(inicio, fim, causa)| match causa {
+                Some(_) => fim.floor() as u16,
+                None => (fim.ceil() - 1.0).max(inicio.floor()) as u16,
+            })
+            .max()
+            .expect("Deveria existir pelo menos um intervalo de exposição.");
+        let tamanho = (idade_maxima - idade_minima + 1) as usize;
+
+        let mut expostos_centrais = vec![0.0; tamanho];
+        let mut extensoes = vec![vec![0.0; tamanho]; causas.len()];
+        let mut eventos = vec![vec![0.0; tamanho]; causas.len()];
+
+        for (idade_inicio, idade_fim, causa) in intervalos {
+            let mut idade = idade_inicio;
+            while idade < idade_fim {
+                let inteira = idade.floor();
+                let proxima = (inteira + 1.0).min(idade_fim);
+                expostos_centrais[inteira as usize - idade_minima as usize] += proxima - idade;
+                idade = proxima;
+            }
+            if let Some(j) = causa {
+                let indice = idade_fim.floor() as usize - idade_minima as usize;
+                eventos[j][indice] += 1.0;
+                extensoes[j][indice] += idade_fim.floor() + 1.0 - idade_fim;
+            }
+        }
+
+        let expostos_iniciais = extensoes
+            .iter()
+            .map(|extensao| {
+                extensao
+                    .iter()
+                    .zip(expostos_centrais.iter())
+                    .map(|(e, c)| e + c)
+                    .collect()
+            })
+            .collect();
+
+        return EstudoExperiencia {
+            idades: (idade_minima..=idade_maxima).collect(),
+            causas,
+            expostos_centrais,
+            expostos_iniciais,
+            eventos,
+            periodicidade,
+        };
+    }
+
+    fn indice_causa(&self, causa: &str) -> usize {
+        return self
+            .causas
+            .iter()
+            .position(|c| c == causa)
+            .unwrap_or_else(|| panic!("Causa de saída desconhecida: {causa}."));
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn causas(&self) -> &Vec<String> {
+        return &self.causas;
+    }
+
+    pub fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    pub fn expostos_centrais(&self) -> &Vec<f64> {
+        return &self.expostos_centrais;
+    }
+
+    // Exposição central acrescida, para cada evento da causa, do tempo entre o evento e o fim do
+    // intervalo de idade.
+    pub fn expostos_iniciais(&self, causa: &str) -> &Vec<f64> {
+        return &self.expostos_iniciais[self.indice_causa(causa)];
+    }
+
+    pub fn eventos(&self, causa: &str) -> &Vec<f64> {
+        return &self.eventos[self.indice_causa(causa)];
+    }
+
+    pub fn experiencia(&self, causa: &str) -> Experiencia {
+        return Experiencia::new(
+            self.idades.clone(),
+            self.eventos(causa).clone(),
+            self.expostos_centrais.clone(),
+            self.periodicidade.clone(),
+        );
+    }
+
+    // Taxas brutas de decremento simples associado à causa. Pela exposição central, assume-se
+    // força constante em cada idade (q = 1 - exp(-D / Ec)); pela inicial, q = D / Ei.
+    pub fn taxas_brutas(&self, causa: &str, metodo: MetodoExposicao) -> Vec<f64> {
+        let eventos = self.eventos(causa);
+        let expostos = match metodo {
+            MetodoExposicao::Central => &self.expostos_centrais,
+            MetodoExposicao::Inicial => self.expostos_iniciais(causa),
+        };
+        return eventos
+            .iter()
+            .zip(expostos.iter())
+            .map(|(d, e)| {
+                if *e == 0.0 {
+                    0.0
+                } else {
+                    match metodo {
+                        MetodoExposicao::Central => 1.0 - (-d / e).exp(),
+                        MetodoExposicao::Inicial => (d / e).min(1.0),
+                    }
+                }
+            })
+            .collect();
+    }
+
+    pub fn tabua(&self, causa: &str, metodo: MetodoExposicao) -> Tabua {
+        return Tabua::a_partir_da_idade(
+            self.idades[0],
+            self.taxas_brutas(causa, metodo),
+            self.periodicidade.clone(),
+            RegraIdadeInferior::Erro,
+        );
+    }
+
+    pub fn tabua_mdt(&self, metodo: MetodoExposicao, hipotese: HipoteseMdt) -> TabuaMDT {
+        let tabuas = self
+            .causas
+            .iter()
+            .map(|causa| self.tabua(causa, metodo.clone()))
+            .collect();
+        return TabuaMDT::new(tabuas, hipotese).com_nomes_decrementos(self.causas.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::TabuaInterface;
+    use approx;
+
+    fn causas() -> Vec<String> {
+        vec!["morte".to_string(), "cancelamento".to_string()]
+    }
+
+    fn criar_registros() -> Vec<RegistroApolice> {
+        vec![
+            RegistroApolice::new(Data::new(1980, 1, 1), Data::new(2010, 1, 1), None, None),
+            RegistroApolice::new(
+                Data::new(1980, 7, 2),
+                Data::new(2009, 1, 1),
+                Some(Data::new(2010, 10, 1)),
+                Some("morte".to_string()),
+            ),
+            RegistroApolice::new(
+                Data::new(1979, 7, 2),
+                Data::new(2010, 1, 1),
+                Some(Data::new(2010, 4, 1)),
+                Some("cancelamento".to_string()),
+            ),
+        ]
+    }
+
+    fn criar_estudo(base_idade: BaseIdade) -> EstudoExperiencia {
+        EstudoExperiencia::new(
+            &criar_registros(),
+            Data::new(2010, 1, 1),
+            Data::new(2011, 1, 1),
+            causas(),
+            base_idade,
+            Periodicidade::Anual,
+        )
+    }
+
+    fn idade(nascimento: Data, data: Data) -> f64 {
+        idade_em_periodos(&nascimento, &data, 12)
+    }
+
+    #[test]
+    fn dias_desde_epoca_funciona() {
+        assert_eq!(Data::new(1970, 1, 1).dias_desde_epoca(), 0);
+        assert_eq!(Data::new(2000, 3, 1).dias_desde_epoca(), 11017);
+        assert_eq!(Data::new(1969, 12, 31).dias_desde_epoca(), -1);
+    }
+
+    #[test]
+    fn somar_meses_ajusta_o_fim_do_mes() {
+        assert_eq!(
+            Data::new(2011, 1, 31).somar_meses(1),
+            Data::new(2011, 2, 28)
+        );
+        assert_eq!(
+            Data::new(2011, 1, 31).somar_meses(-2),
+            Data::new(2010, 11, 30)
+        );
+        assert_eq!(
+            Data::new(2011, 12, 15).somar_meses(13),
+            Data::new(2013, 1, 15)
+        );
+    }
+
+    #[test]
+    fn idade_em_periodos_eh_inteira_nos_aniversarios() {
+        let nascimento = Data::new(1980, 7, 2);
+
+        assert_eq!(
+            idade_em_periodos(&nascimento, &Data::new(2010, 7, 2), 12),
+            30.0
+        );
+        assert_eq!(
+            idade_em_periodos(&nascimento, &Data::new(2010, 8, 2), 1),
+            361.0
+        );
+        approx::assert_relative_eq!(
+            idade_em_periodos(&nascimento, &Data::new(2010, 1, 1), 12),
+            29.0 + 183.0 / 365.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Data inválida")]
+    fn data_invalida_da_erro() {
+        Data::new(2011, 2, 29);
+    }
+
+    #[test]
+    fn exposicao_central_total_eh_o_tempo_observado() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        let total_esperado = 1.0
+            + (idade(Data::new(1980, 7, 2), Data::new(2010, 10, 1))
+                - idade(Data::new(1980, 7, 2), Data::new(2010, 1, 1)))
+            + (idade(Data::new(1979, 7, 2), Data::new(2010, 4, 1))
+                - idade(Data::new(1979, 7, 2), Data::new(2010, 1, 1)));
+
+        approx::assert_relative_eq!(
+            estudo.expostos_centrais().iter().sum::<f64>(),
+            total_esperado,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn eventos_sao_contados_na_idade_da_saida() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        assert_eq!(estudo.idades(), &vec![29, 30]);
+        assert_eq!(estudo.eventos("morte"), &vec![0.0, 1.0]);
+        assert_eq!(estudo.eventos("cancelamento"), &vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn exposicao_inicial_estende_eventos_ate_o_fim_da_idade() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        let extensao_morte = 31.0 - idade(Data::new(1980, 7, 2), Data::new(2010, 10, 1));
+
+        approx::assert_relative_eq!(
+            estudo.expostos_iniciais("morte")[1] - estudo.expostos_centrais()[1],
+            extensao_morte,
+            epsilon = 1e-9
+        );
+        approx::assert_relative_eq!(
+            estudo.expostos_iniciais("morte")[0],
+            estudo.expostos_centrais()[0]
+        );
+    }
+
+    #[test]
+    fn base_aniversario_mais_proximo_desloca_as_idades() {
+        let estudo = criar_estudo(BaseIdade::AniversarioMaisProximo);
+
+        assert_eq!(estudo.idades(), &vec![30, 31]);
+        assert_eq!(estudo.eventos("morte"), &vec![1.0, 0.0]);
+        assert_eq!(estudo.eventos("cancelamento"), &vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn periodicidade_mensal_conta_idades_em_meses() {
+        let estudo = EstudoExperiencia::new(
+            &criar_registros(),
+            Data::new(2010, 1, 1),
+            Data::new(2011, 1, 1),
+            causas(),
+            BaseIdade::UltimoAniversario,
+            Periodicidade::Mensal,
+        );
+
+        assert_eq!(estudo.idades()[0], 29 * 12 + 5);
+        assert_eq!(estudo.eventos("morte")[30 * 12 + 2 - (29 * 12 + 5)], 1.0);
+        approx::assert_relative_eq!(
+            estudo.expostos_centrais().iter().sum::<f64>(),
+            12.0 + 9.0 + 3.0,
+            epsilon = 0.05
+        );
+    }
+
+    #[test]
+    fn taxas_brutas_alimentam_tabua_e_tabua_mdt() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        let taxas = estudo.taxas_brutas("morte", MetodoExposicao::Inicial);
+        let tabua = estudo.tabua("morte", MetodoExposicao::Inicial);
+        let tabua_mdt = estudo.tabua_mdt(MetodoExposicao::Central, HipoteseMdt::UddTabuasSimples);
+
+        approx::assert_relative_eq!(taxas[1], 1.0 / estudo.expostos_iniciais("morte")[1]);
+        approx::assert_relative_eq!(tabua.qx(&vec![30], 0), taxas[1]);
+        assert_eq!(tabua_mdt.numero_decrementos(), 2);
+    }
+
+    #[test]
+    fn experiencia_usa_exposicao_central() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        let experiencia = estudo.experiencia("cancelamento");
+
+        assert_eq!(experiencia.obitos(), estudo.eventos("cancelamento"));
+        assert_eq!(experiencia.expostos(), estudo.expostos_centrais());
+    }
+
+    #[test]
+    #[should_panic(expected = "Causa de saída desconhecida: Morte.")]
+    fn registro_com_causa_fora_do_estudo_da_erro() {
+        let mut registros = criar_registros();
+        registros[1].causa_saida = Some("Morte".to_string());
+
+        EstudoExperiencia::new(
+            &registros,
+            Data::new(2010, 1, 1),
+            Data::new(2011, 1, 1),
+            causas(),
+            BaseIdade::UltimoAniversario,
+            Periodicidade::Anual,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Causa de saída desconhecida")]
+    fn causa_desconhecida_da_erro() {
+        let estudo = criar_estudo(BaseIdade::UltimoAniversario);
+
+        estudo.eventos("invalidez");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::interface::TabuaInterface;
+use infinitable::Infinitable::{Finite, Infinity, NegativeInfinity};
+
+// Tempo de vida em períodos da tábua e o equivalente em anos.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoVida {
+    pub periodos: f64,
+    pub anos: f64,
+}
+
+impl TempoVida {
+    fn new<T: TabuaInterface>(tabua: &T, periodos: f64) -> Self {
+        return TempoVida {
+            periodos,
+            anos: periodos / tabua.periodicidade().quantidade_periodos_1_ano() as f64,
+        };
+    }
+}
+
+// tpx para t = 0, 1, ... até o tempo futuro máximo ou, se ele for infinito, até o primeiro tpx
+// menor que a tolerância, quando o restante da cauda é desprezado.
+pub(crate) fn sobrevivencias<T: TabuaInterface>(
+    tabua: &T,
+    x: &Vec<u16>,
+    tolerancia: f64,
+) -> Vec<f64> {
+    if !(tolerancia > 0.0 && tolerancia < 1.0) {
+        panic!("A tolerância deve estar entre 0 e 1, obtido = {tolerancia}.");
+    }
+    return match tabua.tempo_futuro_maximo(x) {
+        Finite(tempo) => (0..=tempo).map(|t| tabua.tpx(x, t)).collect(),
+        Infinity => {
+            let mut tpx = vec![1.0];
+            let mut t: u16 = 0;
+            while tpx[t as usize] >= tolerancia {
+                if t == u16::MAX {
+                    panic!("A sobrevivência não ficou abaixo da tolerância {tolerancia}.");
+                }
+                t += 1;
+                tpx.push(tabua.tpx(x, t));
+            }
+            tpx
+        }
+        NegativeInfinity => panic!("O tempo futuro máximo não pode ser NegativeInfinity."),
+    };
+}
+
+// e_x = soma de tpx para t >= 1.
+pub fn expectativa_vida_curta<T: TabuaInterface>(
+    tabua: &T,
+    x: &Vec<u16>,
+    tolerancia: f64,
+) -> TempoVida {
+    let tpx = sobrevivencias(tabua, x, tolerancia);
+    return TempoVida::new(tabua, tpx.iter().skip(1).sum());
+}
+
+// e̊_x com distribuição uniforme das mortes dentro de cada período (regra do trapézio).
+pub fn expectativa_vida_completa<T: TabuaInterface>(
+    tabua: &T,
+    x: &Vec<u16>,
+    tolerancia: f64,
+) -> TempoVida {
+    let tpx = sobrevivencias(tabua, x, tolerancia);
+    let periodos = tpx.windows(2).map(|par| 0.5 * (par[0] + par[1])).sum();
+    return TempoVida::new(tabua, periodos);
+}
+
+// Menor u tal que P(T(x) <= u) = probabilidade, interpolando linearmente tpx dentro do período.
+pub fn quantil_tempo_vida<T: TabuaInterface>(
+    tabua: &T,
+    x: &Vec<u16>,
+    probabilidade: f64,
+    tolerancia: f64,
+) -> TempoVida {
+    if !(probabilidade > 0.0 && probabilidade < 1.0) {
+        panic!("A probabilidade do quantil deve estar entre 0 e 1, obtido = {probabilidade}.");
+    }
+    let sobrevivencia_alvo = 1.0 - probabilidade;
+    let tpx = sobrevivencias(tabua, x, tolerancia);
+    for t in 0..tpx.len() - 1 {
+        if tpx[t + 1] <= sobrevivencia_alvo {
+            let fracao = (tpx[t] - sobrevivencia_alvo) / (tpx[t] - tpx[t + 1]);
+            return TempoVida::new(tabua, t as f64 + fracao);
+        }
+    }
+    panic!(
+        "O quantil {probabilidade} está além da truncagem definida pela tolerância {tolerancia}."
+    );
+}
+
+pub fn mediana_tempo_vida<T: TabuaInterface>(
+    tabua: &T,
+    x: &Vec<u16>,
+    tolerancia: f64,
+) -> TempoVida {
+    return quantil_tempo_vida(tabua, x, 0.5, tolerancia);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Periodicidade, Tabua, TabuaMDT, TabuaMultiplasVidas, TabuaVida};
+    use approx;
+
+    #[test]
+    fn expectativas_coincidem_com_a_tabua_de_vida() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+        let vida = TabuaVida::a_partir_da_tabua(&tabua, 1.0);
+
+        let curta = expectativa_vida_curta(&tabua, &vec![1], 1e-12);
+        let completa = expectativa_vida_completa(&tabua, &vec![1], 1e-12);
+
+        approx::assert_abs_diff_eq!(curta.periodos, vida.ex()[1], epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(completa.periodos, vida.ex_completa()[1], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn tabua_plato_eh_truncada_pela_tolerancia() {
+        let tabua = Tabua::new(vec![0.1], Periodicidade::Semestral);
+
+        let curta = expectativa_vida_curta(&tabua, &vec![0], 1e-10);
+
+        approx::assert_abs_diff_eq!(curta.periodos, 9.0, epsilon = 1e-8);
+        approx::assert_abs_diff_eq!(curta.anos, 4.5, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn mediana_interpola_dentro_do_periodo() {
+        let tabua = Tabua::new(vec![0.2, 0.5, 1.0], Periodicidade::Anual);
+
+        let mediana = mediana_tempo_vida(&tabua, &vec![0], 1e-10);
+        let quantil = quantil_tempo_vida(&tabua, &vec![0], 0.1, 1e-10);
+
+        approx::assert_abs_diff_eq!(mediana.periodos, 1.0 + 0.3 / 0.4, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(quantil.periodos, 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn funcoes_aceitam_tabuas_mdt_e_de_multiplas_vidas() {
+        let tabua = Tabua::new(vec![0.1], Periodicidade::Anual);
+        let mdt = TabuaMDT::new(
+            vec![tabua.clone(), tabua.clone()],
+            crate::HipoteseMdt::UddTabuasSimples,
+        );
+        let conjunta = TabuaMultiplasVidas::new(vec![tabua.clone(), tabua.clone()], crate::First);
+
+        let e_mdt = expectativa_vida_curta(&mdt, &vec![0, 0], 1e-12);
+        let e_conjunta = expectativa_vida_curta(&conjunta, &vec![0, 0], 1e-12);
+
+        approx::assert_abs_diff_eq!(e_mdt.periodos, 0.81 / 0.19, epsilon = 1e-9);
+        approx::assert_abs_diff_eq!(e_conjunta.periodos, 0.81 / 0.19, epsilon = 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "A tolerância deve estar entre 0 e 1")]
+    fn tolerancia_deve_estar_entre_0_e_1() {
+        let tabua = Tabua::new(vec![0.1], Periodicidade::Anual);
+        expectativa_vida_curta(&tabua, &vec![0], 0.0);
+    }
+}
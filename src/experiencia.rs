@@ -0,0 +1,111 @@
+use crate::interface::TabuaInterface;
+use crate::Periodicidade;
+use crate::Tabua;
+
+// Óbitos e exposições centrais observados por idade. As idades são expressas em períodos da
+// periodicidade informada, da mesma forma que nas tábuas.
+#[derive(Debug, Clone)]
+pub struct Experiencia {
+    idades: Vec<u16>,
+    obitos: Vec<f64>,
+    expostos: Vec<f64>,
+    periodicidade: Periodicidade,
+}
+
+impl Experiencia {
+    pub fn new(
+        idades: Vec<u16>,
+        obitos: Vec<f64>,
+        expostos: Vec<f64>,
+        periodicidade: Periodicidade,
+    ) -> Self {
+        if idades.is_empty() {
+            panic!("A experiência deve possuir pelo menos uma idade.");
+        }
+        if idades.len() != obitos.len() || idades.len() != expostos.len() {
+            panic!("Idades, óbitos e expostos devem possuir o mesmo tamanho.");
+        }
+        if obitos
+            .iter()
+            .chain(expostos.iter())
+            .any(|v| *v < 0.0 || !v.is_finite())
+        {
+            panic!("Óbitos e expostos devem ser finitos e maiores que ou iguais a zero.");
+        }
+        return Experiencia {
+            idades,
+            obitos,
+            expostos,
+            periodicidade,
+        };
+    }
+
+    // Gera a experiência que seria observada se a mortalidade seguisse exatamente a tábua,
+    // usando a taxa central -ln(1 - qx) sobre as exposições informadas.
+    pub fn a_partir_da_tabua(tabua: &Tabua, idades: Vec<u16>, expostos: Vec<f64>) -> Self {
+        let obitos = idades
+            .iter()
+            .zip(expostos.iter())
+            .map(|(idade, exposto)| exposto * -(1.0 - tabua.qx(&vec![*idade], 0)).ln())
+            .collect();
+        return Experiencia::new(idades, obitos, expostos, tabua.periodicidade().clone());
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn obitos(&self) -> &Vec<f64> {
+        return &self.obitos;
+    }
+
+    pub fn expostos(&self) -> &Vec<f64> {
+        return &self.expostos;
+    }
+
+    pub fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    pub fn taxas_centrais(&self) -> Vec<f64> {
+        return self
+            .obitos
+            .iter()
+            .zip(self.expostos.iter())
+            .map(|(obitos, expostos)| {
+                if *expostos == 0.0 {
+                    0.0
+                } else {
+                    obitos / expostos
+                }
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn a_partir_da_tabua_gera_obitos_pela_taxa_central() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.5], Periodicidade::Anual);
+
+        let experiencia = Experiencia::a_partir_da_tabua(&tabua, vec![0, 2], vec![100.0, 10.0]);
+
+        approx::assert_relative_eq!(experiencia.obitos()[0], -100.0 * 0.9_f64.ln());
+        approx::assert_relative_eq!(experiencia.taxas_centrais()[1], -(0.5_f64.ln()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Idades, óbitos e expostos devem possuir o mesmo tamanho.")]
+    fn experiencia_da_erro_com_vetores_de_tamanhos_diferentes() {
+        Experiencia::new(
+            vec![0, 1],
+            vec![1.0],
+            vec![10.0, 10.0],
+            Periodicidade::Anual,
+        );
+    }
+}
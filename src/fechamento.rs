@@ -0,0 +1,384 @@
+use crate::estatistica::regressao_linear;
+use crate::interface::TabuaInterface;
+use crate::Periodicidade;
+use crate::Tabua;
+use infinitable::Infinitable;
+
+// Métodos de extrapolação da mortalidade em idades avançadas. As idades de ajuste delimitam os
+// qx da tábua original usados para estimar os parâmetros.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetodoFechamento {
+    Kannisto {
+        idade_ajuste_inicial: u16,
+        idade_ajuste_final: u16,
+    },
+    CoaleKisker {
+        taxa_central_final: f64,
+    },
+    DenuitGoderniaux {
+        idade_ajuste_inicial: u16,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultadoFechamento {
+    pub tabua: Tabua,
+    pub parametros: Vec<f64>,
+    pub tempo_futuro_maximo: Infinitable<u16>,
+}
+
+fn qx_original(tabua: &Tabua, idade: u16) -> f64 {
+    return tabua.qx(&vec![idade], 0);
+}
+
+fn validar_idades_ajuste(tabua: &Tabua, inicial: u16, final_: u16) {
+    if inicial >= final_
+        || inicial < tabua.idade_inicial()
+        || final_ as usize >= tabua.obter_tabua_base().idade_limite()
+    {
+        panic!("As idades de ajuste devem formar um intervalo dentro da tábua original.");
+    }
+    if (inicial..=final_).any(|x| {
+        let q = qx_original(tabua, x);
+        q <= 0.0 || q >= 1.0
+    }) {
+        panic!("Os qx usados no ajuste do fechamento devem estar entre 0 e 1.");
+    }
+}
+
+// Kannisto: μ(x) = a * e^(b * x) / (1 + a * e^(b * x)), ou seja, logit μ é linear na idade. O ajuste
+// usa μ(x + 0.5) = -ln(1 - qx) e o qx extrapolado integra a força ao longo da idade.
+fn fechamento_kannisto(
+    tabua: &Tabua,
+    idade_ajuste_inicial: u16,
+    idade_ajuste_final: u16,
+    idade_inicio: u16,
+    idade_final: u16,
+) -> (Vec<f64>, Vec<f64>) {
+    validar_idades_ajuste(tabua, idade_ajuste_inicial, idade_ajuste_final);
+    let idades: Vec<f64> = (idade_ajuste_inicial..=idade_ajuste_final)
+        .map(|x| x as f64 + 0.5)
+        .collect();
+    let logits: Vec<f64> = (idade_ajuste_inicial..=idade_ajuste_final)
+        .map(|x| {
+            let q = qx_original(tabua, x);
+            let mu = -(1.0 - q).ln();
+            // A força de Kannisto é menor que 1, o que exige qx < 1 - 1/e.
+            if mu >= 1.0 {
+                panic!(
+                    "O ajuste de Kannisto exige força de mortalidade menor que 1 nas idades de \
+                     ajuste, mas a idade {x} possui qx = {q}."
+                );
+            }
+            (mu / (1.0 - mu)).ln()
+        })
+        .collect();
+    let (ln_a, b) = regressao_linear(&idades, &logits, &vec![1.0; idades.len()]);
+    if b <= 0.0 {
+        panic!("O ajuste de Kannisto precisa de mortalidade crescente com a idade.");
+    }
+    let a = ln_a.exp();
+    let qx = (idade_inicio..idade_final)
+        .map(|x| {
+            let forca_acumulada =
+                ((1.0 + a * (b * (x as f64 + 1.0)).exp()) / (1.0 + a * (b * x as f64).exp())).ln()
+                    / b;
+            1.0 - (-forca_acumulada).exp()
+        })
+        .collect();
+    return (qx, vec![a, b]);
+}
+
+// Coale-Kisker: m(x) = m(x - 1) * e^k(x), com k decrescendo linearmente a partir de k(x0), taxa
+// média de crescimento dos 20 anos anteriores, de modo que m(ω) seja a taxa central final.
+fn fechamento_coale_kisker(
+    tabua: &Tabua,
+    taxa_central_final: f64,
+    idade_inicio: u16,
+    idade_final: u16,
+) -> (Vec<f64>, Vec<f64>) {
+    if taxa_central_final <= 0.0 || !taxa_central_final.is_finite() {
+        panic!("A taxa central final de Coale-Kisker deve ser positiva.");
+    }
+    if idade_inicio < 2 {
+        panic!("Coale-Kisker precisa de pelo menos duas idades anteriores ao fechamento.");
+    }
+    let idade_base = idade_inicio - 1;
+    let amplitude = idade_base.min(20);
+    validar_idades_ajuste(tabua, idade_base - amplitude, idade_base);
+    let m = |x: u16| -(1.0 - qx_original(tabua, x)).ln();
+    let m_base = m(idade_base);
+    let k_inicial = (m_base / m(idade_base - amplitude)).ln() / amplitude as f64;
+
+    let n = (idade_final - idade_inicio + 1) as f64;
+    let r = if n > 1.0 {
+        2.0 * (n * k_inicial + (m_base / taxa_central_final).ln()) / (n * (n - 1.0))
+    } else {
+        0.0
+    };
+    let mut m_anterior = m_base;
+    let qx = (idade_inicio..idade_final)
+        .map(|x| {
+            m_anterior *= (k_inicial - (x - idade_inicio) as f64 * r).exp();
+            1.0 - (-m_anterior).exp()
+        })
+        .collect();
+    return (qx, vec![k_inicial, r]);
+}
+
+// Denuit-Goderniaux: ln qx = a + b * x + c * x^2 com as restrições q(ω) = 1 e q'(ω) = 0, o que
+// reduz o modelo a ln qx = c * (ω - x)^2, com c estimado por mínimos quadrados.
+fn fechamento_denuit_goderniaux(
+    tabua: &Tabua,
+    idade_ajuste_inicial: u16,
+    idade_inicio: u16,
+    idade_final: u16,
+) -> (Vec<f64>, Vec<f64>) {
+    let idade_ajuste_final =
+        (tabua.obter_tabua_base().idade_limite() as u16 - 1).min(idade_final - 1);
+    validar_idades_ajuste(tabua, idade_ajuste_inicial, idade_ajuste_final);
+    let (numerador, denominador) = (idade_ajuste_inicial..=idade_ajuste_final).fold(
+        (0.0, 0.0),
+        |(numerador, denominador), x| {
+            let distancia = (idade_final - x) as f64;
+            (
+                numerador + qx_original(tabua, x).ln() * distancia.powi(2),
+                denominador + distancia.powi(4),
+            )
+        },
+    );
+    let c = numerador / denominador;
+    let qx = (idade_inicio..idade_final)
+        .map(|x| (c * ((idade_final - x) as f64).powi(2)).exp())
+        .collect();
+    return (qx, vec![c]);
+}
+
+// Substitui os qx a partir de idade_inicio pela curva extrapolada, que termina com q(ω) = 1 na
+// idade final. Nas idades da faixa de transição, o qx é a média entre o original e o
+// extrapolado, com o peso do extrapolado crescendo linearmente.
+pub fn fechar_tabua(
+    tabua: &Tabua,
+    metodo: MetodoFechamento,
+    idade_inicio: u16,
+    idade_final: u16,
+    faixa_transicao: u16,
+) -> ResultadoFechamento {
+    if *tabua.periodicidade() != Periodicidade::Anual {
+        panic!("O fechamento de idades avançadas deve ser aplicado a tábuas anuais.");
+    }
+    if idade_inicio >= idade_final {
+        panic!("A idade de início do fechamento deve ser menor que a idade final.");
+    }
+    if idade_inicio <= tabua.idade_inicial()
+        || idade_inicio as usize > tabua.obter_tabua_base().idade_limite()
+    {
+        panic!("A idade de início do fechamento deve estar dentro da tábua original.");
+    }
+
+    let (extrapolados, parametros) = match metodo {
+        MetodoFechamento::Kannisto {
+            idade_ajuste_inicial,
+            idade_ajuste_final,
+        } => fechamento_kannisto(
+            tabua,
+            idade_ajuste_inicial,
+            idade_ajuste_final,
+            idade_inicio,
+            idade_final,
+        ),
+        MetodoFechamento::CoaleKisker { taxa_central_final } => {
+            fechamento_coale_kisker(tabua, taxa_central_final, idade_inicio, idade_final)
+        }
+        MetodoFechamento::DenuitGoderniaux {
+            idade_ajuste_inicial,
+        } => fechamento_denuit_goderniaux(tabua, idade_ajuste_inicial, idade_inicio, idade_final),
+    };
+
+    let mut qx: Vec<f64> = (tabua.idade_inicial()..idade_inicio)
+        .map(|x| qx_original(tabua, x))
+        .collect();
+    for (indice, q_extrapolado) in extrapolados.iter().enumerate() {
+        let idade = idade_inicio + indice as u16;
+        let peso = if (indice as u16) < faixa_transicao {
+            (indice + 1) as f64 / (faixa_transicao + 1) as f64
+        } else {
+            1.0
+        };
+        let q = (1.0 - peso) * qx_original(tabua, idade) + peso * q_extrapolado;
+        qx.push(q.clamp(0.0, 1.0));
+    }
+    qx.push(1.0);
+
+    let tabua =
+        Tabua::a_partir_da_tabua_base(tabua.obter_tabua_base().com_qx(qx), Periodicidade::Anual);
+    let tempo_futuro_maximo = tabua.tempo_futuro_maximo(&vec![tabua.idade_inicial()]);
+    return ResultadoFechamento {
+        tabua,
+        parametros,
+        tempo_futuro_maximo,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+    use infinitable::Infinitable::Finite;
+
+    fn forca_kannisto(a: f64, b: f64, x: f64) -> f64 {
+        return ((1.0 + a * (b * (x + 1.0)).exp()) / (1.0 + a * (b * x).exp())).ln() / b;
+    }
+
+    // Tábua truncada em 100 anos, com platô, gerada por Gompertz.
+    fn criar_tabua_truncada() -> Tabua {
+        let qx = (0..=100)
+            .map(|x| {
+                let b = 0.00005;
+                let c: f64 = 1.1;
+                1.0 - (-b * c.powi(x) * (c - 1.0) / c.ln()).exp()
+            })
+            .collect();
+        Tabua::new(qx, Periodicidade::Anual)
+    }
+
+    #[test]
+    fn fechamento_termina_com_q_igual_a_1_na_idade_final() {
+        let tabua = criar_tabua_truncada();
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Infinitable::Infinity);
+
+        for metodo in [
+            MetodoFechamento::Kannisto {
+                idade_ajuste_inicial: 80,
+                idade_ajuste_final: 95,
+            },
+            MetodoFechamento::CoaleKisker {
+                taxa_central_final: 1.0,
+            },
+            MetodoFechamento::DenuitGoderniaux {
+                idade_ajuste_inicial: 75,
+            },
+        ] {
+            let resultado = fechar_tabua(&tabua, metodo, 96, 115, 0);
+
+            assert_eq!(resultado.tempo_futuro_maximo, Finite(116));
+            approx::assert_relative_eq!(resultado.tabua.qx(&vec![115], 0), 1.0);
+            approx::assert_relative_eq!(resultado.tabua.qx(&vec![90], 0), tabua.qx(&vec![90], 0));
+        }
+    }
+
+    #[test]
+    fn kannisto_recupera_parametros_de_dados_exatos() {
+        let (a, b) = (0.00002, 0.11);
+        let qx = (0..=100)
+            .map(|x| 1.0 - (-forca_kannisto(a, b, x as f64)).exp())
+            .collect();
+        let tabua = Tabua::new(qx, Periodicidade::Anual);
+
+        let resultado = fechar_tabua(
+            &tabua,
+            MetodoFechamento::Kannisto {
+                idade_ajuste_inicial: 80,
+                idade_ajuste_final: 100,
+            },
+            101,
+            120,
+            0,
+        );
+
+        approx::assert_relative_eq!(resultado.parametros[1], b, epsilon = 1e-3);
+        approx::assert_relative_eq!(
+            resultado.tabua.qx(&vec![105], 0),
+            1.0 - (-forca_kannisto(a, b, 105.0)).exp(),
+            max_relative = 1e-3
+        );
+    }
+
+    #[test]
+    fn coale_kisker_atinge_a_taxa_central_final() {
+        let resultado = fechar_tabua(
+            &criar_tabua_truncada(),
+            MetodoFechamento::CoaleKisker {
+                taxa_central_final: 0.8,
+            },
+            86,
+            111,
+            0,
+        );
+
+        // A taxa central da idade final seria 0.8, mas a tábua é fechada com q = 1 nessa idade.
+        let m_110 = -(1.0 - resultado.tabua.qx(&vec![110], 0)).ln();
+        let [k, r] = [resultado.parametros[0], resultado.parametros[1]];
+        approx::assert_relative_eq!(m_110 * (k - 25.0 * r).exp(), 0.8, epsilon = 1e-10);
+        approx::assert_relative_eq!(k, 1.1_f64.ln(), epsilon = 1e-3);
+    }
+
+    #[test]
+    fn denuit_goderniaux_tem_derivada_nula_no_fim() {
+        let resultado = fechar_tabua(
+            &criar_tabua_truncada(),
+            MetodoFechamento::DenuitGoderniaux {
+                idade_ajuste_inicial: 75,
+            },
+            90,
+            115,
+            0,
+        );
+
+        assert!(resultado.parametros[0] < 0.0);
+        let q_113 = resultado.tabua.qx(&vec![113], 0);
+        let q_114 = resultado.tabua.qx(&vec![114], 0);
+        assert!(q_114 > q_113);
+        assert!(1.0 - q_114 < q_114 - q_113);
+    }
+
+    #[test]
+    fn faixa_de_transicao_mistura_original_e_extrapolado() {
+        let tabua = criar_tabua_truncada();
+        let metodo = MetodoFechamento::DenuitGoderniaux {
+            idade_ajuste_inicial: 75,
+        };
+        let direto = fechar_tabua(&tabua, metodo.clone(), 90, 115, 0);
+        let suave = fechar_tabua(&tabua, metodo, 90, 115, 3);
+
+        approx::assert_relative_eq!(
+            suave.tabua.qx(&vec![91], 0),
+            0.5 * tabua.qx(&vec![91], 0) + 0.5 * direto.tabua.qx(&vec![91], 0)
+        );
+        approx::assert_relative_eq!(suave.tabua.qx(&vec![93], 0), direto.tabua.qx(&vec![93], 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "a idade 7 possui qx = 0.7")]
+    fn kannisto_exige_forca_menor_que_1_nas_idades_de_ajuste() {
+        let tabua = Tabua::new(
+            vec![0.1, 0.15, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 1.0],
+            Periodicidade::Anual,
+        );
+        fechar_tabua(
+            &tabua,
+            MetodoFechamento::Kannisto {
+                idade_ajuste_inicial: 3,
+                idade_ajuste_final: 8,
+            },
+            9,
+            20,
+            0,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "tábuas anuais")]
+    fn fechamento_exige_tabua_anual() {
+        let tabua = Tabua::new(vec![0.1; 10], Periodicidade::Mensal);
+        fechar_tabua(
+            &tabua,
+            MetodoFechamento::CoaleKisker {
+                taxa_central_final: 1.0,
+            },
+            5,
+            20,
+            0,
+        );
+    }
+}
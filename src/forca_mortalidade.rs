@@ -0,0 +1,402 @@
+use crate::interface::{JurosInterface, TabuaInterface};
+use crate::Tabua;
+
+// Quantidade de subintervalos da regra de Simpson em cada período.
+const SUBINTERVALOS_SIMPSON: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetodoForcaMortalidade {
+    // μ constante em cada período, igual a -ln(1 - qx). Reproduz exatamente os tpx inteiros.
+    ConstantePorPeriodo,
+    // Spline cúbica natural de ln μ com nós no meio de cada período. Os tpx inteiros passam a
+    // ser aproximados. Cada sequência de períodos com qx positivo recebe sua própria spline; nos
+    // períodos com qx = 0 a força é nula.
+    SplineLogaritmica,
+}
+
+// Spline cúbica natural por nós igualmente espaçados de uma unidade.
+#[derive(Debug, Clone)]
+struct SplineNatural {
+    inicio: f64,
+    valores: Vec<f64>,
+    derivadas_segundas: Vec<f64>,
+}
+
+impl SplineNatural {
+    fn new(inicio: f64, valores: Vec<f64>) -> Self {
+        let n = valores.len();
+        let mut derivadas_segundas = vec![0.0; n];
+        if n > 2 {
+            // Sistema tridiagonal 1 4 1 para os nós interiores (algoritmo de Thomas).
+            let m = n - 2;
+            let mut diagonal = vec![4.0; m];
+            let mut termos: Vec<f64> = (1..n - 1)
+                .map(|i| 6.0 * (valores[i + 1] - 2.0 * valores[i] + valores[i - 1]))
+                .collect();
+            for i in 1..m {
+                let fator = 1.0 / diagonal[i - 1];
+                diagonal[i] -= fator;
+                termos[i] -= fator * termos[i - 1];
+            }
+            derivadas_segundas[m] = termos[m - 1] / diagonal[m - 1];
+            for i in (0..m - 1).rev() {
+                derivadas_segundas[i + 1] = (termos[i] - derivadas_segundas[i + 2]) / diagonal[i];
+            }
+        }
+        return SplineNatural {
+            inicio,
+            valores,
+            derivadas_segundas,
+        };
+    }
+
+    // Fora dos nós o valor é mantido constante.
+    fn avaliar(&self, posicao: f64) -> f64 {
+        let n = self.valores.len();
+        let u = (posicao - self.inicio).clamp(0.0, (n - 1) as f64);
+        if n == 1 {
+            return self.valores[0];
+        }
+        let i = (u.floor() as usize).min(n - 2);
+        let b = u - i as f64;
+        let a = 1.0 - b;
+        return a * self.valores[i]
+            + b * self.valores[i + 1]
+            + ((a.powi(3) - a) * self.derivadas_segundas[i]
+                + (b.powi(3) - b) * self.derivadas_segundas[i + 1])
+                / 6.0;
+    }
+}
+
+// Força de mortalidade μ em idades reais, com idades e tempos medidos em períodos da tábua.
+// O qx de cada período vem da própria tábua, respeitando platô e a regra das idades inferiores
+// à inicial. No período em que qx = 1 supõe-se UDD, com μ = 1 / (1 - s), e a sobrevivência
+// chega a zero no fim do período.
+#[derive(Debug, Clone)]
+pub struct ForcaMortalidade {
+    tabua: Tabua,
+    metodo: MetodoForcaMortalidade,
+    // (idade inicial, idade final exclusiva, spline) de cada sequência de qx positivos.
+    splines: Vec<(u16, u16, SplineNatural)>,
+}
+
+fn integrar_simpson<F: FnMut(f64) -> f64>(mut funcao: F, inicio: f64, fim: f64) -> f64 {
+    let h = (fim - inicio) / SUBINTERVALOS_SIMPSON as f64;
+    let soma = (0..=SUBINTERVALOS_SIMPSON).fold(0.0, |acc, i| {
+        let peso = if i == 0 || i == SUBINTERVALOS_SIMPSON {
+            1.0
+        } else if i % 2 == 1 {
+            4.0
+        } else {
+            2.0
+        };
+        acc + peso * funcao(inicio + h * i as f64)
+    });
+    return soma * h / 3.0;
+}
+
+// Fator de desconto em tempo real com força de juros constante dentro de cada período.
+struct Desconto<'a, J: JurosInterface> {
+    juros: &'a J,
+    forcas_acumuladas: Vec<f64>,
+}
+
+impl<'a, J: JurosInterface> Desconto<'a, J> {
+    fn fator(&mut self, t: f64) -> f64 {
+        let k = t.floor() as usize;
+        while self.forcas_acumuladas.len() <= k + 1 {
+            let periodo = self.forcas_acumuladas.len() - 1;
+            let forca = (1.0 + self.juros.taxa_juros(periodo as u16)).ln();
+            self.forcas_acumuladas
+                .push(self.forcas_acumuladas[periodo] + forca);
+        }
+        let forca_periodo = self.forcas_acumuladas[k + 1] - self.forcas_acumuladas[k];
+        return (-(self.forcas_acumuladas[k] + (t - k as f64) * forca_periodo)).exp();
+    }
+}
+
+impl ForcaMortalidade {
+    pub fn new(tabua: &Tabua, metodo: MetodoForcaMortalidade) -> Self {
+        let mut splines = Vec::new();
+        if metodo == MetodoForcaMortalidade::SplineLogaritmica {
+            let tabua_base = tabua.obter_tabua_base();
+            let inicio = tabua_base.idade_inicial();
+            let mut log_mu: Vec<f64> = Vec::new();
+            let mut inicio_sequencia = inicio;
+            for (i, q) in tabua_base.qx.iter().take_while(|q| **q < 1.0).enumerate() {
+                let idade = inicio + i as u16;
+                if *q > 0.0 {
+                    if log_mu.is_empty() {
+                        inicio_sequencia = idade;
+                    }
+                    log_mu.push((-(1.0 - q).ln()).ln());
+                } else if !log_mu.is_empty() {
+                    splines.push(ForcaMortalidade::criar_spline(inicio_sequencia, log_mu));
+                    log_mu = Vec::new();
+                }
+            }
+            if !log_mu.is_empty() {
+                splines.push(ForcaMortalidade::criar_spline(inicio_sequencia, log_mu));
+            }
+        }
+        return ForcaMortalidade {
+            tabua: tabua.clone(),
+            metodo,
+            splines,
+        };
+    }
+
+    fn criar_spline(inicio: u16, log_mu: Vec<f64>) -> (u16, u16, SplineNatural) {
+        let fim = inicio + log_mu.len() as u16;
+        return (inicio, fim, SplineNatural::new(inicio as f64 + 0.5, log_mu));
+    }
+
+    pub fn metodo(&self) -> &MetodoForcaMortalidade {
+        return &self.metodo;
+    }
+
+    fn qx_periodo(&self, idade: u16) -> f64 {
+        return self.tabua.qx(&vec![idade], 0);
+    }
+
+    fn spline_no_periodo(&self, idade: u16) -> Option<&SplineNatural> {
+        return self
+            .splines
+            .iter()
+            .find(|(inicio, fim, _)| idade >= *inicio && idade < *fim)
+            .map(|(_, _, spline)| spline);
+    }
+
+    fn validar_idade(idade: f64) {
+        if !idade.is_finite() || idade < 0.0 || idade >= u16::MAX as f64 {
+            panic!("A idade deve ser um número real não negativo, obtido = {idade}.");
+        }
+    }
+
+    pub fn mu(&self, idade: f64) -> f64 {
+        ForcaMortalidade::validar_idade(idade);
+        return self.mu_no_periodo(idade.floor() as u16, idade);
+    }
+
+    // μ no período k, inclusive no limite à esquerda do fim do período.
+    fn mu_no_periodo(&self, k: u16, idade: f64) -> f64 {
+        let q = self.qx_periodo(k);
+        if q >= 1.0 {
+            return 1.0 / (1.0 - (idade - k as f64));
+        }
+        return match self.spline_no_periodo(k) {
+            Some(spline) => spline.avaliar(idade).exp(),
+            None => -(1.0 - q).ln(),
+        };
+    }
+
+    // Integral de μ entre duas idades reais do mesmo período k.
+    fn forca_acumulada_no_periodo(&self, k: u16, inicio: f64, fim: f64) -> f64 {
+        if fim <= inicio {
+            return 0.0;
+        }
+        let q = self.qx_periodo(k);
+        if q >= 1.0 {
+            let s0 = inicio - k as f64;
+            let s1 = fim - k as f64;
+            if s1 >= 1.0 {
+                return f64::INFINITY;
+            }
+            return (1.0 - s0).ln() - (1.0 - s1).ln();
+        }
+        return match self.spline_no_periodo(k) {
+            Some(spline) => integrar_simpson(|idade| spline.avaliar(idade).exp(), inicio, fim),
+            None => -(1.0 - q).ln() * (fim - inicio),
+        };
+    }
+
+    fn forca_acumulada(&self, inicio: f64, fim: f64) -> f64 {
+        let mut acumulada: f64 = 0.0;
+        let mut a = inicio;
+        while a < fim && acumulada.is_finite() {
+            let k = a.floor();
+            let b = fim.min(k + 1.0);
+            acumulada += self.forca_acumulada_no_periodo(k as u16, a, b);
+            a = b;
+        }
+        return acumulada;
+    }
+
+    pub fn tpx(&self, x: f64, t: f64) -> f64 {
+        ForcaMortalidade::validar_idade(x);
+        if !t.is_finite() || t < 0.0 {
+            panic!("O tempo deve ser um número real não negativo, obtido = {t}.");
+        }
+        ForcaMortalidade::validar_idade(x + t);
+        return (-self.forca_acumulada(x, x + t)).exp();
+    }
+
+    // Integra v(t) tpx e v(t) tpx μ(x + t) por Simpson entre as mudanças de período da idade,
+    // até a sobrevivência ficar abaixo da tolerância ou se anular.
+    fn integrar<J: JurosInterface>(&self, x: f64, juros: &J, tolerancia: f64) -> (f64, f64) {
+        ForcaMortalidade::validar_idade(x);
+        if juros.periodicidade() != self.tabua.periodicidade() {
+            panic!("A tábua e os juros devem possuir a mesma periodicidade.");
+        }
+        if !(tolerancia > 0.0 && tolerancia < 1.0) {
+            panic!("A tolerância deve estar entre 0 e 1, obtido = {tolerancia}.");
+        }
+        let mut desconto = Desconto {
+            juros,
+            forcas_acumuladas: vec![0.0],
+        };
+        let mut anuidade = 0.0;
+        let mut seguro = 0.0;
+        let mut sobrevivencia = 1.0;
+        let mut idade = x;
+        while sobrevivencia >= tolerancia {
+            let k = idade.floor();
+            if k + 1.0 >= u16::MAX as f64 {
+                panic!("A sobrevivência não ficou abaixo da tolerância {tolerancia}.");
+            }
+            let proxima = k + 1.0;
+            let periodo = k as u16;
+            if self.qx_periodo(periodo) >= 1.0 {
+                // UDD no último período: tpx decresce linearmente e tpx μ é constante.
+                let s0 = idade - k;
+                let densidade = sobrevivencia / (1.0 - s0);
+                anuidade += integrar_simpson(
+                    |s| desconto.fator(s - x) * densidade * (1.0 - (s - k)),
+                    idade,
+                    proxima,
+                );
+                seguro += integrar_simpson(|s| desconto.fator(s - x) * densidade, idade, proxima);
+                break;
+            }
+            let inicio = idade;
+            let sobrevivencia_em = |s: f64| {
+                sobrevivencia * (-self.forca_acumulada_no_periodo(periodo, inicio, s)).exp()
+            };
+            anuidade += integrar_simpson(
+                |s| desconto.fator(s - x) * sobrevivencia_em(s),
+                idade,
+                proxima,
+            );
+            seguro += integrar_simpson(
+                |s| desconto.fator(s - x) * sobrevivencia_em(s) * self.mu_no_periodo(periodo, s),
+                idade,
+                proxima,
+            );
+            sobrevivencia = sobrevivencia_em(proxima);
+            idade = proxima;
+        }
+        return (anuidade, seguro);
+    }
+
+    // ā_x: anuidade contínua vitalícia de 1 por período.
+    pub fn anuidade_continua<J: JurosInterface>(&self, x: f64, juros: &J, tolerancia: f64) -> f64 {
+        return self.integrar(x, juros, tolerancia).0;
+    }
+
+    // Ā_x: seguro vitalício de 1 pago no momento da morte.
+    pub fn seguro_continuo<J: JurosInterface>(&self, x: f64, juros: &J, tolerancia: f64) -> f64 {
+        return self.integrar(x, juros, tolerancia).1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JurosConstante, Periodicidade, RegraIdadeInferior};
+    use approx;
+
+    #[test]
+    fn forca_constante_reproduz_tpx_inteiros() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.3], Periodicidade::Anual);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::ConstantePorPeriodo);
+
+        approx::assert_relative_eq!(forca.tpx(0.0, 2.0), 0.9 * 0.8, epsilon = 1e-12);
+        approx::assert_relative_eq!(forca.tpx(1.0, 0.5), 0.8_f64.sqrt(), epsilon = 1e-12);
+        approx::assert_relative_eq!(forca.mu(2.7), -(0.7_f64.ln()), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn ultimo_periodo_de_tabua_completa_segue_udd() {
+        let tabua = Tabua::new(vec![0.5, 1.0], Periodicidade::Anual);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::ConstantePorPeriodo);
+
+        approx::assert_relative_eq!(forca.tpx(1.0, 0.25), 0.75, epsilon = 1e-12);
+        assert_eq!(forca.tpx(0.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn seguro_e_anuidade_continuos_com_forca_constante() {
+        // Com μ e δ constantes, ā = 1 / (μ + δ) e Ā = μ / (μ + δ).
+        let tabua = Tabua::new(vec![0.05], Periodicidade::Anual);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::ConstantePorPeriodo);
+        let juros = JurosConstante::new(0.04, Periodicidade::Anual);
+        let mu = -(0.95_f64.ln());
+        let delta = 1.04_f64.ln();
+
+        let anuidade = forca.anuidade_continua(0.3, &juros, 1e-12);
+        let seguro = forca.seguro_continuo(0.3, &juros, 1e-12);
+
+        approx::assert_relative_eq!(anuidade, 1.0 / (mu + delta), epsilon = 1e-8);
+        approx::assert_relative_eq!(seguro, mu / (mu + delta), epsilon = 1e-8);
+        approx::assert_relative_eq!(seguro, 1.0 - delta * anuidade, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn spline_passa_pelos_nos_e_aproxima_tpx() {
+        let qx: Vec<f64> = (0..40)
+            .map(|x| 1.0 - (-0.001 * (0.08 * x as f64).exp()).exp())
+            .collect();
+        let tabua = Tabua::a_partir_da_idade(
+            30,
+            qx.clone(),
+            Periodicidade::Anual,
+            RegraIdadeInferior::Erro,
+        );
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::SplineLogaritmica);
+
+        approx::assert_relative_eq!(forca.mu(40.5), -(1.0 - qx[10]).ln(), epsilon = 1e-12);
+        approx::assert_relative_eq!(
+            forca.tpx(35.0, 10.0),
+            tabua.tpx(&vec![35], 10),
+            epsilon = 1e-4
+        );
+    }
+
+    #[test]
+    fn spline_aceita_periodos_com_qx_nulo() {
+        let mut qx: Vec<f64> = (0..30)
+            .map(|x| 1.0 - (-0.001 * (0.08 * x as f64).exp()).exp())
+            .collect();
+        qx[0] = 0.0;
+        qx[10] = 0.0;
+        qx.push(1.0);
+        let tabua = Tabua::new(qx.clone(), Periodicidade::Anual);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::SplineLogaritmica);
+
+        assert_eq!(forca.mu(0.5), 0.0);
+        assert_eq!(forca.mu(10.5), 0.0);
+        approx::assert_relative_eq!(forca.mu(5.5), -(1.0 - qx[5]).ln(), epsilon = 1e-12);
+        approx::assert_relative_eq!(forca.mu(20.5), -(1.0 - qx[20]).ln(), epsilon = 1e-12);
+        approx::assert_relative_eq!(forca.tpx(10.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn relacao_entre_seguro_e_anuidade_vale_para_tabua_completa() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Mensal);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::ConstantePorPeriodo);
+        let juros = JurosConstante::new(0.01, Periodicidade::Mensal);
+
+        let anuidade = forca.anuidade_continua(0.0, &juros, 1e-12);
+        let seguro = forca.seguro_continuo(0.0, &juros, 1e-12);
+
+        approx::assert_relative_eq!(seguro, 1.0 - 1.01_f64.ln() * anuidade, epsilon = 1e-8);
+    }
+
+    #[test]
+    #[should_panic(expected = "A tábua e os juros devem possuir a mesma periodicidade.")]
+    fn juros_e_tabua_devem_ter_mesma_periodicidade() {
+        let tabua = Tabua::new(vec![0.1], Periodicidade::Anual);
+        let forca = ForcaMortalidade::new(&tabua, MetodoForcaMortalidade::ConstantePorPeriodo);
+        forca.anuidade_continua(0.0, &JurosConstante::new(0.01, Periodicidade::Mensal), 1e-6);
+    }
+}
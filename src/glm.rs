@@ -0,0 +1,402 @@
+use crate::algebra_linear::{inverter_matriz, resolver_sistema};
+use crate::estatistica::ln_gama;
+use crate::interface::TabuaInterface;
+use crate::Tabua;
+
+// As duas famílias usam ligação logarítmica com a tábua base como offset, para que exp(β) seja
+// lido diretamente como multiplicador: da taxa central no caso de Poisson (exposição central) e
+// do qx no caso binomial (exposição inicial).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FamiliaGlm {
+    Poisson,
+    Binomial,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObservacaoGlm {
+    pub idade: u16,
+    pub niveis: Vec<String>,
+    pub obitos: f64,
+    pub expostos: f64,
+}
+
+impl ObservacaoGlm {
+    pub fn new(idade: u16, niveis: Vec<&str>, obitos: f64, expostos: f64) -> Self {
+        if obitos < 0.0 || expostos < 0.0 {
+            panic!("Óbitos e expostos devem ser maiores que ou iguais a zero.");
+        }
+        return ObservacaoGlm {
+            idade,
+            niveis: niveis.iter().map(|nivel| nivel.to_string()).collect(),
+            obitos,
+            expostos,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultadoGlm {
+    pub familia: FamiliaGlm,
+    pub fatores: Vec<String>,
+    pub niveis: Vec<Vec<String>>,
+    pub nomes_coeficientes: Vec<String>,
+    pub coeficientes: Vec<f64>,
+    pub erros_padrao: Vec<f64>,
+    pub multiplicadores: Vec<f64>,
+    pub deviance: f64,
+    pub aic: f64,
+    pub iteracoes: usize,
+    pub convergiu: bool,
+}
+
+fn niveis_por_fator(
+    observacoes: &Vec<ObservacaoGlm>,
+    quantidade_fatores: usize,
+) -> Vec<Vec<String>> {
+    let mut niveis: Vec<Vec<String>> = vec![Vec::new(); quantidade_fatores];
+    for observacao in observacoes {
+        if observacao.niveis.len() != quantidade_fatores {
+            panic!("Cada observação deve informar um nível para cada fator.");
+        }
+        for (niveis_fator, nivel) in niveis.iter_mut().zip(observacao.niveis.iter()) {
+            if !niveis_fator.contains(nivel) {
+                niveis_fator.push(nivel.clone());
+            }
+        }
+    }
+    return niveis;
+}
+
+// Intercepto seguido de uma variável indicadora para cada nível que não é o de referência.
+fn linha_desenho(niveis: &Vec<Vec<String>>, niveis_observacao: &Vec<String>) -> Vec<f64> {
+    let mut linha = vec![1.0];
+    for (niveis_fator, nivel) in niveis.iter().zip(niveis_observacao.iter()) {
+        let posicao = niveis_fator.iter().position(|n| n == nivel);
+        for k in 1..niveis_fator.len() {
+            linha.push(if posicao == Some(k) { 1.0 } else { 0.0 });
+        }
+    }
+    return linha;
+}
+
+fn taxa_base(tabua: &Tabua, idade: u16, familia: &FamiliaGlm) -> f64 {
+    let qx = tabua.qx(&vec![idade], 0);
+    return match familia {
+        FamiliaGlm::Poisson => -(1.0 - qx).ln(),
+        FamiliaGlm::Binomial => qx,
+    };
+}
+
+// Mínimos quadrados iterativamente reponderados. Com ligação log, a variável de trabalho é
+// z = η - offset + (y - μ) / μ e o peso é μ² / V(μ).
+pub fn ajustar_glm(
+    observacoes: &Vec<ObservacaoGlm>,
+    fatores: Vec<String>,
+    tabua_base: &Tabua,
+    familia: FamiliaGlm,
+) -> ResultadoGlm {
+    if observacoes.is_empty() {
+        panic!("O GLM deve possuir pelo menos uma observação.");
+    }
+    let niveis = niveis_por_fator(observacoes, fatores.len());
+    let desenho: Vec<Vec<f64>> = observacoes
+        .iter()
+        .map(|observacao| linha_desenho(&niveis, &observacao.niveis))
+        .collect();
+    let offsets: Vec<f64> = observacoes
+        .iter()
+        .map(|observacao| {
+            (observacao.expostos * taxa_base(tabua_base, observacao.idade, &familia)).ln()
+        })
+        .collect();
+    let p = desenho[0].len();
+
+    let total_obitos: f64 = observacoes.iter().map(|o| o.obitos).sum();
+    let total_esperado: f64 = offsets.iter().map(|offset| offset.exp()).sum();
+    let mut beta = vec![0.0; p];
+    if total_obitos > 0.0 && total_esperado > 0.0 {
+        beta[0] = (total_obitos / total_esperado).ln();
+    }
+
+    let media = |beta: &Vec<f64>, i: usize| -> f64 {
+        let eta = offsets[i]
+            + desenho[i]
+                .iter()
+                .zip(beta.iter())
+                .map(|(x, b)| x * b)
+                .sum::<f64>();
+        let mu = eta.exp();
+        return match familia {
+            FamiliaGlm::Poisson => mu,
+            FamiliaGlm::Binomial => mu.min(observacoes[i].expostos * (1.0 - 1e-10)),
+        };
+    };
+    let peso = |mu: f64, i: usize| -> f64 {
+        return match familia {
+            FamiliaGlm::Poisson => mu,
+            FamiliaGlm::Binomial => mu / (1.0 - mu / observacoes[i].expostos),
+        };
+    };
+
+    let mut convergiu = false;
+    let mut iteracoes = 0;
+    let mut informacao = vec![vec![0.0; p]; p];
+    while iteracoes < 100 {
+        iteracoes += 1;
+        let mut xtwx = vec![vec![0.0; p]; p];
+        let mut xtwz = vec![0.0; p];
+        for i in 0..observacoes.len() {
+            if !offsets[i].is_finite() {
+                continue;
+            }
+            let mu = media(&beta, i);
+            let w = peso(mu, i);
+            let eta_sem_offset: f64 = desenho[i].iter().zip(beta.iter()).map(|(x, b)| x * b).sum();
+            let z = eta_sem_offset + (observacoes[i].obitos - mu) / mu;
+            for a in 0..p {
+                xtwz[a] += w * desenho[i][a] * z;
+                for b in 0..p {
+                    xtwx[a][b] += w * desenho[i][a] * desenho[i][b];
+                }
+            }
+        }
+        let novo_beta = resolver_sistema(xtwx.clone(), xtwz);
+        let variacao = novo_beta
+            .iter()
+            .zip(beta.iter())
+            .fold(0.0_f64, |acc, (novo, antigo)| {
+                acc.max((novo - antigo).abs())
+            });
+        beta = novo_beta;
+        informacao = xtwx;
+        if variacao < 1e-10 {
+            convergiu = true;
+            break;
+        }
+    }
+
+    let covariancia = inverter_matriz(&informacao);
+    let erros_padrao = (0..p).map(|k| covariancia[k][k].sqrt()).collect();
+
+    let mut deviance = 0.0;
+    let mut log_verossimilhanca = 0.0;
+    for (i, observacao) in observacoes.iter().enumerate() {
+        if !offsets[i].is_finite() {
+            continue;
+        }
+        let (y, mu) = (observacao.obitos, media(&beta, i));
+        match familia {
+            FamiliaGlm::Poisson => {
+                deviance += 2.0 * (if y > 0.0 { y * (y / mu).ln() } else { 0.0 } - (y - mu));
+                log_verossimilhanca += y * mu.ln() - mu - ln_gama(y + 1.0);
+            }
+            FamiliaGlm::Binomial => {
+                let n = observacao.expostos;
+                let sobreviventes = n - y;
+                deviance += 2.0
+                    * (if y > 0.0 { y * (y / mu).ln() } else { 0.0 }
+                        + if sobreviventes > 0.0 {
+                            sobreviventes * (sobreviventes / (n - mu)).ln()
+                        } else {
+                            0.0
+                        });
+                log_verossimilhanca += ln_gama(n + 1.0) - ln_gama(y + 1.0) - ln_gama(n - y + 1.0)
+                    + y * (mu / n).ln()
+                    + sobreviventes * (1.0 - mu / n).ln();
+            }
+        }
+    }
+
+    let mut nomes_coeficientes = vec!["intercepto".to_string()];
+    for (fator, niveis_fator) in fatores.iter().zip(niveis.iter()) {
+        for nivel in niveis_fator.iter().skip(1) {
+            nomes_coeficientes.push(format!("{fator}={nivel}"));
+        }
+    }
+
+    return ResultadoGlm {
+        familia,
+        fatores,
+        niveis,
+        nomes_coeficientes,
+        multiplicadores: beta.iter().map(|b| b.exp()).collect(),
+        coeficientes: beta,
+        erros_padrao,
+        deviance,
+        aic: 2.0 * p as f64 - 2.0 * log_verossimilhanca,
+        iteracoes,
+        convergiu,
+    };
+}
+
+impl ResultadoGlm {
+    // Multiplicador total da classe de risco, relativo à tábua base. A ordem dos níveis segue a
+    // ordem dos fatores; o nível de referência de cada fator é o primeiro observado nos dados.
+    pub fn multiplicador(&self, niveis: Vec<&str>) -> f64 {
+        if niveis.len() != self.fatores.len() {
+            panic!("Deve ser informado um nível para cada fator.");
+        }
+        for (fator, (niveis_fator, nivel)) in self
+            .fatores
+            .iter()
+            .zip(self.niveis.iter().zip(niveis.iter()))
+        {
+            if !niveis_fator.iter().any(|n| n == nivel) {
+                panic!("Nível desconhecido para o fator {fator}: {nivel}.");
+            }
+        }
+        let niveis: Vec<String> = niveis.iter().map(|nivel| nivel.to_string()).collect();
+        let linha = linha_desenho(&self.niveis, &niveis);
+        return linha
+            .iter()
+            .zip(self.coeficientes.iter())
+            .map(|(x, b)| x * b)
+            .sum::<f64>()
+            .exp();
+    }
+
+    // O multiplicador é aplicado à mesma taxa usada no offset: à taxa central no caso de Poisson
+    // e ao qx no caso binomial.
+    pub fn tabua_ajustada(&self, tabua_base: &Tabua, niveis: Vec<&str>) -> Tabua {
+        let multiplicador = self.multiplicador(niveis);
+        return match self.familia {
+            FamiliaGlm::Poisson => tabua_base.agravar_forca(multiplicador),
+            FamiliaGlm::Binomial => tabua_base.agravar(multiplicador),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Periodicidade;
+    use approx;
+
+    fn criar_tabua_base() -> Tabua {
+        Tabua::new(
+            (0..10).map(|x| 0.01 + 0.005 * x as f64).collect(),
+            Periodicidade::Anual,
+        )
+    }
+
+    fn criar_observacoes(familia: &FamiliaGlm) -> Vec<ObservacaoGlm> {
+        let tabua = criar_tabua_base();
+        let mut observacoes = Vec::new();
+        for idade in 2..8 {
+            for (sexo, multiplicador_sexo) in [("M", 1.0), ("F", 0.8)] {
+                for (fumante, multiplicador_fumante) in [("N", 1.0), ("S", 1.5)] {
+                    let expostos = 1000.0 + 100.0 * idade as f64;
+                    let obitos = 1.1
+                        * multiplicador_sexo
+                        * multiplicador_fumante
+                        * expostos
+                        * taxa_base(&tabua, idade, familia);
+                    observacoes.push(ObservacaoGlm::new(
+                        idade,
+                        vec![sexo, fumante],
+                        obitos,
+                        expostos,
+                    ));
+                }
+            }
+        }
+        observacoes
+    }
+
+    fn fatores() -> Vec<String> {
+        vec!["sexo".to_string(), "fumante".to_string()]
+    }
+
+    #[test]
+    fn glm_poisson_recupera_multiplicadores() {
+        let familia = FamiliaGlm::Poisson;
+        let observacoes = criar_observacoes(&familia);
+
+        let resultado = ajustar_glm(&observacoes, fatores(), &criar_tabua_base(), familia);
+
+        assert!(resultado.convergiu);
+        assert_eq!(
+            resultado.nomes_coeficientes,
+            vec!["intercepto", "sexo=F", "fumante=S"]
+        );
+        approx::assert_relative_eq!(resultado.multiplicadores[0], 1.1, epsilon = 1e-8);
+        approx::assert_relative_eq!(resultado.multiplicadores[1], 0.8, epsilon = 1e-8);
+        approx::assert_relative_eq!(resultado.multiplicadores[2], 1.5, epsilon = 1e-8);
+        approx::assert_abs_diff_eq!(resultado.deviance, 0.0, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn glm_binomial_recupera_multiplicadores() {
+        let familia = FamiliaGlm::Binomial;
+        let observacoes = criar_observacoes(&familia);
+
+        let resultado = ajustar_glm(&observacoes, fatores(), &criar_tabua_base(), familia);
+
+        assert!(resultado.convergiu);
+        approx::assert_relative_eq!(
+            resultado.multiplicador(vec!["F", "S"]),
+            1.1 * 0.8 * 1.5,
+            epsilon = 1e-8
+        );
+        assert!(resultado
+            .erros_padrao
+            .iter()
+            .all(|e| *e > 0.0 && e.is_finite()));
+    }
+
+    #[test]
+    fn tabua_ajustada_aplica_o_multiplicador_da_classe() {
+        let familia = FamiliaGlm::Binomial;
+        let tabua_base = criar_tabua_base();
+        let resultado = ajustar_glm(
+            &criar_observacoes(&familia),
+            fatores(),
+            &tabua_base,
+            familia,
+        );
+
+        let tabua = resultado.tabua_ajustada(&tabua_base, vec!["M", "S"]);
+
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![5], 0),
+            1.1 * 1.5 * tabua_base.qx(&vec![5], 0),
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn tabua_ajustada_de_poisson_multiplica_a_taxa_central() {
+        let familia = FamiliaGlm::Poisson;
+        let tabua_base = criar_tabua_base();
+        let resultado = ajustar_glm(
+            &criar_observacoes(&familia),
+            fatores(),
+            &tabua_base,
+            familia,
+        );
+        let multiplicador = resultado.multiplicador(vec!["M", "S"]);
+
+        let tabua = resultado.tabua_ajustada(&tabua_base, vec!["M", "S"]);
+
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![5], 0),
+            1.0 - (1.0 - tabua_base.qx(&vec![5], 0)).powf(multiplicador),
+            epsilon = 1e-12
+        );
+        approx::assert_relative_eq!(multiplicador, 1.1 * 1.5, epsilon = 1e-8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nível desconhecido para o fator sexo: X.")]
+    fn multiplicador_da_erro_com_nivel_desconhecido() {
+        let familia = FamiliaGlm::Poisson;
+        let resultado = ajustar_glm(
+            &criar_observacoes(&familia),
+            fatores(),
+            &criar_tabua_base(),
+            familia,
+        );
+
+        resultado.multiplicador(vec!["X", "S"]);
+    }
+}
@@ -0,0 +1,408 @@
+use crate::algebra_linear::resolver_sistema;
+use crate::experiencia::Experiencia;
+use crate::tabua::RegraIdadeInferior;
+use crate::Periodicidade;
+use crate::Tabua;
+
+#[derive(Debug, Clone)]
+pub struct ResultadoGraduacao {
+    pub tabua: Tabua,
+    pub idades: Vec<u16>,
+    pub taxas_brutas: Vec<f64>,
+    pub taxas_graduadas: Vec<f64>,
+    pub residuos: Vec<f64>,
+    pub termo_aderencia: f64,
+    pub termo_suavidade: f64,
+}
+
+fn diferencas(valores: &Vec<f64>, ordem: usize) -> Vec<f64> {
+    let mut resultado = valores.clone();
+    for _ in 0..ordem {
+        resultado = resultado.windows(2).map(|par| par[1] - par[0]).collect();
+    }
+    return resultado;
+}
+
+// Coeficientes binomiais com sinal alternado do operador de diferença de ordem z.
+fn coeficientes_diferenca(ordem: usize) -> Vec<f64> {
+    let mut coeficientes = vec![1.0];
+    for _ in 0..ordem {
+        let mut proximo = vec![0.0; coeficientes.len() + 1];
+        for (i, c) in coeficientes.iter().enumerate() {
+            proximo[i] -= c;
+            proximo[i + 1] += c;
+        }
+        coeficientes = proximo;
+    }
+    return coeficientes;
+}
+
+fn validar_idades_consecutivas(idades: &Vec<u16>) {
+    if idades.windows(2).any(|par| par[1] != par[0] + 1) {
+        panic!("As idades a serem graduadas devem ser consecutivas.");
+    }
+}
+
+fn montar_resultado(
+    idades: Vec<u16>,
+    taxas_brutas: Vec<f64>,
+    taxas_graduadas: Vec<f64>,
+    pesos: &Vec<f64>,
+    ordem: usize,
+    periodicidade: Periodicidade,
+) -> ResultadoGraduacao {
+    let taxas_graduadas: Vec<f64> = taxas_graduadas.iter().map(|q| q.clamp(0.0, 1.0)).collect();
+    let residuos: Vec<f64> = taxas_brutas
+        .iter()
+        .zip(taxas_graduadas.iter())
+        .map(|(bruta, graduada)| bruta - graduada)
+        .collect();
+    let termo_aderencia = residuos
+        .iter()
+        .zip(pesos.iter())
+        .map(|(residuo, peso)| peso * residuo * residuo)
+        .sum();
+    let termo_suavidade = diferencas(&taxas_graduadas, ordem)
+        .iter()
+        .map(|d| d * d)
+        .sum();
+
+    return ResultadoGraduacao {
+        tabua: Tabua::a_partir_da_idade(
+            idades[0],
+            taxas_graduadas.clone(),
+            periodicidade,
+            RegraIdadeInferior::Erro,
+        ),
+        idades,
+        taxas_brutas,
+        taxas_graduadas,
+        residuos,
+        termo_aderencia,
+        termo_suavidade,
+    };
+}
+
+// Solução de (W + h * K'K) g = W u, sem restringir os valores graduados.
+pub(crate) fn suavizar_whittaker_henderson(
+    valores: &Vec<f64>,
+    pesos: &Vec<f64>,
+    ordem: usize,
+    suavidade: f64,
+) -> Vec<f64> {
+    let n = valores.len();
+    let coeficientes = coeficientes_diferenca(ordem);
+    let mut matriz = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        matriz[i][i] = pesos[i];
+    }
+    for linha in 0..(n - ordem) {
+        for (a, ca) in coeficientes.iter().enumerate() {
+            for (b, cb) in coeficientes.iter().enumerate() {
+                matriz[linha + a][linha + b] += suavidade * ca * cb;
+            }
+        }
+    }
+    let termos: Vec<f64> = pesos
+        .iter()
+        .zip(valores.iter())
+        .map(|(peso, valor)| peso * valor)
+        .collect();
+
+    return resolver_sistema(matriz, termos);
+}
+
+// Cada valor entra na janela com o peso da janela multiplicado pelo seu peso; valores com peso
+// zero não influenciam os vizinhos.
+pub(crate) fn suavizar_media_movel(
+    valores: &Vec<f64>,
+    pesos: &Vec<f64>,
+    pesos_janela: &Vec<f64>,
+) -> Vec<f64> {
+    let n = valores.len();
+    let semi_amplitude = (pesos_janela.len() / 2) as isize;
+    return (0..n as isize)
+        .map(|i| {
+            let (soma, soma_pesos) = (-semi_amplitude..=semi_amplitude)
+                .filter(|k| i + k >= 0 && i + k < n as isize)
+                .fold((0.0, 0.0), |(soma, soma_pesos), k| {
+                    let peso =
+                        pesos_janela[(k + semi_amplitude) as usize] * pesos[(i + k) as usize];
+                    (soma + peso * valores[(i + k) as usize], soma_pesos + peso)
+                });
+            if soma_pesos == 0.0 {
+                valores[i as usize]
+            } else {
+                soma / soma_pesos
+            }
+        })
+        .collect();
+}
+
+// Minimiza sum(w * (g - u)^2) + h * sum((Δ^z g)^2), cuja solução é (W + h * K'K) g = W u.
+pub fn whittaker_henderson(
+    idades: Vec<u16>,
+    taxas_brutas: Vec<f64>,
+    pesos: Vec<f64>,
+    ordem: usize,
+    suavidade: f64,
+    periodicidade: Periodicidade,
+) -> ResultadoGraduacao {
+    let n = taxas_brutas.len();
+    if idades.len() != n || pesos.len() != n {
+        panic!("Idades, taxas brutas e pesos devem possuir o mesmo tamanho.");
+    }
+    if ordem == 0 || ordem >= n {
+        panic!("A ordem das diferenças deve estar entre 1 e a quantidade de idades menos 1.");
+    }
+    if suavidade < 0.0 {
+        panic!("O parâmetro de suavidade deve ser maior que ou igual a zero.");
+    }
+    if pesos.iter().any(|peso| *peso < 0.0) || pesos.iter().all(|peso| *peso == 0.0) {
+        panic!("Os pesos devem ser não negativos e pelo menos um deve ser positivo.");
+    }
+    validar_idades_consecutivas(&idades);
+
+    let taxas_graduadas = suavizar_whittaker_henderson(&taxas_brutas, &pesos, ordem, suavidade);
+
+    return montar_resultado(
+        idades,
+        taxas_brutas,
+        taxas_graduadas,
+        &pesos,
+        ordem,
+        periodicidade,
+    );
+}
+
+fn taxas_iniciais(experiencia: &Experiencia) -> Vec<f64> {
+    return experiencia
+        .taxas_centrais()
+        .iter()
+        .map(|m| 1.0 - (-m).exp())
+        .collect();
+}
+
+// Gradua as taxas iniciais 1 - exp(-m) da experiência. Sem ponderação pela exposição, todas as
+// idades recebem peso 1.
+pub fn whittaker_henderson_experiencia(
+    experiencia: &Experiencia,
+    ordem: usize,
+    suavidade: f64,
+    ponderar_pela_exposicao: bool,
+) -> ResultadoGraduacao {
+    let pesos = if ponderar_pela_exposicao {
+        experiencia.expostos().clone()
+    } else {
+        vec![1.0; experiencia.idades().len()]
+    };
+    return whittaker_henderson(
+        experiencia.idades().clone(),
+        taxas_iniciais(experiencia),
+        pesos,
+        ordem,
+        suavidade,
+        experiencia.periodicidade().clone(),
+    );
+}
+
+// Média móvel ponderada simétrica. Nas extremidades, os pesos da janela que ficam fora do
+// intervalo são descartados e os demais são renormalizados. A suavidade é medida pelas
+// diferenças de terceira ordem.
+pub fn media_movel_ponderada(
+    idades: Vec<u16>,
+    taxas_brutas: Vec<f64>,
+    pesos_janela: Vec<f64>,
+    periodicidade: Periodicidade,
+) -> ResultadoGraduacao {
+    let n = taxas_brutas.len();
+    if idades.len() != n {
+        panic!("Idades e taxas brutas devem possuir o mesmo tamanho.");
+    }
+    if pesos_janela.len().is_multiple_of(2) {
+        panic!("A janela da média móvel deve possuir uma quantidade ímpar de pesos.");
+    }
+    validar_idades_consecutivas(&idades);
+
+    let taxas_graduadas = suavizar_media_movel(&taxas_brutas, &vec![1.0; n], &pesos_janela);
+
+    return montar_resultado(
+        idades,
+        taxas_brutas,
+        taxas_graduadas,
+        &vec![1.0; n],
+        3,
+        periodicidade,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::TabuaInterface;
+    use approx;
+
+    fn criar_taxas_brutas() -> Vec<f64> {
+        vec![
+            0.010, 0.013, 0.011, 0.016, 0.015, 0.020, 0.019, 0.026, 0.025, 0.033, 0.034, 0.044,
+        ]
+    }
+
+    #[test]
+    fn coeficientes_diferenca_sao_binomiais() {
+        assert_eq!(coeficientes_diferenca(1), vec![-1.0, 1.0]);
+        assert_eq!(coeficientes_diferenca(2), vec![1.0, -2.0, 1.0]);
+        assert_eq!(coeficientes_diferenca(3), vec![-1.0, 3.0, -3.0, 1.0]);
+    }
+
+    #[test]
+    fn whittaker_henderson_sem_suavidade_reproduz_taxas_brutas() {
+        let taxas = criar_taxas_brutas();
+        let n = taxas.len();
+
+        let resultado = whittaker_henderson(
+            (40..40 + n as u16).collect(),
+            taxas.clone(),
+            vec![1.0; n],
+            2,
+            0.0,
+            Periodicidade::Anual,
+        );
+
+        for (bruta, graduada) in taxas.iter().zip(resultado.taxas_graduadas.iter()) {
+            approx::assert_abs_diff_eq!(bruta, graduada, epsilon = 1e-12);
+        }
+        approx::assert_abs_diff_eq!(resultado.termo_aderencia, 0.0, epsilon = 1e-20);
+    }
+
+    #[test]
+    fn whittaker_henderson_com_suavidade_alta_aproxima_polinomio() {
+        let taxas = criar_taxas_brutas();
+        let n = taxas.len();
+
+        let resultado = whittaker_henderson(
+            (40..40 + n as u16).collect(),
+            taxas,
+            vec![1.0; n],
+            2,
+            1e9,
+            Periodicidade::Anual,
+        );
+
+        approx::assert_abs_diff_eq!(resultado.termo_suavidade, 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn whittaker_henderson_aumentar_suavidade_troca_aderencia_por_suavidade() {
+        let taxas = criar_taxas_brutas();
+        let n = taxas.len();
+        let graduar = |suavidade| {
+            whittaker_henderson(
+                (40..40 + n as u16).collect(),
+                taxas.clone(),
+                vec![1.0; n],
+                3,
+                suavidade,
+                Periodicidade::Anual,
+            )
+        };
+
+        let pouco_suave = graduar(1.0);
+        let muito_suave = graduar(100.0);
+
+        assert!(muito_suave.termo_suavidade < pouco_suave.termo_suavidade);
+        assert!(muito_suave.termo_aderencia > pouco_suave.termo_aderencia);
+    }
+
+    #[test]
+    fn whittaker_henderson_preserva_taxa_media_ponderada() {
+        let taxas = criar_taxas_brutas();
+        let n = taxas.len();
+        let pesos: Vec<f64> = (0..n).map(|i| 100.0 + 10.0 * i as f64).collect();
+
+        let resultado = whittaker_henderson(
+            (40..40 + n as u16).collect(),
+            taxas.clone(),
+            pesos.clone(),
+            2,
+            50.0,
+            Periodicidade::Anual,
+        );
+
+        let media = |valores: &Vec<f64>| {
+            valores
+                .iter()
+                .zip(pesos.iter())
+                .map(|(v, w)| v * w)
+                .sum::<f64>()
+        };
+        approx::assert_relative_eq!(
+            media(&resultado.taxas_graduadas),
+            media(&taxas),
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn whittaker_henderson_experiencia_gera_tabua_com_taxas_graduadas() {
+        let experiencia = Experiencia::new(
+            vec![2, 3, 4, 5, 6],
+            vec![10.0, 14.0, 13.0, 20.0, 22.0],
+            vec![1000.0, 1000.0, 900.0, 900.0, 800.0],
+            Periodicidade::Mensal,
+        );
+
+        let resultado = whittaker_henderson_experiencia(&experiencia, 2, 10.0, true);
+
+        assert_eq!(resultado.tabua.periodicidade(), &Periodicidade::Mensal);
+        approx::assert_relative_eq!(
+            resultado.tabua.qx(&vec![4], 0),
+            resultado.taxas_graduadas[2]
+        );
+        approx::assert_relative_eq!(resultado.taxas_brutas[0], 1.0 - (-0.01_f64).exp());
+        assert_eq!(resultado.residuos.len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "As idades a serem graduadas devem ser consecutivas.")]
+    fn whittaker_henderson_da_erro_com_idades_nao_consecutivas() {
+        whittaker_henderson(
+            vec![1, 2, 4],
+            vec![0.1, 0.2, 0.3],
+            vec![1.0; 3],
+            1,
+            1.0,
+            Periodicidade::Anual,
+        );
+    }
+
+    #[test]
+    fn media_movel_ponderada_preserva_reta() {
+        let taxas: Vec<f64> = (0..10).map(|i| 0.01 + 0.002 * i as f64).collect();
+
+        let resultado = media_movel_ponderada(
+            (50..60).collect(),
+            taxas.clone(),
+            vec![1.0, 2.0, 3.0, 2.0, 1.0],
+            Periodicidade::Anual,
+        );
+
+        for (graduada, taxa) in resultado.taxas_graduadas[2..8].iter().zip(&taxas[2..8]) {
+            approx::assert_relative_eq!(graduada, taxa, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn media_movel_ponderada_renormaliza_pesos_nas_extremidades() {
+        let resultado = media_movel_ponderada(
+            vec![0, 1, 2],
+            vec![0.1, 0.4, 0.7],
+            vec![1.0, 1.0, 1.0],
+            Periodicidade::Anual,
+        );
+
+        approx::assert_relative_eq!(resultado.taxas_graduadas[0], 0.25);
+        approx::assert_relative_eq!(resultado.taxas_graduadas[1], 0.4);
+        approx::assert_relative_eq!(resultado.taxas_graduadas[2], 0.55);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::matriz_mortalidade::MatrizMortalidade;
+use crate::Periodicidade;
+use crate::Tabua;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SexoHmd {
+    Feminino,
+    Masculino,
+    Total,
+}
+
+impl SexoHmd {
+    fn coluna(&self) -> usize {
+        match self {
+            SexoHmd::Feminino => 2,
+            SexoHmd::Masculino => 3,
+            SexoHmd::Total => 4,
+        }
+    }
+}
+
+// Conteúdo de um arquivo 1x1 do Human Mortality Database (Mx, Deaths ou Exposures) para um
+// sexo. Valores ausentes (".") ficam como None.
+struct ArquivoHmd {
+    anos: Vec<i32>,
+    idades: Vec<u16>,
+    valores: Vec<Vec<Option<f64>>>,
+    possui_idade_aberta: bool,
+}
+
+fn ler_arquivo(caminho: &str) -> String {
+    return match std::fs::read_to_string(caminho) {
+        Ok(conteudo) => conteudo,
+        Err(erro) => panic!("Não foi possível ler o arquivo {caminho}: {erro}."),
+    };
+}
+
+// Anos com mudança territorial aparecem como "1914-" e "1914+"; a última ocorrência prevalece.
+fn interpretar_arquivo(conteudo: &str, sexo: &SexoHmd) -> ArquivoHmd {
+    let mut linhas = conteudo.lines();
+    if !linhas.any(|linha| linha.trim_start().starts_with("Year")) {
+        panic!("O arquivo não possui o cabeçalho de colunas do HMD.");
+    }
+
+    let mut anos: Vec<i32> = Vec::new();
+    let mut valores: Vec<Vec<Option<f64>>> = Vec::new();
+    let mut possui_idade_aberta = false;
+    for linha in linhas {
+        let campos: Vec<&str> = linha.split_whitespace().collect();
+        if campos.is_empty() {
+            continue;
+        }
+        if campos.len() != 5 {
+            panic!("Linha inválida no arquivo do HMD: {linha}");
+        }
+        let (texto_ano, texto_idade) = (campos[0], campos[1]);
+        let ano: i32 = texto_ano
+            .trim_end_matches(['+', '-'])
+            .parse()
+            .unwrap_or_else(|_| panic!("Ano inválido no arquivo do HMD: {texto_ano}"));
+        let idade_aberta = texto_idade.ends_with('+');
+        let idade: usize = texto_idade
+            .trim_end_matches('+')
+            .parse()
+            .unwrap_or_else(|_| panic!("Idade inválida no arquivo do HMD: {texto_idade}"));
+        let valor = match campos[sexo.coluna()] {
+            "." => None,
+            texto => Some(
+                texto
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Valor inválido no arquivo do HMD: {texto}")),
+            ),
+        };
+
+        if idade == 0 {
+            if anos.last() == Some(&ano) {
+                valores.pop();
+                anos.pop();
+            }
+            anos.push(ano);
+            valores.push(Vec::new());
+        }
+        match valores.last_mut() {
+            Some(linha_ano) if linha_ano.len() == idade => linha_ano.push(valor),
+            _ => panic!("As idades do ano {ano} não são consecutivas a partir de 0."),
+        }
+        possui_idade_aberta = idade_aberta;
+    }
+
+    if anos.is_empty() {
+        panic!("O arquivo do HMD não possui dados.");
+    }
+    let quantidade_idades = valores[0].len();
+    if valores.iter().any(|linha| linha.len() != quantidade_idades) {
+        panic!("Todos os anos do arquivo do HMD devem possuir as mesmas idades.");
+    }
+    return ArquivoHmd {
+        anos,
+        idades: (0..quantidade_idades as u16).collect(),
+        valores,
+        possui_idade_aberta,
+    };
+}
+
+// Taxas centrais anuais por ano e idade lidas do HMD. As idades são em anos, a partir de 0, e a
+// última idade é o grupo aberto (110+), que vira o platô da tábua.
+#[derive(Debug, Clone)]
+pub struct DadosHmd {
+    anos: Vec<i32>,
+    idades: Vec<u16>,
+    taxas_centrais: Vec<Vec<Option<f64>>>,
+    possui_idade_aberta: bool,
+}
+
+impl DadosHmd {
+    pub fn ler_taxas(caminho_mx: &str, sexo: SexoHmd) -> Self {
+        let arquivo = interpretar_arquivo(&ler_arquivo(caminho_mx), &sexo);
+        return DadosHmd {
+            anos: arquivo.anos,
+            idades: arquivo.idades,
+            taxas_centrais: arquivo.valores,
+            possui_idade_aberta: arquivo.possui_idade_aberta,
+        };
+    }
+
+    // m = D / E; idades sem exposição ficam sem taxa.
+    pub fn ler_obitos_e_expostos(
+        caminho_obitos: &str,
+        caminho_expostos: &str,
+        sexo: SexoHmd,
+    ) -> Self {
+        let obitos = interpretar_arquivo(&ler_arquivo(caminho_obitos), &sexo);
+        let expostos = interpretar_arquivo(&ler_arquivo(caminho_expostos), &sexo);
+        if obitos.anos != expostos.anos || obitos.idades != expostos.idades {
+            panic!("Os arquivos de óbitos e expostos devem possuir os mesmos anos e idades.");
+        }
+        let taxas_centrais = obitos
+            .valores
+            .iter()
+            .zip(expostos.valores.iter())
+            .map(|(d, e)| {
+                d.iter()
+                    .zip(e.iter())
+                    .map(|(d, e)| match (d, e) {
+                        (Some(d), Some(e)) if *e > 0.0 => Some(d / e),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        return DadosHmd {
+            anos: obitos.anos,
+            idades: obitos.idades,
+            taxas_centrais,
+            possui_idade_aberta: obitos.possui_idade_aberta,
+        };
+    }
+
+    pub fn anos(&self) -> &Vec<i32> {
+        return &self.anos;
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn taxas_centrais(&self) -> &Vec<Vec<Option<f64>>> {
+        return &self.taxas_centrais;
+    }
+
+    pub fn possui_idade_aberta(&self) -> bool {
+        return self.possui_idade_aberta;
+    }
+
+    fn indice_ano(&self, ano: i32) -> usize {
+        return match self.anos.iter().position(|a| *a == ano) {
+            Some(indice) => indice,
+            None => panic!("O ano {ano} não está disponível nos dados do HMD."),
+        };
+    }
+
+    fn taxas_do_ano(&self, ano: i32, idade_inicial: u16, idade_final: u16) -> Vec<f64> {
+        let linha = &self.taxas_centrais[self.indice_ano(ano)];
+        return (idade_inicial..=idade_final)
+            .map(|idade| match linha.get(idade as usize) {
+                Some(Some(m)) => *m,
+                Some(None) => panic!("O ano {ano} não possui taxa para a idade {idade}."),
+                None => panic!("A idade {idade} não está disponível nos dados do HMD."),
+            })
+            .collect();
+    }
+
+    pub fn matriz(&self) -> MatrizMortalidade {
+        let idade_final = self.idades[self.idades.len() - 1];
+        return self.matriz_recortada(self.anos[0], self.anos[self.anos.len() - 1], 0, idade_final);
+    }
+
+    pub fn matriz_recortada(
+        &self,
+        ano_inicial: i32,
+        ano_final: i32,
+        idade_inicial: u16,
+        idade_final: u16,
+    ) -> MatrizMortalidade {
+        if ano_inicial > ano_final || idade_inicial > idade_final {
+            panic!("O intervalo de anos e idades do recorte é inválido.");
+        }
+        let taxas = (ano_inicial..=ano_final)
+            .map(|ano| self.taxas_do_ano(ano, idade_inicial, idade_final))
+            .collect();
+        return MatrizMortalidade::new(
+            (ano_inicial..=ano_final).collect(),
+            (idade_inicial..=idade_final).collect(),
+            taxas,
+        );
+    }
+
+    // Supõe força de mortalidade constante em cada idade, de modo que cada período da idade x
+    // tem qx = 1 - exp(-m / períodos por ano). A última idade se repete como platô.
+    pub fn tabua(&self, ano: i32, periodicidade: Periodicidade) -> Tabua {
+        let idade_final = self.idades[self.idades.len() - 1];
+        let periodos = periodicidade.quantidade_periodos_1_ano();
+        let qx = self
+            .taxas_do_ano(ano, 0, idade_final)
+            .iter()
+            .flat_map(|m| {
+                std::iter::repeat_n(1.0 - (-m / periodos as f64).exp(), periodos as usize)
+            })
+            .collect();
+        return Tabua::new(qx, periodicidade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::TabuaInterface;
+    use approx;
+    use infinitable::Infinitable;
+
+    const MX: &str = "Testland, Death rates (period 1x1)\tLast modified: 01 Jan 2024;  Methods Protocol: v6 (2017)
+
+  Year          Age             Female            Male           Total
+  2000            0           0.005000        0.006000        0.005500
+  2000            1           0.001000        0.001200        0.001100
+  2000            2+          0.500000        0.600000        0.550000
+  2001            0           0.004000        0.005000        0.004500
+  2001            1           0.000900        .               0.001000
+  2001            2+          0.450000        0.550000        0.500000
+";
+
+    fn escrever_arquivo(nome: &str, conteudo: &str) -> String {
+        let caminho = std::env::temp_dir().join(format!("tabatu_hmd_{nome}"));
+        std::fs::write(&caminho, conteudo).unwrap();
+        return caminho.to_str().unwrap().to_string();
+    }
+
+    #[test]
+    fn le_taxas_por_ano_e_idade() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx.txt", MX), SexoHmd::Feminino);
+
+        assert_eq!(dados.anos(), &vec![2000, 2001]);
+        assert_eq!(dados.idades(), &vec![0, 1, 2]);
+        assert!(dados.possui_idade_aberta());
+        assert_eq!(dados.taxas_centrais()[1][1], Some(0.0009));
+    }
+
+    #[test]
+    fn valores_ausentes_ficam_sem_taxa() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx_m.txt", MX), SexoHmd::Masculino);
+
+        assert_eq!(dados.taxas_centrais()[1][1], None);
+    }
+
+    #[test]
+    #[should_panic(expected = "O ano 2001 não possui taxa para a idade 1.")]
+    fn tabua_da_erro_com_taxa_ausente() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx_m2.txt", MX), SexoHmd::Masculino);
+
+        dados.tabua(2001, Periodicidade::Anual);
+    }
+
+    #[test]
+    fn idade_aberta_vira_plato() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx_t.txt", MX), SexoHmd::Total);
+
+        let tabua = dados.tabua(2000, Periodicidade::Anual);
+
+        assert!(tabua.obter_tabua_base().possui_fechamento_plato());
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Infinitable::Infinity);
+        approx::assert_relative_eq!(tabua.qx(&vec![5], 0), 1.0 - (-0.55_f64).exp());
+    }
+
+    #[test]
+    fn tabua_mensal_usa_forca_constante_dentro_da_idade() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx_f.txt", MX), SexoHmd::Feminino);
+
+        let tabua = dados.tabua(2001, Periodicidade::Mensal);
+
+        approx::assert_relative_eq!(tabua.tpx(&vec![0], 12), (-0.004_f64).exp(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn obitos_e_expostos_geram_taxas_centrais() {
+        let obitos = "Testland, Deaths (period 1x1)
+
+  Year          Age             Female            Male           Total
+  2000            0              10.00           12.00           22.00
+  2000            1+              5.00            0.00            5.00
+";
+        let expostos = "Testland, Exposure to risk (period 1x1)
+
+  Year          Age             Female            Male           Total
+  2000            0            1000.00         1200.00         2200.00
+  2000            1+             10.00            0.00           10.00
+";
+        let dados = DadosHmd::ler_obitos_e_expostos(
+            &escrever_arquivo("deaths.txt", obitos),
+            &escrever_arquivo("exposures.txt", expostos),
+            SexoHmd::Masculino,
+        );
+
+        assert_eq!(dados.taxas_centrais()[0], vec![Some(0.01), None]);
+    }
+
+    #[test]
+    fn matriz_recortada_serve_para_os_modelos_estocasticos() {
+        let dados = DadosHmd::ler_taxas(&escrever_arquivo("mx_r.txt", MX), SexoHmd::Total);
+
+        let matriz = dados.matriz_recortada(2000, 2001, 0, 1);
+
+        assert_eq!(matriz.anos(), &vec![2000, 2001]);
+        assert_eq!(matriz.taxas_centrais()[1], vec![0.0045, 0.001]);
+        assert_eq!(dados.matriz().idades().len(), 3);
+    }
+}
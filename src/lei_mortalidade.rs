@@ -0,0 +1,411 @@
+use crate::interface::{validar_idades_tabuas, TabuaInterface};
+use crate::Periodicidade;
+use crate::Tabua;
+use infinitable::Infinitable;
+
+// Os parâmetros são sempre expressos em anos. A periodicidade da lei só define a unidade em que
+// x e t são informados em qx e tpx.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParametrosLei {
+    // mu(x) = b * c^x
+    Gompertz {
+        b: f64,
+        c: f64,
+    },
+    // mu(x) = a + b * c^x
+    Makeham {
+        a: f64,
+        b: f64,
+        c: f64,
+    },
+    // mu(x) = k * x^n
+    Weibull {
+        k: f64,
+        n: f64,
+    },
+    // qx / px = a^((x + b)^c) + d * exp(-e * (ln(x) - ln(f))^2) + g * h^x
+    HeligmanPollard {
+        a: f64,
+        b: f64,
+        c: f64,
+        d: f64,
+        e: f64,
+        f: f64,
+        g: f64,
+        h: f64,
+    },
+    // mu(x) = (a + b * c^x) / (1 + d * c^x)
+    Perks {
+        a: f64,
+        b: f64,
+        c: f64,
+        d: f64,
+    },
+}
+
+pub(crate) fn parametros_validos(parametros: &ParametrosLei) -> bool {
+    let valores_finitos = match *parametros {
+        ParametrosLei::Gompertz { b, c } => vec![b, c],
+        ParametrosLei::Makeham { a, b, c } => vec![a, b, c],
+        ParametrosLei::Weibull { k, n } => vec![k, n],
+        ParametrosLei::HeligmanPollard {
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            g,
+            h,
+        } => vec![a, b, c, d, e, f, g, h],
+        ParametrosLei::Perks { a, b, c, d } => vec![a, b, c, d],
+    }
+    .iter()
+    .all(|v| v.is_finite());
+
+    let valido = match *parametros {
+        ParametrosLei::Gompertz { b, c } => b > 0.0 && c > 1.0,
+        ParametrosLei::Makeham { a, b, c } => a >= 0.0 && b > 0.0 && c > 1.0,
+        ParametrosLei::Weibull { k, n } => k > 0.0 && n > -1.0,
+        ParametrosLei::HeligmanPollard {
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            g,
+            h,
+        } => {
+            a > 0.0
+                && a < 1.0
+                && b >= 0.0
+                && c > 0.0
+                && d >= 0.0
+                && e > 0.0
+                && f > 0.0
+                && g > 0.0
+                && h > 0.0
+        }
+        ParametrosLei::Perks { a, b, c, d } => a >= 0.0 && b > 0.0 && c > 1.0 && d >= 0.0,
+    };
+    return valores_finitos && valido;
+}
+
+fn validar_parametros(parametros: &ParametrosLei) {
+    if !parametros_validos(parametros) {
+        panic!("Parâmetros inválidos para a lei de mortalidade: {parametros:?}.");
+    }
+}
+
+fn qx_heligman_pollard(parametros: &ParametrosLei, idade: f64) -> f64 {
+    match *parametros {
+        ParametrosLei::HeligmanPollard {
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            g,
+            h,
+        } => {
+            let razao = a.powf((idade + b).powf(c))
+                + d * (-e * (idade.ln() - f.ln()).powi(2)).exp()
+                + g * h.powf(idade);
+            if !razao.is_finite() {
+                return 1.0;
+            }
+            return razao / (1.0 + razao);
+        }
+        _ => panic!("qx_heligman_pollard só deve ser chamado com parâmetros de Heligman-Pollard."),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeiMortalidade {
+    parametros: ParametrosLei,
+    periodicidade: Periodicidade,
+}
+
+impl LeiMortalidade {
+    pub fn new(parametros: ParametrosLei, periodicidade: Periodicidade) -> Self {
+        validar_parametros(&parametros);
+        return LeiMortalidade {
+            parametros,
+            periodicidade,
+        };
+    }
+
+    pub fn gompertz(b: f64, c: f64, periodicidade: Periodicidade) -> Self {
+        return LeiMortalidade::new(ParametrosLei::Gompertz { b, c }, periodicidade);
+    }
+
+    pub fn makeham(a: f64, b: f64, c: f64, periodicidade: Periodicidade) -> Self {
+        return LeiMortalidade::new(ParametrosLei::Makeham { a, b, c }, periodicidade);
+    }
+
+    pub fn weibull(k: f64, n: f64, periodicidade: Periodicidade) -> Self {
+        return LeiMortalidade::new(ParametrosLei::Weibull { k, n }, periodicidade);
+    }
+
+    pub fn perks(a: f64, b: f64, c: f64, d: f64, periodicidade: Periodicidade) -> Self {
+        return LeiMortalidade::new(ParametrosLei::Perks { a, b, c, d }, periodicidade);
+    }
+
+    pub fn parametros(&self) -> &ParametrosLei {
+        return &self.parametros;
+    }
+
+    // Força de mortalidade na idade informada em anos. Para Heligman-Pollard, que é definida
+    // sobre qx anual, assume-se força constante dentro de cada ano de idade.
+    pub fn forca_mortalidade(&self, idade: f64) -> f64 {
+        return match self.parametros {
+            ParametrosLei::Gompertz { b, c } => b * c.powf(idade),
+            ParametrosLei::Makeham { a, b, c } => a + b * c.powf(idade),
+            ParametrosLei::Weibull { k, n } => k * idade.powf(n),
+            ParametrosLei::HeligmanPollard { .. } => {
+                -(1.0 - qx_heligman_pollard(&self.parametros, idade.floor())).ln()
+            }
+            ParametrosLei::Perks { a, b, c, d } => {
+                (a + b * c.powf(idade)) / (1.0 + d * c.powf(idade))
+            }
+        };
+    }
+
+    // Integral da força de mortalidade entre 0 e a idade informada em anos.
+    pub fn forca_acumulada(&self, idade: f64) -> f64 {
+        return match self.parametros {
+            ParametrosLei::Gompertz { b, c } => b / c.ln() * (c.powf(idade) - 1.0),
+            ParametrosLei::Makeham { a, b, c } => a * idade + b / c.ln() * (c.powf(idade) - 1.0),
+            ParametrosLei::Weibull { k, n } => k / (n + 1.0) * idade.powf(n + 1.0),
+            ParametrosLei::HeligmanPollard { .. } => {
+                let inteira = idade.floor();
+                let acumulada = (0..inteira as u32).fold(0.0, |acc, k| {
+                    acc - (1.0 - qx_heligman_pollard(&self.parametros, k as f64)).ln()
+                });
+                acumulada + (idade - inteira) * self.forca_mortalidade(inteira)
+            }
+            ParametrosLei::Perks { a, b, c, d } => {
+                if d == 0.0 {
+                    a * idade + b / c.ln() * (c.powf(idade) - 1.0)
+                } else {
+                    a * idade
+                        + (b / d - a) / c.ln() * ((1.0 + d * c.powf(idade)).ln() - (1.0 + d).ln())
+                }
+            }
+        };
+    }
+
+    // Integral da força de mortalidade entre duas idades informadas em anos.
+    pub fn forca_acumulada_entre(&self, inicio: f64, fim: f64) -> f64 {
+        return match self.parametros {
+            ParametrosLei::HeligmanPollard { .. } => {
+                let mut acumulada = 0.0;
+                let mut idade = inicio.floor();
+                while idade < fim {
+                    let exposicao = (idade + 1.0).min(fim) - idade.max(inicio);
+                    acumulada += exposicao * self.forca_mortalidade(idade);
+                    idade += 1.0;
+                }
+                acumulada
+            }
+            _ => self.forca_acumulada(fim) - self.forca_acumulada(inicio),
+        };
+    }
+
+    fn sobrevivencia(&self, idade: u16, t: u16) -> f64 {
+        let periodos = self.periodicidade.quantidade_periodos_1_ano() as f64;
+        let inicio = idade as f64 / periodos;
+        let fim = (idade as f64 + t as f64) / periodos;
+        let diferenca = self.forca_acumulada_entre(inicio, fim);
+        if diferenca.is_nan() {
+            return 0.0;
+        }
+        return (-diferenca).exp();
+    }
+
+    pub fn gerar_tabua(&self, quantidade_periodos: u16) -> Tabua {
+        if quantidade_periodos == 0 {
+            panic!("A quantidade de períodos da tábua gerada deve ser maior que zero.");
+        }
+        let qx = (0..quantidade_periodos)
+            .map(|x| 1.0 - self.sobrevivencia(x, 1))
+            .collect();
+        return Tabua::new(qx, self.periodicidade.clone());
+    }
+}
+
+impl TabuaInterface for LeiMortalidade {
+    fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return 1;
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return Infinitable::Infinity;
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return 1.0 - self.sobrevivencia(x[0].saturating_add(t), 1);
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return self.sobrevivencia(x[0], t);
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        return LeiMortalidade::new(self.parametros.clone(), nova_periodicidade);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    fn criar_heligman_pollard() -> LeiMortalidade {
+        LeiMortalidade::new(
+            ParametrosLei::HeligmanPollard {
+                a: 0.0005,
+                b: 0.01,
+                c: 0.1,
+                d: 0.001,
+                e: 10.0,
+                f: 20.0,
+                g: 0.00005,
+                h: 1.1,
+            },
+            Periodicidade::Anual,
+        )
+    }
+
+    #[test]
+    fn tpx_de_gompertz_segue_forma_fechada() {
+        let lei = LeiMortalidade::gompertz(0.0001, 1.1, Periodicidade::Anual);
+        let (x, t) = (40.0_f64, 10.0_f64);
+
+        let esperado = (-0.0001 / 1.1_f64.ln() * 1.1_f64.powf(x) * (1.1_f64.powf(t) - 1.0)).exp();
+
+        approx::assert_relative_eq!(lei.tpx(&vec![40], 10), esperado, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn makeham_sem_termo_constante_eh_gompertz() {
+        let gompertz = LeiMortalidade::gompertz(0.0001, 1.1, Periodicidade::Anual);
+        let makeham = LeiMortalidade::makeham(0.0, 0.0001, 1.1, Periodicidade::Anual);
+
+        approx::assert_relative_eq!(gompertz.tpx(&vec![30], 25), makeham.tpx(&vec![30], 25));
+        approx::assert_relative_eq!(gompertz.qx(&vec![30], 25), makeham.qx(&vec![30], 25));
+    }
+
+    #[test]
+    fn perks_sem_denominador_eh_makeham() {
+        let makeham = LeiMortalidade::makeham(0.001, 0.0001, 1.1, Periodicidade::Anual);
+        let perks = LeiMortalidade::perks(0.001, 0.0001, 1.1, 0.0, Periodicidade::Anual);
+
+        approx::assert_relative_eq!(makeham.tpx(&vec![30], 25), perks.tpx(&vec![30], 25));
+    }
+
+    #[test]
+    fn forca_acumulada_de_perks_eh_integral_da_forca() {
+        let lei = LeiMortalidade::perks(0.001, 0.0001, 1.1, 0.00005, Periodicidade::Anual);
+
+        let passos = 10000;
+        let integral_numerica = (0..passos).fold(0.0, |acc, i| {
+            acc + lei.forca_mortalidade(80.0 * (i as f64 + 0.5) / passos as f64)
+                * (80.0 / passos as f64)
+        });
+
+        approx::assert_relative_eq!(lei.forca_acumulada(80.0), integral_numerica, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn tpx_de_weibull_segue_forma_fechada() {
+        let lei = LeiMortalidade::weibull(1e-8, 3.5, Periodicidade::Anual);
+
+        let esperado = (-1e-8 / 4.5 * (70.0_f64.powf(4.5) - 60.0_f64.powf(4.5))).exp();
+
+        approx::assert_relative_eq!(lei.tpx(&vec![60], 10), esperado, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn qx_anual_de_heligman_pollard_segue_a_formula() {
+        let lei = criar_heligman_pollard();
+        let x = 50.0_f64;
+
+        let razao = 0.0005_f64.powf((x + 0.01).powf(0.1))
+            + 0.001 * (-10.0 * (x.ln() - 20.0_f64.ln()).powi(2)).exp()
+            + 0.00005 * 1.1_f64.powf(x);
+
+        approx::assert_relative_eq!(lei.qx(&vec![50], 0), razao / (1.0 + razao), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn qx_eh_consistente_com_tpx() {
+        let lei = criar_heligman_pollard().alterar_periodicidade(Periodicidade::Mensal);
+        let x = vec![600];
+
+        for t in 0..30 {
+            approx::assert_relative_eq!(
+                lei.tpx(&x, t + 1),
+                lei.tpx(&x, t) * (1.0 - lei.qx(&x, t)),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn alterar_periodicidade_preserva_a_sobrevivencia_em_anos() {
+        let anual = LeiMortalidade::makeham(0.0005, 0.00005, 1.1, Periodicidade::Anual);
+        let mensal = anual.alterar_periodicidade(Periodicidade::Mensal);
+
+        assert_eq!(mensal.periodicidade(), &Periodicidade::Mensal);
+        approx::assert_relative_eq!(
+            anual.tpx(&vec![40], 5),
+            mensal.tpx(&vec![480], 60),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn tempo_futuro_maximo_eh_infinito() {
+        let lei = LeiMortalidade::gompertz(0.0001, 1.1, Periodicidade::Anual);
+
+        assert_eq!(lei.tempo_futuro_maximo(&vec![0]), Infinitable::Infinity);
+        assert_eq!(lei.tempo_futuro_maximo(&vec![100]), Infinitable::Infinity);
+    }
+
+    #[test]
+    fn gerar_tabua_reproduz_qx_da_lei() {
+        let lei = LeiMortalidade::gompertz(0.0001, 1.1, Periodicidade::Trimestral);
+
+        let tabua = lei.gerar_tabua(400);
+
+        assert_eq!(tabua.periodicidade(), &Periodicidade::Trimestral);
+        for x in [0, 100, 250, 399] {
+            approx::assert_relative_eq!(tabua.qx(&vec![x], 0), lei.qx(&vec![x], 0));
+            approx::assert_relative_eq!(
+                tabua.tpx(&vec![0], x),
+                lei.tpx(&vec![0], x),
+                epsilon = 1e-9
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Parâmetros inválidos para a lei de mortalidade")]
+    fn gompertz_nao_aceita_c_menor_ou_igual_a_1() {
+        LeiMortalidade::gompertz(0.0001, 1.0, Periodicidade::Anual);
+    }
+}
@@ -1,19 +1,68 @@
+pub use crate::ajuste_lei::{MetodoAjuste, ResultadoAjuste, TipoLei};
+pub use crate::anuidade_fracionada::AproximacaoFracionada;
+pub use crate::credibilidade::{AplicacaoCredibilidade, ResultadoCredibilidade};
+pub use crate::escala_melhoria::{EscalaMelhoria, SuavizacaoMelhoria};
+pub use crate::estudo_experiencia::{
+    BaseIdade, Data, EstudoExperiencia, MetodoExposicao, RegistroApolice,
+};
+pub use crate::expectativa::TempoVida;
+pub use crate::experiencia::Experiencia;
+pub use crate::fechamento::{MetodoFechamento, ResultadoFechamento};
+pub use crate::forca_mortalidade::{ForcaMortalidade, MetodoForcaMortalidade};
+pub use crate::glm::{FamiliaGlm, ObservacaoGlm, ResultadoGlm};
+pub use crate::graduacao::ResultadoGraduacao;
+pub use crate::hmd::{DadosHmd, SexoHmd};
 pub use crate::interface::{JurosInterface, TabuaInterface};
 pub use crate::juros_constante::JurosConstante;
+pub use crate::lei_mortalidade::{LeiMortalidade, ParametrosLei};
+pub use crate::matriz_mortalidade::MatrizMortalidade;
+pub use crate::modelo_estocastico::{Cbd, LeeCarter};
 pub use crate::periodicidade::Periodicidade;
 pub use crate::periodicidade::Periodicidade::{
     Anual, Bimestral, Mensal, Quadrimestral, Semestral, Trimestral,
 };
-pub use crate::tabua::Tabua;
-pub use crate::tabua_mdt::TabuaMDT;
-pub use crate::tabua_multiplas_vidas::StatusVidasConjuntas::{First, Last};
+pub use crate::real_esperado::{FaixaRealEsperado, ModeloRealEsperado, ResultadoRealEsperado};
+pub use crate::status::{
+    StatusConjunto, StatusDiferido, StatusPrazoCerto, StatusUltimoSobrevivente,
+};
+pub use crate::tabua::{RegraIdadeInferior, Tabua};
+pub use crate::tabua_geracional::TabuaGeracional;
+pub use crate::tabua_mdt::{HipoteseMdt, ResultadoInversaoMdt, TabuaMDT};
+pub use crate::tabua_mistura::TabuaMistura;
+pub use crate::tabua_multiplas_vidas::StatusVidasConjuntas::{Exatamente, First, Last, PeloMenos};
 pub use crate::tabua_multiplas_vidas::{StatusVidasConjuntas, TabuaMultiplasVidas};
+pub use crate::tabua_vida::TabuaVida;
 
+pub mod ajuste_lei;
+mod aleatorio;
+mod algebra_linear;
 pub mod alterar;
+pub mod anuidade_fracionada;
+pub mod beneficio_contingente;
+pub mod credibilidade;
+pub mod escala_melhoria;
+mod estatistica;
+pub mod estudo_experiencia;
+pub mod expectativa;
+pub mod experiencia;
+pub mod fechamento;
+pub mod forca_mortalidade;
+pub mod glm;
+pub mod graduacao;
+pub mod hmd;
 pub mod interface;
 pub mod juros_constante;
+pub mod lei_mortalidade;
+pub mod matriz_mortalidade;
+pub mod modelo_estocastico;
+mod otimizacao;
 pub mod periodicidade;
+pub mod real_esperado;
+pub mod status;
 pub mod tabua;
 mod tabua_base;
+pub mod tabua_geracional;
 pub mod tabua_mdt;
+pub mod tabua_mistura;
 pub mod tabua_multiplas_vidas;
+pub mod tabua_vida;
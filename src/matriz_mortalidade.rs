@@ -0,0 +1,90 @@
+// Taxas centrais de mortalidade anuais por ano calendário e idade, no formato usado pelos
+// modelos estocásticos. As taxas são indexadas como taxas_centrais[ano][idade].
+#[derive(Debug, Clone)]
+pub struct MatrizMortalidade {
+    anos: Vec<i32>,
+    idades: Vec<u16>,
+    taxas_centrais: Vec<Vec<f64>>,
+}
+
+fn consecutivos(valores: Vec<i64>) -> bool {
+    return valores.windows(2).all(|par| par[1] == par[0] + 1);
+}
+
+impl MatrizMortalidade {
+    pub fn new(anos: Vec<i32>, idades: Vec<u16>, taxas_centrais: Vec<Vec<f64>>) -> Self {
+        if anos.is_empty() || idades.is_empty() {
+            panic!("A matriz de mortalidade deve possuir pelo menos um ano e uma idade.");
+        }
+        if !consecutivos(anos.iter().map(|v| *v as i64).collect())
+            || !consecutivos(idades.iter().map(|v| *v as i64).collect())
+        {
+            panic!("Os anos e as idades da matriz de mortalidade devem ser consecutivos.");
+        }
+        if taxas_centrais.len() != anos.len()
+            || taxas_centrais
+                .iter()
+                .any(|linha| linha.len() != idades.len())
+        {
+            panic!("A matriz de taxas deve possuir uma linha por ano e uma coluna por idade.");
+        }
+        if taxas_centrais
+            .iter()
+            .flatten()
+            .any(|m| *m < 0.0 || !m.is_finite())
+        {
+            panic!("As taxas centrais devem ser finitas e maiores que ou iguais a zero.");
+        }
+        return MatrizMortalidade {
+            anos,
+            idades,
+            taxas_centrais,
+        };
+    }
+
+    pub fn anos(&self) -> &Vec<i32> {
+        return &self.anos;
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn taxas_centrais(&self) -> &Vec<Vec<f64>> {
+        return &self.taxas_centrais;
+    }
+
+    // Probabilidades anuais supondo força de mortalidade constante em cada idade e ano.
+    pub fn qx(&self) -> Vec<Vec<f64>> {
+        return self
+            .taxas_centrais
+            .iter()
+            .map(|linha| linha.iter().map(|m| 1.0 - (-m).exp()).collect())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn qx_usa_forca_constante() {
+        let matriz = MatrizMortalidade::new(vec![2000], vec![60, 61], vec![vec![0.01, 0.02]]);
+
+        approx::assert_relative_eq!(matriz.qx()[0][1], 1.0 - (-0.02_f64).exp());
+    }
+
+    #[test]
+    #[should_panic(expected = "uma linha por ano")]
+    fn dimensoes_devem_ser_compativeis() {
+        MatrizMortalidade::new(vec![2000, 2001], vec![60], vec![vec![0.01]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "consecutivos")]
+    fn anos_devem_ser_consecutivos() {
+        MatrizMortalidade::new(vec![2000, 2002], vec![60], vec![vec![0.01], vec![0.01]]);
+    }
+}
@@ -0,0 +1,627 @@
+use crate::aleatorio::GeradorAleatorio;
+use crate::estatistica::regressao_linear;
+use crate::interface::TabuaInterface;
+use crate::matriz_mortalidade::MatrizMortalidade;
+use crate::tabua::RegraIdadeInferior;
+use crate::tabua_geracional::TabuaGeracional;
+use crate::Periodicidade;
+use crate::Tabua;
+
+fn validar_matriz(matriz: &MatrizMortalidade) {
+    if matriz.anos().len() < 3 {
+        panic!("São necessários pelo menos três anos para ajustar o modelo.");
+    }
+    if matriz.taxas_centrais().iter().flatten().any(|m| *m <= 0.0) {
+        panic!("As taxas centrais devem ser positivas para o ajuste do modelo.");
+    }
+}
+
+// Monta a tábua geracional anual a partir dos qx por ano (anos ajustados seguidos dos
+// projetados) e converte para a periodicidade pedida.
+fn montar_tabua_geracional(
+    qx_por_ano: Vec<Vec<f64>>,
+    ano_inicial: i32,
+    idade_inicial: u16,
+    ano_avaliacao: i32,
+    periodicidade: Periodicidade,
+) -> TabuaGeracional {
+    let tabuas = qx_por_ano
+        .iter()
+        .map(|qx| {
+            Tabua::a_partir_da_idade(
+                idade_inicial,
+                qx.clone(),
+                Periodicidade::Anual,
+                RegraIdadeInferior::Erro,
+            )
+        })
+        .collect();
+    return TabuaGeracional::new(tabuas, ano_inicial, ano_avaliacao)
+        .alterar_periodicidade(periodicidade);
+}
+
+fn validar_ano_avaliacao(anos: &Vec<i32>, horizonte: usize, ano_avaliacao: i32) {
+    let ultimo_ano = anos[anos.len() - 1] + horizonte as i32;
+    if ano_avaliacao < anos[0] || ano_avaliacao > ultimo_ano {
+        panic!("O ano de avaliação deve estar entre o primeiro ano ajustado e o último projetado.");
+    }
+}
+
+// Primeiro vetor singular à esquerda de z (idades x anos) pelo método da potência sobre z * z'.
+fn primeiro_vetor_singular(z: &Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let n = z.len();
+    let mut b = vec![1.0 / (n as f64).sqrt(); n];
+    for _ in 0..10000 {
+        let zt_b: Vec<f64> = (0..z[0].len())
+            .map(|t| (0..n).map(|i| z[i][t] * b[i]).sum())
+            .collect();
+        let novo: Vec<f64> = z
+            .iter()
+            .map(|linha| linha.iter().zip(zt_b.iter()).map(|(a, c)| a * c).sum())
+            .collect();
+        let norma = novo.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if norma == 0.0 {
+            return None;
+        }
+        let novo: Vec<f64> = novo.iter().map(|v| v / norma).collect();
+        let diferenca = novo
+            .iter()
+            .zip(b.iter())
+            .map(|(a, c)| (a - c).abs())
+            .fold(0.0, f64::max);
+        b = novo;
+        if diferenca < 1e-14 {
+            break;
+        }
+    }
+    return Some(b);
+}
+
+// ln m(x, t) = a(x) + b(x) * k(t), com sum(b) = 1 e sum(k) = 0. O ajuste usa a decomposição em
+// valores singulares da matriz centrada e k(t) é projetado como passeio aleatório com deriva.
+#[derive(Debug, Clone)]
+pub struct LeeCarter {
+    anos: Vec<i32>,
+    idades: Vec<u16>,
+    ax: Vec<f64>,
+    bx: Vec<f64>,
+    kt: Vec<f64>,
+    deriva: f64,
+    volatilidade: f64,
+}
+
+impl LeeCarter {
+    pub fn ajustar(matriz: &MatrizMortalidade) -> Self {
+        validar_matriz(matriz);
+        let taxas = matriz.taxas_centrais();
+        let quantidade_anos = matriz.anos().len();
+        let quantidade_idades = matriz.idades().len();
+
+        let ax: Vec<f64> = (0..quantidade_idades)
+            .map(|i| taxas.iter().map(|linha| linha[i].ln()).sum::<f64>() / quantidade_anos as f64)
+            .collect();
+        let z: Vec<Vec<f64>> = (0..quantidade_idades)
+            .map(|i| taxas.iter().map(|linha| linha[i].ln() - ax[i]).collect())
+            .collect();
+
+        let (bx, kt) = match primeiro_vetor_singular(&z) {
+            Some(vetor) => {
+                let soma: f64 = vetor.iter().sum();
+                if soma == 0.0 {
+                    panic!("Não foi possível normalizar b(x) do modelo de Lee-Carter.");
+                }
+                let kt = (0..quantidade_anos)
+                    .map(|t| {
+                        (0..quantidade_idades)
+                            .map(|i| z[i][t] * vetor[i])
+                            .sum::<f64>()
+                            * soma
+                    })
+                    .collect();
+                (vetor.iter().map(|b| b / soma).collect(), kt)
+            }
+            None => (
+                vec![1.0 / quantidade_idades as f64; quantidade_idades],
+                vec![0.0; quantidade_anos],
+            ),
+        };
+        let (deriva, volatilidade) = estimar_passeio_aleatorio(&kt);
+
+        return LeeCarter {
+            anos: matriz.anos().clone(),
+            idades: matriz.idades().clone(),
+            ax,
+            bx,
+            kt,
+            deriva,
+            volatilidade,
+        };
+    }
+
+    pub fn anos(&self) -> &Vec<i32> {
+        return &self.anos;
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn ax(&self) -> &Vec<f64> {
+        return &self.ax;
+    }
+
+    pub fn bx(&self) -> &Vec<f64> {
+        return &self.bx;
+    }
+
+    pub fn kt(&self) -> &Vec<f64> {
+        return &self.kt;
+    }
+
+    pub fn deriva(&self) -> f64 {
+        return self.deriva;
+    }
+
+    pub fn volatilidade(&self) -> f64 {
+        return self.volatilidade;
+    }
+
+    pub fn taxas_centrais(&self, kt: f64) -> Vec<f64> {
+        return self
+            .ax
+            .iter()
+            .zip(self.bx.iter())
+            .map(|(a, b)| (a + b * kt).exp())
+            .collect();
+    }
+
+    pub fn projetar_kt(&self, horizonte: usize) -> Vec<f64> {
+        let ultimo = self.kt[self.kt.len() - 1];
+        return (1..=horizonte)
+            .map(|h| ultimo + self.deriva * h as f64)
+            .collect();
+    }
+
+    pub fn simular_kt(
+        &self,
+        horizonte: usize,
+        quantidade_cenarios: usize,
+        semente: u64,
+    ) -> Vec<Vec<f64>> {
+        let mut gerador = GeradorAleatorio::new(semente);
+        return (0..quantidade_cenarios)
+            .map(|_| {
+                let mut k = self.kt[self.kt.len() - 1];
+                (0..horizonte)
+                    .map(|_| {
+                        k += self.deriva + self.volatilidade * gerador.normal();
+                        k
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn tabua_geracional(
+        &self,
+        kt_projetado: &Vec<f64>,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> TabuaGeracional {
+        validar_ano_avaliacao(&self.anos, kt_projetado.len(), ano_avaliacao);
+        let qx_por_ano = self
+            .kt
+            .iter()
+            .chain(kt_projetado.iter())
+            .map(|k| {
+                self.taxas_centrais(*k)
+                    .iter()
+                    .map(|m| 1.0 - (-m).exp())
+                    .collect()
+            })
+            .collect();
+        return montar_tabua_geracional(
+            qx_por_ano,
+            self.anos[0],
+            self.idades[0],
+            ano_avaliacao,
+            periodicidade,
+        );
+    }
+
+    pub fn tabua_geracional_central(
+        &self,
+        horizonte: usize,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> TabuaGeracional {
+        return self.tabua_geracional(&self.projetar_kt(horizonte), ano_avaliacao, periodicidade);
+    }
+
+    pub fn cenarios(
+        &self,
+        horizonte: usize,
+        quantidade_cenarios: usize,
+        semente: u64,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> Vec<TabuaGeracional> {
+        return self
+            .simular_kt(horizonte, quantidade_cenarios, semente)
+            .iter()
+            .map(|kt| self.tabua_geracional(kt, ano_avaliacao, periodicidade.clone()))
+            .collect();
+    }
+}
+
+fn estimar_passeio_aleatorio(kt: &Vec<f64>) -> (f64, f64) {
+    let diferencas: Vec<f64> = kt.windows(2).map(|par| par[1] - par[0]).collect();
+    let deriva = diferencas.iter().sum::<f64>() / diferencas.len() as f64;
+    let variancia = diferencas.iter().map(|d| (d - deriva).powi(2)).sum::<f64>()
+        / (diferencas.len() - 1) as f64;
+    return (deriva, variancia.sqrt());
+}
+
+// Cairns-Blake-Dowd: logit q(x, t) = k1(t) + k2(t) * (x - média das idades). Cada ano é ajustado
+// por regressão linear e (k1, k2) é projetado como passeio aleatório bivariado com deriva.
+#[derive(Debug, Clone)]
+pub struct Cbd {
+    anos: Vec<i32>,
+    idades: Vec<u16>,
+    idade_media: f64,
+    kt1: Vec<f64>,
+    kt2: Vec<f64>,
+    deriva: [f64; 2],
+    covariancia: [[f64; 2]; 2],
+}
+
+impl Cbd {
+    pub fn ajustar(matriz: &MatrizMortalidade) -> Self {
+        validar_matriz(matriz);
+        let idades: Vec<f64> = matriz.idades().iter().map(|x| *x as f64).collect();
+        let idade_media = idades.iter().sum::<f64>() / idades.len() as f64;
+        let x: Vec<f64> = idades.iter().map(|x| x - idade_media).collect();
+        let pesos = vec![1.0; x.len()];
+
+        let (kt1, kt2): (Vec<f64>, Vec<f64>) = matriz
+            .qx()
+            .iter()
+            .map(|linha| {
+                let y = linha.iter().map(|q| (q / (1.0 - q)).ln()).collect();
+                regressao_linear(&x, &y, &pesos)
+            })
+            .unzip();
+
+        let diferencas: Vec<[f64; 2]> = (1..kt1.len())
+            .map(|t| [kt1[t] - kt1[t - 1], kt2[t] - kt2[t - 1]])
+            .collect();
+        let n = diferencas.len() as f64;
+        let deriva = [
+            diferencas.iter().map(|d| d[0]).sum::<f64>() / n,
+            diferencas.iter().map(|d| d[1]).sum::<f64>() / n,
+        ];
+        let mut covariancia = [[0.0; 2]; 2];
+        for d in diferencas.iter() {
+            for i in 0..2 {
+                for j in 0..2 {
+                    covariancia[i][j] += (d[i] - deriva[i]) * (d[j] - deriva[j]) / (n - 1.0);
+                }
+            }
+        }
+
+        return Cbd {
+            anos: matriz.anos().clone(),
+            idades: matriz.idades().clone(),
+            idade_media,
+            kt1,
+            kt2,
+            deriva,
+            covariancia,
+        };
+    }
+
+    pub fn anos(&self) -> &Vec<i32> {
+        return &self.anos;
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn idade_media(&self) -> f64 {
+        return self.idade_media;
+    }
+
+    pub fn kt1(&self) -> &Vec<f64> {
+        return &self.kt1;
+    }
+
+    pub fn kt2(&self) -> &Vec<f64> {
+        return &self.kt2;
+    }
+
+    pub fn deriva(&self) -> [f64; 2] {
+        return self.deriva;
+    }
+
+    pub fn covariancia(&self) -> [[f64; 2]; 2] {
+        return self.covariancia;
+    }
+
+    pub fn qx(&self, kt: [f64; 2]) -> Vec<f64> {
+        return self
+            .idades
+            .iter()
+            .map(|x| {
+                let logit = kt[0] + kt[1] * (*x as f64 - self.idade_media);
+                1.0 / (1.0 + (-logit).exp())
+            })
+            .collect();
+    }
+
+    fn ultimo_kt(&self) -> [f64; 2] {
+        let ultimo = self.kt1.len() - 1;
+        return [self.kt1[ultimo], self.kt2[ultimo]];
+    }
+
+    pub fn projetar_kt(&self, horizonte: usize) -> Vec<[f64; 2]> {
+        let ultimo = self.ultimo_kt();
+        return (1..=horizonte)
+            .map(|h| {
+                [
+                    ultimo[0] + self.deriva[0] * h as f64,
+                    ultimo[1] + self.deriva[1] * h as f64,
+                ]
+            })
+            .collect();
+    }
+
+    // Os choques correlacionados vêm da decomposição de Cholesky da covariância.
+    pub fn simular_kt(
+        &self,
+        horizonte: usize,
+        quantidade_cenarios: usize,
+        semente: u64,
+    ) -> Vec<Vec<[f64; 2]>> {
+        let c = self.covariancia;
+        let l11 = c[0][0].max(0.0).sqrt();
+        let l21 = if l11 > 0.0 { c[1][0] / l11 } else { 0.0 };
+        let l22 = (c[1][1] - l21 * l21).max(0.0).sqrt();
+
+        let mut gerador = GeradorAleatorio::new(semente);
+        return (0..quantidade_cenarios)
+            .map(|_| {
+                let mut k = self.ultimo_kt();
+                (0..horizonte)
+                    .map(|_| {
+                        let e1 = gerador.normal();
+                        let e2 = gerador.normal();
+                        k[0] += self.deriva[0] + l11 * e1;
+                        k[1] += self.deriva[1] + l21 * e1 + l22 * e2;
+                        k
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    fn tabua_geracional(
+        &self,
+        kt_projetado: &Vec<[f64; 2]>,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> TabuaGeracional {
+        validar_ano_avaliacao(&self.anos, kt_projetado.len(), ano_avaliacao);
+        let qx_por_ano = self
+            .kt1
+            .iter()
+            .zip(self.kt2.iter())
+            .map(|(k1, k2)| [*k1, *k2])
+            .chain(kt_projetado.iter().copied())
+            .map(|kt| self.qx(kt))
+            .collect();
+        return montar_tabua_geracional(
+            qx_por_ano,
+            self.anos[0],
+            self.idades[0],
+            ano_avaliacao,
+            periodicidade,
+        );
+    }
+
+    pub fn tabua_geracional_central(
+        &self,
+        horizonte: usize,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> TabuaGeracional {
+        return self.tabua_geracional(&self.projetar_kt(horizonte), ano_avaliacao, periodicidade);
+    }
+
+    pub fn cenarios(
+        &self,
+        horizonte: usize,
+        quantidade_cenarios: usize,
+        semente: u64,
+        ano_avaliacao: i32,
+        periodicidade: Periodicidade,
+    ) -> Vec<TabuaGeracional> {
+        return self
+            .simular_kt(horizonte, quantidade_cenarios, semente)
+            .iter()
+            .map(|kt| self.tabua_geracional(kt, ano_avaliacao, periodicidade.clone()))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    const KT: [f64; 5] = [4.0, 1.5, 0.5, -2.0, -4.0];
+
+    fn criar_matriz_lee_carter() -> MatrizMortalidade {
+        let ax: Vec<f64> = (0..4).map(|i| -5.0 + 0.3 * i as f64).collect();
+        let bx = [0.4, 0.3, 0.2, 0.1];
+        let taxas = KT
+            .iter()
+            .map(|k| (0..4).map(|i| (ax[i] + bx[i] * k).exp()).collect())
+            .collect();
+        MatrizMortalidade::new((2000..2005).collect(), (60..64).collect(), taxas)
+    }
+
+    fn criar_matriz_cbd() -> MatrizMortalidade {
+        let kt1 = [-3.0, -3.1, -3.15, -3.3, -3.35];
+        let kt2 = [0.10, 0.11, 0.11, 0.12, 0.125];
+        let taxas = (0..5)
+            .map(|t| {
+                (60..65)
+                    .map(|x| {
+                        let q = 1.0 / (1.0 + (-(kt1[t] + kt2[t] * (x as f64 - 62.0))).exp());
+                        -(1.0 - q).ln()
+                    })
+                    .collect()
+            })
+            .collect();
+        MatrizMortalidade::new((2000..2005).collect(), (60..65).collect(), taxas)
+    }
+
+    #[test]
+    fn lee_carter_recupera_parametros_de_dados_exatos() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        for (obtido, esperado) in modelo.bx().iter().zip([0.4, 0.3, 0.2, 0.1].iter()) {
+            approx::assert_abs_diff_eq!(obtido, esperado, epsilon = 1e-10);
+        }
+        for (obtido, esperado) in modelo.kt().iter().zip(KT.iter()) {
+            approx::assert_abs_diff_eq!(obtido, esperado, epsilon = 1e-9);
+        }
+        approx::assert_abs_diff_eq!(modelo.ax()[0], -5.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn lee_carter_estima_deriva_e_volatilidade_do_passeio_aleatorio() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        approx::assert_abs_diff_eq!(modelo.deriva(), -2.0, epsilon = 1e-9);
+        let diferencas = [-2.5, -1.0, -2.5, -2.0];
+        let variancia = diferencas
+            .iter()
+            .map(|d| (d + 2.0_f64).powi(2))
+            .sum::<f64>()
+            / 3.0;
+        approx::assert_abs_diff_eq!(modelo.volatilidade(), variancia.sqrt(), epsilon = 1e-9);
+        approx::assert_abs_diff_eq!(modelo.projetar_kt(2)[1], -8.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn lee_carter_cenarios_sao_reprodutiveis_pela_semente() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        let a = modelo.simular_kt(10, 3, 123);
+        let b = modelo.simular_kt(10, 3, 123);
+        let c = modelo.simular_kt(10, 3, 124);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a[0].len(), 10);
+    }
+
+    #[test]
+    fn lee_carter_media_dos_cenarios_se_aproxima_da_projecao_central() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        let cenarios = modelo.simular_kt(5, 4000, 1);
+        let media = cenarios.iter().map(|k| k[4]).sum::<f64>() / 4000.0;
+
+        approx::assert_abs_diff_eq!(media, modelo.projetar_kt(5)[4], epsilon = 0.1);
+    }
+
+    #[test]
+    fn lee_carter_tabua_geracional_segue_a_coorte() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        let tabua = modelo.tabua_geracional_central(10, 2004, Periodicidade::Anual);
+
+        let q_esperado = |k: f64, i: usize| 1.0 - (-modelo.taxas_centrais(k)[i]).exp();
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![60], 0),
+            q_esperado(modelo.kt()[4], 0),
+            epsilon = 1e-12
+        );
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![60], 2),
+            q_esperado(modelo.projetar_kt(2)[1], 2),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn lee_carter_gera_uma_tabua_por_cenario() {
+        let modelo = LeeCarter::ajustar(&criar_matriz_lee_carter());
+
+        let tabuas = modelo.cenarios(20, 5, 99, 2004, Periodicidade::Anual);
+        let kt = modelo.simular_kt(20, 5, 99);
+
+        assert_eq!(tabuas.len(), 5);
+        let q = 1.0 - (-modelo.taxas_centrais(kt[3][0])[1]).exp();
+        approx::assert_relative_eq!(tabuas[3].qx(&vec![60], 1), q, epsilon = 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "pelo menos três anos")]
+    fn lee_carter_precisa_de_tres_anos() {
+        LeeCarter::ajustar(&MatrizMortalidade::new(
+            vec![2000, 2001],
+            vec![60],
+            vec![vec![0.01], vec![0.009]],
+        ));
+    }
+
+    #[test]
+    fn cbd_recupera_parametros_de_dados_exatos() {
+        let modelo = Cbd::ajustar(&criar_matriz_cbd());
+
+        approx::assert_relative_eq!(modelo.idade_media(), 62.0);
+        approx::assert_abs_diff_eq!(modelo.kt1()[3], -3.3, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(modelo.kt2()[4], 0.125, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(modelo.deriva()[0], -0.0875, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn cbd_cenarios_sao_reprodutiveis_e_correlacionados_pela_covariancia() {
+        let modelo = Cbd::ajustar(&criar_matriz_cbd());
+
+        assert_eq!(modelo.simular_kt(5, 2, 7), modelo.simular_kt(5, 2, 7));
+        let c = modelo.covariancia();
+        approx::assert_relative_eq!(c[0][1], c[1][0]);
+
+        let cenarios = modelo.simular_kt(1, 20000, 3);
+        let passos: Vec<[f64; 2]> = cenarios
+            .iter()
+            .map(|k| {
+                [
+                    k[0][0] - modelo.kt1()[4] - modelo.deriva()[0],
+                    k[0][1] - modelo.kt2()[4] - modelo.deriva()[1],
+                ]
+            })
+            .collect();
+        let covariancia = passos.iter().map(|p| p[0] * p[1]).sum::<f64>() / 20000.0;
+        approx::assert_abs_diff_eq!(covariancia, c[0][1], epsilon = 0.1 * c[0][1].abs() + 1e-7);
+    }
+
+    #[test]
+    fn cbd_tabua_geracional_usa_kt_projetado() {
+        let modelo = Cbd::ajustar(&criar_matriz_cbd());
+
+        let tabua = modelo.tabua_geracional_central(5, 2004, Periodicidade::Anual);
+
+        approx::assert_relative_eq!(
+            tabua.qx(&vec![61], 1),
+            modelo.qx(modelo.projetar_kt(1)[0])[2],
+            epsilon = 1e-12
+        );
+    }
+}
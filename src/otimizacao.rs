@@ -0,0 +1,158 @@
+pub(crate) struct ResultadoOtimizacao {
+    pub x: Vec<f64>,
+    pub valor: f64,
+    pub iteracoes: usize,
+    pub convergiu: bool,
+}
+
+fn avaliar<F: Fn(&Vec<f64>) -> f64>(funcao: &F, x: &Vec<f64>) -> f64 {
+    let valor = funcao(x);
+    if valor.is_nan() {
+        return f64::INFINITY;
+    }
+    return valor;
+}
+
+fn combinar(a: &Vec<f64>, b: &Vec<f64>, coeficiente: f64) -> Vec<f64> {
+    return a
+        .iter()
+        .zip(b.iter())
+        .map(|(a, b)| a + coeficiente * (b - a))
+        .collect();
+}
+
+// Nelder-Mead com os coeficientes usuais (reflexão 1, expansão 2, contração e encolhimento 0.5).
+// O simplex é reiniciado ao redor do melhor ponto enquanto houver melhora, o que evita parte
+// das convergências prematuras em problemas com muitos parâmetros.
+pub(crate) fn nelder_mead<F: Fn(&Vec<f64>) -> f64>(
+    funcao: F,
+    x0: Vec<f64>,
+    passo: f64,
+    tolerancia: f64,
+    maximo_iteracoes: usize,
+) -> ResultadoOtimizacao {
+    let n = x0.len();
+    if n == 0 {
+        panic!("A otimização precisa de pelo menos um parâmetro.");
+    }
+
+    let mut melhor = x0;
+    let mut melhor_valor = avaliar(&funcao, &melhor);
+    let mut iteracoes = 0;
+    let mut convergiu = false;
+
+    for _ in 0..5 {
+        let mut simplex: Vec<Vec<f64>> = vec![melhor.clone()];
+        for i in 0..n {
+            let mut ponto = melhor.clone();
+            ponto[i] += passo;
+            simplex.push(ponto);
+        }
+        let mut valores: Vec<f64> = simplex.iter().map(|p| avaliar(&funcao, p)).collect();
+
+        convergiu = false;
+        while iteracoes < maximo_iteracoes {
+            iteracoes += 1;
+
+            let mut ordem: Vec<usize> = (0..=n).collect();
+            ordem.sort_by(|a, b| valores[*a].total_cmp(&valores[*b]));
+            simplex = ordem.iter().map(|i| simplex[*i].clone()).collect();
+            valores = ordem.iter().map(|i| valores[*i]).collect();
+
+            let amplitude = (valores[n] - valores[0]).abs();
+            if amplitude <= tolerancia * (valores[0].abs() + tolerancia) {
+                convergiu = true;
+                break;
+            }
+
+            let centroide: Vec<f64> = (0..n)
+                .map(|j| simplex[..n].iter().map(|p| p[j]).sum::<f64>() / n as f64)
+                .collect();
+
+            let refletido = combinar(&centroide, &simplex[n], -1.0);
+            let valor_refletido = avaliar(&funcao, &refletido);
+
+            if valor_refletido < valores[0] {
+                let expandido = combinar(&centroide, &simplex[n], -2.0);
+                let valor_expandido = avaliar(&funcao, &expandido);
+                if valor_expandido < valor_refletido {
+                    simplex[n] = expandido;
+                    valores[n] = valor_expandido;
+                } else {
+                    simplex[n] = refletido;
+                    valores[n] = valor_refletido;
+                }
+            } else if valor_refletido < valores[n - 1] {
+                simplex[n] = refletido;
+                valores[n] = valor_refletido;
+            } else {
+                let contraido = if valor_refletido < valores[n] {
+                    combinar(&centroide, &refletido, 0.5)
+                } else {
+                    combinar(&centroide, &simplex[n], 0.5)
+                };
+                let valor_contraido = avaliar(&funcao, &contraido);
+                if valor_contraido < valores[n].min(valor_refletido) {
+                    simplex[n] = contraido;
+                    valores[n] = valor_contraido;
+                } else {
+                    for i in 1..=n {
+                        simplex[i] = combinar(&simplex[0], &simplex[i], 0.5);
+                        valores[i] = avaliar(&funcao, &simplex[i]);
+                    }
+                }
+            }
+        }
+
+        let indice_melhor = (0..=n)
+            .min_by(|a, b| valores[*a].total_cmp(&valores[*b]))
+            .expect("O simplex deveria possuir pelo menos um ponto.");
+        let melhorou = valores[indice_melhor] < melhor_valor - tolerancia * melhor_valor.abs();
+        if valores[indice_melhor] <= melhor_valor {
+            melhor = simplex[indice_melhor].clone();
+            melhor_valor = valores[indice_melhor];
+        }
+        if !melhorou || iteracoes >= maximo_iteracoes {
+            break;
+        }
+    }
+
+    return ResultadoOtimizacao {
+        x: melhor,
+        valor: melhor_valor,
+        iteracoes,
+        convergiu,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn nelder_mead_encontra_minimo_de_rosenbrock() {
+        let rosenbrock = |x: &Vec<f64>| (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2);
+
+        let resultado = nelder_mead(rosenbrock, vec![-1.2, 1.0], 0.5, 1e-14, 10000);
+
+        assert!(resultado.convergiu);
+        approx::assert_abs_diff_eq!(resultado.x[0], 1.0, epsilon = 1e-4);
+        approx::assert_abs_diff_eq!(resultado.x[1], 1.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn nelder_mead_trata_nan_como_infinito() {
+        let funcao = |x: &Vec<f64>| {
+            if x[0] < 0.0 {
+                f64::NAN
+            } else {
+                (x[0] - 2.0).powi(2)
+            }
+        };
+
+        let resultado = nelder_mead(funcao, vec![0.5], 1.0, 1e-12, 1000);
+
+        approx::assert_abs_diff_eq!(resultado.x[0], 2.0, epsilon = 1e-4);
+    }
+}
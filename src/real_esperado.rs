@@ -0,0 +1,579 @@
+use crate::estatistica::{cdf_binomial, cdf_qui_quadrado, quantil_normal, quantil_qui_quadrado};
+use crate::experiencia::Experiencia;
+use crate::interface::TabuaInterface;
+use crate::TabuaMDT;
+
+// No modelo de Poisson as exposições da experiência são centrais e os óbitos esperados são
+// E * m, com m = -ln(1 - q). No binomial, as exposições são iniciais e os esperados são E * q.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeloRealEsperado {
+    Poisson,
+    Binomial,
+}
+
+#[derive(Debug, Clone)]
+pub struct FaixaRealEsperado {
+    pub idade_inicial: u16,
+    pub idade_final: u16,
+    pub expostos: f64,
+    pub obitos_reais: f64,
+    pub obitos_esperados: f64,
+    pub variancia: f64,
+    pub razao: f64,
+    pub limite_inferior: f64,
+    pub limite_superior: f64,
+    pub desvio_padronizado: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultadoRealEsperado {
+    pub faixas: Vec<FaixaRealEsperado>,
+    pub total: FaixaRealEsperado,
+    pub qui_quadrado: f64,
+    pub p_valor_qui_quadrado: f64,
+    pub deviance: f64,
+    pub p_valor_deviance: f64,
+    pub quantidade_desvios_positivos: usize,
+    pub p_valor_sinais: f64,
+}
+
+fn intervalo_confianca(
+    obitos_reais: f64,
+    obitos_esperados: f64,
+    variancia: f64,
+    modelo: &ModeloRealEsperado,
+    nivel_confianca: f64,
+) -> (f64, f64) {
+    if obitos_esperados <= 0.0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let alfa = 1.0 - nivel_confianca;
+    return match modelo {
+        // Intervalo exato de Garwood para a média de Poisson.
+        ModeloRealEsperado::Poisson => {
+            let inferior = if obitos_reais > 0.0 {
+                quantil_qui_quadrado(alfa / 2.0, 2.0 * obitos_reais) / 2.0
+            } else {
+                0.0
+            };
+            let superior = quantil_qui_quadrado(1.0 - alfa / 2.0, 2.0 * obitos_reais + 2.0) / 2.0;
+            (inferior / obitos_esperados, superior / obitos_esperados)
+        }
+        ModeloRealEsperado::Binomial => {
+            let z = quantil_normal(1.0 - alfa / 2.0);
+            let amplitude = z * variancia.sqrt();
+            (
+                ((obitos_reais - amplitude) / obitos_esperados).max(0.0),
+                (obitos_reais + amplitude) / obitos_esperados,
+            )
+        }
+    };
+}
+
+fn montar_faixa(
+    idade_inicial: u16,
+    idade_final: u16,
+    dados: &[(u16, f64, f64, f64, f64)],
+    modelo: &ModeloRealEsperado,
+    nivel_confianca: f64,
+) -> FaixaRealEsperado {
+    let (expostos, obitos_reais, obitos_esperados, variancia) = dados.iter().fold(
+        (0.0, 0.0, 0.0, 0.0),
+        |(e, a, esp, v), (_, expostos, reais, esperados, variancia)| {
+            (e + expostos, a + reais, esp + esperados, v + variancia)
+        },
+    );
+    let (limite_inferior, limite_superior) = intervalo_confianca(
+        obitos_reais,
+        obitos_esperados,
+        variancia,
+        modelo,
+        nivel_confianca,
+    );
+    let razao = if obitos_esperados > 0.0 {
+        obitos_reais / obitos_esperados
+    } else {
+        f64::NAN
+    };
+    let desvio_padronizado = if variancia > 0.0 {
+        (obitos_reais - obitos_esperados) / variancia.sqrt()
+    } else {
+        0.0
+    };
+    return FaixaRealEsperado {
+        idade_inicial,
+        idade_final,
+        expostos,
+        obitos_reais,
+        obitos_esperados,
+        variancia,
+        razao,
+        limite_inferior,
+        limite_superior,
+        desvio_padronizado,
+    };
+}
+
+// Núcleo da análise, a partir das taxas esperadas qx já calculadas para cada idade da experiência.
+// Os testes de qui-quadrado, deviance e sinais são feitos sobre as faixas etárias.
+fn analisar(
+    experiencia: &Experiencia,
+    qx_esperados: Vec<f64>,
+    taxas_centrais_esperadas: Vec<f64>,
+    modelo: ModeloRealEsperado,
+    amplitude_faixa: u16,
+    nivel_confianca: f64,
+) -> ResultadoRealEsperado {
+    if amplitude_faixa == 0 {
+        panic!("A amplitude das faixas etárias deve ser maior que zero.");
+    }
+    if !(nivel_confianca > 0.0 && nivel_confianca < 1.0) {
+        panic!("O nível de confiança deve estar entre 0 e 1, obtido = {nivel_confianca}.");
+    }
+
+    let mut dados: Vec<(u16, f64, f64, f64, f64)> = experiencia
+        .idades()
+        .iter()
+        .zip(experiencia.expostos().iter())
+        .zip(experiencia.obitos().iter())
+        .zip(qx_esperados.iter().zip(taxas_centrais_esperadas.iter()))
+        .map(|(((idade, expostos), reais), (qx, mx))| match modelo {
+            // Sem exposição não há óbitos esperados, mesmo onde a taxa esperada é infinita.
+            _ if *expostos == 0.0 => (*idade, 0.0, *reais, 0.0, 0.0),
+            ModeloRealEsperado::Poisson => {
+                if !mx.is_finite() {
+                    panic!(
+                        "A idade {idade} possui qx esperado igual a 1 e exposição positiva, o que \
+                         torna infinitos os óbitos esperados no modelo de Poisson."
+                    );
+                }
+                (*idade, *expostos, *reais, expostos * mx, expostos * mx)
+            }
+            ModeloRealEsperado::Binomial => (
+                *idade,
+                *expostos,
+                *reais,
+                expostos * qx,
+                expostos * qx * (1.0 - qx),
+            ),
+        })
+        .collect();
+    // As faixas são formadas por idades consecutivas, e a experiência não exige idades ordenadas.
+    dados.sort_by_key(|d| d.0);
+
+    let mut faixas = Vec::new();
+    let mut inicio = 0;
+    while inicio < dados.len() {
+        let faixa = dados[inicio].0 / amplitude_faixa;
+        let fim = (inicio..dados.len())
+            .find(|i| dados[*i].0 / amplitude_faixa != faixa)
+            .unwrap_or(dados.len());
+        faixas.push(montar_faixa(
+            faixa * amplitude_faixa,
+            faixa * amplitude_faixa + amplitude_faixa - 1,
+            &dados[inicio..fim],
+            &modelo,
+            nivel_confianca,
+        ));
+        inicio = fim;
+    }
+
+    let idade_final = dados.iter().map(|d| d.0).max().unwrap_or(0);
+    let idade_inicial = dados.iter().map(|d| d.0).min().unwrap_or(0);
+    let total = montar_faixa(idade_inicial, idade_final, &dados, &modelo, nivel_confianca);
+
+    let faixas_com_esperado: Vec<&FaixaRealEsperado> = faixas
+        .iter()
+        .filter(|faixa| faixa.obitos_esperados > 0.0)
+        .collect();
+    let graus_liberdade = faixas_com_esperado.len() as f64;
+
+    let qui_quadrado: f64 = faixas_com_esperado
+        .iter()
+        .map(|faixa| faixa.desvio_padronizado.powi(2))
+        .sum();
+    let deviance: f64 = faixas_com_esperado
+        .iter()
+        .map(|faixa| {
+            let (a, e, n) = (faixa.obitos_reais, faixa.obitos_esperados, faixa.expostos);
+            match modelo {
+                ModeloRealEsperado::Poisson => 2.0 * (termo_deviance(a, e) - (a - e)),
+                ModeloRealEsperado::Binomial => {
+                    2.0 * (termo_deviance(a, e) + termo_deviance(n - a, n - e))
+                }
+            }
+        })
+        .sum();
+
+    let quantidade_desvios_positivos = faixas_com_esperado
+        .iter()
+        .filter(|faixa| faixa.obitos_reais > faixa.obitos_esperados)
+        .count();
+    let n = faixas_com_esperado.len() as u64;
+    let k = quantidade_desvios_positivos as u64;
+    let cauda = cdf_binomial(k.min(n - k), n, 0.5);
+    let p_valor_sinais = (2.0 * cauda).min(1.0);
+
+    return ResultadoRealEsperado {
+        faixas,
+        total,
+        qui_quadrado,
+        p_valor_qui_quadrado: 1.0 - cdf_qui_quadrado(qui_quadrado, graus_liberdade),
+        deviance,
+        p_valor_deviance: 1.0 - cdf_qui_quadrado(deviance, graus_liberdade),
+        quantidade_desvios_positivos,
+        p_valor_sinais,
+    };
+}
+
+// o ln(o / e), com o limite 0 quando o = 0.
+fn termo_deviance(observado: f64, esperado: f64) -> f64 {
+    if observado > 0.0 {
+        return observado * (observado / esperado).ln();
+    }
+    return 0.0;
+}
+
+fn taxa_central(qx: f64) -> f64 {
+    if qx >= 1.0 {
+        return f64::INFINITY;
+    }
+    return -(1.0 - qx).ln();
+}
+
+fn validar_periodicidade<T: TabuaInterface>(experiencia: &Experiencia, tabua: &T) {
+    if experiencia.periodicidade() != tabua.periodicidade() {
+        panic!("A experiência e a tábua devem possuir a mesma periodicidade.");
+    }
+}
+
+// A idade da experiência é usada para todas as vidas e decrementos da tábua.
+pub fn analisar_real_esperado<T: TabuaInterface>(
+    experiencia: &Experiencia,
+    tabua: &T,
+    modelo: ModeloRealEsperado,
+    amplitude_faixa: u16,
+    nivel_confianca: f64,
+) -> ResultadoRealEsperado {
+    validar_periodicidade(experiencia, tabua);
+    let tamanho_x = tabua.numero_decrementos() * tabua.numero_vidas();
+    let qx: Vec<f64> = experiencia
+        .idades()
+        .iter()
+        .map(|idade| tabua.qx(&vec![*idade; tamanho_x], 0))
+        .collect();
+    let mx = qx.iter().map(|q| taxa_central(*q)).collect();
+    return analisar(
+        experiencia,
+        qx,
+        mx,
+        modelo,
+        amplitude_faixa,
+        nivel_confianca,
+    );
+}
+
+// Compara os eventos observados de uma causa com a probabilidade dependente q^(j) da tábua MDT.
+// No modelo de Poisson, a taxa central da causa é m^(j) = q^(j) / q^(τ) * -ln(1 - q^(τ)),
+// supondo força constante em cada idade.
+pub fn analisar_real_esperado_decremento(
+    experiencia: &Experiencia,
+    tabua: &TabuaMDT,
+    decremento: usize,
+    modelo: ModeloRealEsperado,
+    amplitude_faixa: u16,
+    nivel_confianca: f64,
+) -> ResultadoRealEsperado {
+    validar_periodicidade(experiencia, tabua);
+    if decremento >= tabua.numero_decrementos() {
+        panic!(
+            "O decremento deve ser menor que o número de decrementos. decremento = {decremento}, número de decrementos = {}",
+            tabua.numero_decrementos()
+        );
+    }
+    let (qx, mx) = experiencia
+        .idades()
+        .iter()
+        .map(|idade| {
+            let x = vec![*idade; tabua.numero_decrementos()];
+            let qx_j = tabua.qx_j(&x, 0, decremento);
+            let qx_total = tabua.qx(&x, 0);
+            let mx_j = if qx_total > 0.0 {
+                qx_j / qx_total * taxa_central(qx_total)
+            } else {
+                0.0
+            };
+            (qx_j, mx_j)
+        })
+        .unzip();
+    return analisar(
+        experiencia,
+        qx,
+        mx,
+        modelo,
+        amplitude_faixa,
+        nivel_confianca,
+    );
+}
+
+// Igual a analisar_real_esperado_decremento, identificando a causa pelo nome.
+pub fn analisar_real_esperado_decremento_nomeado(
+    experiencia: &Experiencia,
+    tabua: &TabuaMDT,
+    nome: &str,
+    modelo: ModeloRealEsperado,
+    amplitude_faixa: u16,
+    nivel_confianca: f64,
+) -> ResultadoRealEsperado {
+    return analisar_real_esperado_decremento(
+        experiencia,
+        tabua,
+        tabua.indice_decremento(nome),
+        modelo,
+        amplitude_faixa,
+        nivel_confianca,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Periodicidade;
+    use crate::Tabua;
+    use approx;
+
+    fn criar_tabua() -> Tabua {
+        Tabua::new(
+            (0..10).map(|x| 0.01 + 0.01 * x as f64).collect(),
+            Periodicidade::Anual,
+        )
+    }
+
+    fn criar_experiencia() -> Experiencia {
+        Experiencia::new(
+            vec![2, 3, 4, 5, 6, 7],
+            vec![30.0, 38.0, 52.0, 55.0, 70.0, 85.0],
+            vec![1000.0; 6],
+            Periodicidade::Anual,
+        )
+    }
+
+    #[test]
+    fn razao_total_eh_soma_dos_reais_sobre_soma_dos_esperados() {
+        let tabua = criar_tabua();
+        let experiencia = criar_experiencia();
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Binomial, 1, 0.95);
+
+        let esperados: f64 = (2..8).map(|x| 1000.0 * (0.01 + 0.01 * x as f64)).sum();
+        approx::assert_relative_eq!(resultado.total.obitos_esperados, esperados, epsilon = 1e-9);
+        approx::assert_relative_eq!(resultado.total.razao, 330.0 / esperados, epsilon = 1e-9);
+        assert_eq!(resultado.faixas.len(), 6);
+    }
+
+    #[test]
+    fn faixas_agrupam_idades_pela_amplitude() {
+        let tabua = criar_tabua();
+        let experiencia = criar_experiencia();
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Poisson, 5, 0.95);
+
+        assert_eq!(resultado.faixas.len(), 2);
+        assert_eq!(resultado.faixas[0].idade_inicial, 0);
+        assert_eq!(resultado.faixas[0].idade_final, 4);
+        approx::assert_relative_eq!(resultado.faixas[0].obitos_reais, 120.0);
+        approx::assert_relative_eq!(resultado.faixas[1].obitos_reais, 210.0);
+    }
+
+    #[test]
+    fn faixas_agrupam_idades_fora_de_ordem() {
+        let tabua = criar_tabua();
+        let experiencia = Experiencia::new(
+            vec![0, 5, 1],
+            vec![10.0, 50.0, 20.0],
+            vec![1000.0; 3],
+            Periodicidade::Anual,
+        );
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Poisson, 5, 0.95);
+
+        assert_eq!(resultado.faixas.len(), 2);
+        assert_eq!(resultado.faixas[0].idade_inicial, 0);
+        approx::assert_relative_eq!(resultado.faixas[0].obitos_reais, 30.0);
+        assert_eq!(resultado.faixas[1].idade_inicial, 5);
+        approx::assert_relative_eq!(resultado.faixas[1].obitos_reais, 50.0);
+    }
+
+    #[test]
+    fn deviance_binomial_inclui_os_sobreviventes() {
+        let tabua = criar_tabua();
+        let experiencia = criar_experiencia();
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Binomial, 1, 0.95);
+
+        let n = 1000.0;
+        let deviance: f64 = [30.0, 38.0, 52.0, 55.0, 70.0, 85.0]
+            .iter()
+            .zip(2..8)
+            .map(|(a, x)| {
+                let e = n * (0.01 + 0.01 * x as f64);
+                2.0 * (a * (a / e).ln() + (n - a) * ((n - a) / (n - e)).ln())
+            })
+            .sum();
+        approx::assert_relative_eq!(resultado.deviance, deviance, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn idade_sem_exposicao_nao_gera_esperados() {
+        let tabua = Tabua::new(vec![0.01, 0.02, 1.0], Periodicidade::Anual);
+        let experiencia = Experiencia::new(
+            vec![0, 1, 2],
+            vec![12.0, 18.0, 0.0],
+            vec![1000.0, 1000.0, 0.0],
+            Periodicidade::Anual,
+        );
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Poisson, 5, 0.95);
+
+        let esperados = -1000.0 * (0.99_f64.ln() + 0.98_f64.ln());
+        approx::assert_relative_eq!(resultado.total.obitos_esperados, esperados, epsilon = 1e-9);
+        assert!(resultado.total.limite_superior.is_finite());
+        assert!(resultado.qui_quadrado.is_finite());
+    }
+
+    #[test]
+    #[should_panic(expected = "A idade 2 possui qx esperado igual a 1")]
+    fn poisson_rejeita_idade_com_qx_1_e_exposicao() {
+        let tabua = Tabua::new(vec![0.01, 0.02, 1.0], Periodicidade::Anual);
+        let experiencia = Experiencia::new(
+            vec![1, 2],
+            vec![18.0, 5.0],
+            vec![1000.0, 5.0],
+            Periodicidade::Anual,
+        );
+
+        analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Poisson, 5, 0.95);
+    }
+
+    #[test]
+    fn intervalo_de_poisson_eh_exato() {
+        let (inferior, superior) =
+            intervalo_confianca(10.0, 10.0, 10.0, &ModeloRealEsperado::Poisson, 0.95);
+
+        approx::assert_relative_eq!(inferior, 0.4795389, epsilon = 1e-6);
+        approx::assert_relative_eq!(superior, 1.8390356, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn experiencia_igual_a_tabua_nao_rejeita_a_tabua() {
+        let tabua = criar_tabua();
+        let idades: Vec<u16> = (0..10).collect();
+        let experiencia = Experiencia::a_partir_da_tabua(&tabua, idades, vec![1000.0; 10]);
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Poisson, 1, 0.95);
+
+        approx::assert_relative_eq!(resultado.total.razao, 1.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(resultado.qui_quadrado, 0.0, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(resultado.p_valor_qui_quadrado, 1.0, epsilon = 1e-12);
+        assert_eq!(resultado.quantidade_desvios_positivos, 0);
+        assert!(resultado.total.limite_inferior < 1.0 && resultado.total.limite_superior > 1.0);
+    }
+
+    #[test]
+    fn teste_de_sinais_detecta_desvios_sistematicos() {
+        let tabua = criar_tabua();
+        let idades: Vec<u16> = (0..10).collect();
+        let obitos = idades
+            .iter()
+            .map(|x| 2000.0 * (0.01 + 0.01 * *x as f64))
+            .collect();
+        let experiencia = Experiencia::new(idades, obitos, vec![1000.0; 10], Periodicidade::Anual);
+
+        let resultado =
+            analisar_real_esperado(&experiencia, &tabua, ModeloRealEsperado::Binomial, 1, 0.95);
+
+        assert_eq!(resultado.quantidade_desvios_positivos, 10);
+        approx::assert_relative_eq!(resultado.p_valor_sinais, 2.0 / 1024.0, epsilon = 1e-12);
+        assert!(resultado.p_valor_qui_quadrado < 1e-6);
+    }
+
+    #[test]
+    fn analise_por_decremento_usa_probabilidade_dependente() {
+        let morte = criar_tabua();
+        let cancelamento = Tabua::new(vec![0.1; 10], Periodicidade::Anual);
+        let tabua_mdt = TabuaMDT::new(
+            vec![morte, cancelamento],
+            crate::HipoteseMdt::UddTabuasSimples,
+        );
+        let experiencia = criar_experiencia();
+
+        let resultado = analisar_real_esperado_decremento(
+            &experiencia,
+            &tabua_mdt,
+            0,
+            ModeloRealEsperado::Binomial,
+            1,
+            0.95,
+        );
+
+        let esperado: f64 = (2..8)
+            .map(|x| 1000.0 * (0.01 + 0.01 * x as f64) * (1.0 - 0.5 * 0.1))
+            .sum();
+        approx::assert_relative_eq!(resultado.total.obitos_esperados, esperado, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn analise_por_decremento_aceita_o_nome_da_causa() {
+        let tabua_mdt = TabuaMDT::new(
+            vec![
+                criar_tabua(),
+                Tabua::new(vec![0.1; 10], Periodicidade::Anual),
+            ],
+            crate::HipoteseMdt::UddTabuasSimples,
+        )
+        .com_nomes_decrementos(vec!["morte".to_string(), "cancelamento".to_string()]);
+        let experiencia = criar_experiencia();
+
+        let por_nome = analisar_real_esperado_decremento_nomeado(
+            &experiencia,
+            &tabua_mdt,
+            "cancelamento",
+            ModeloRealEsperado::Poisson,
+            1,
+            0.95,
+        );
+        let por_indice = analisar_real_esperado_decremento(
+            &experiencia,
+            &tabua_mdt,
+            1,
+            ModeloRealEsperado::Poisson,
+            1,
+            0.95,
+        );
+
+        assert_eq!(
+            por_nome.total.obitos_esperados,
+            por_indice.total.obitos_esperados
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A experiência e a tábua devem possuir a mesma periodicidade.")]
+    fn analise_da_erro_com_periodicidades_diferentes() {
+        let tabua = criar_tabua().alterar_periodicidade(Periodicidade::Mensal);
+
+        analisar_real_esperado(
+            &criar_experiencia(),
+            &tabua,
+            ModeloRealEsperado::Poisson,
+            1,
+            0.95,
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use crate::interface::{validar_idades_tabuas, TabuaInterface};
+use crate::Periodicidade;
+use infinitable::Infinitable::{self, Finite};
+
+// Combinações de status sobre quaisquer tábuas. Os componentes são supostos independentes e o
+// vetor de idades do status combinado é a concatenação dos vetores de idades dos componentes,
+// por isso numero_vidas conta as posições desse vetor e numero_decrementos é 1.
+
+fn tamanho_idades<T: TabuaInterface>(tabua: &T) -> usize {
+    return tabua.numero_decrementos() * tabua.numero_vidas();
+}
+
+fn validar_periodicidades(a: &Periodicidade, b: &Periodicidade) {
+    if a != b {
+        panic!("Todas as tabuas devem possuir a mesma periodicidade.");
+    }
+}
+
+fn alterar_prazo(prazo: u16, atual: &Periodicidade, nova: &Periodicidade) -> u16 {
+    let periodos = prazo as usize * nova.quantidade_periodos_1_ano() as usize;
+    let periodos_atuais = atual.quantidade_periodos_1_ano() as usize;
+    if !periodos.is_multiple_of(periodos_atuais) {
+        panic!("O prazo não corresponde a um período completo da nova periodicidade.");
+    }
+    return (periodos / periodos_atuais) as u16;
+}
+
+// 1 - tpx(t + 1) / tpx(t), com qx = 1 quando o status já não vale em t.
+fn qx_pela_sobrevivencia<T: TabuaInterface>(tabua: &T, x: &Vec<u16>, t: u16) -> f64 {
+    let tpx = tabua.tpx(x, t);
+    if tpx == 0.0 {
+        return 1.0;
+    }
+    return (1.0 - tabua.tpx(x, t + 1) / tpx).clamp(0.0, 1.0);
+}
+
+// Vale enquanto os dois status valem.
+pub struct StatusConjunto<A: TabuaInterface, B: TabuaInterface> {
+    a: A,
+    b: B,
+}
+
+impl<A: TabuaInterface, B: TabuaInterface> StatusConjunto<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        validar_periodicidades(a.periodicidade(), b.periodicidade());
+        return StatusConjunto { a, b };
+    }
+
+    fn separar_idades(&self, x: &Vec<u16>) -> (Vec<u16>, Vec<u16>) {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        let (xa, xb) = x.split_at(tamanho_idades(&self.a));
+        return (xa.to_vec(), xb.to_vec());
+    }
+}
+
+impl<A: TabuaInterface, B: TabuaInterface> TabuaInterface for StatusConjunto<A, B> {
+    fn periodicidade(&self) -> &Periodicidade {
+        return self.a.periodicidade();
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return tamanho_idades(&self.a) + tamanho_idades(&self.b);
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        let (xa, xb) = self.separar_idades(x);
+        return self
+            .a
+            .tempo_futuro_maximo(&xa)
+            .min(self.b.tempo_futuro_maximo(&xb));
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        let (xa, xb) = self.separar_idades(x);
+        return 1.0 - (1.0 - self.a.qx(&xa, t)) * (1.0 - self.b.qx(&xb, t));
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        let (xa, xb) = self.separar_idades(x);
+        return self.a.tpx(&xa, t) * self.b.tpx(&xb, t);
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        return StatusConjunto::new(
+            self.a.alterar_periodicidade(nova_periodicidade.clone()),
+            self.b.alterar_periodicidade(nova_periodicidade),
+        );
+    }
+}
+
+// Vale enquanto pelo menos um dos dois status vale.
+pub struct StatusUltimoSobrevivente<A: TabuaInterface, B: TabuaInterface> {
+    a: A,
+    b: B,
+}
+
+impl<A: TabuaInterface, B: TabuaInterface> StatusUltimoSobrevivente<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        validar_periodicidades(a.periodicidade(), b.periodicidade());
+        return StatusUltimoSobrevivente { a, b };
+    }
+
+    fn separar_idades(&self, x: &Vec<u16>) -> (Vec<u16>, Vec<u16>) {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        let (xa, xb) = x.split_at(tamanho_idades(&self.a));
+        return (xa.to_vec(), xb.to_vec());
+    }
+}
+
+impl<A: TabuaInterface, B: TabuaInterface> TabuaInterface for StatusUltimoSobrevivente<A, B> {
+    fn periodicidade(&self) -> &Periodicidade {
+        return self.a.periodicidade();
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return tamanho_idades(&self.a) + tamanho_idades(&self.b);
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        let (xa, xb) = self.separar_idades(x);
+        return self
+            .a
+            .tempo_futuro_maximo(&xa)
+            .max(self.b.tempo_futuro_maximo(&xb));
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        return qx_pela_sobrevivencia(self, x, t);
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        let (xa, xb) = self.separar_idades(x);
+        let tpx_a = self.a.tpx(&xa, t);
+        let tpx_b = self.b.tpx(&xb, t);
+        return tpx_a + tpx_b - tpx_a * tpx_b;
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        return StatusUltimoSobrevivente::new(
+            self.a.alterar_periodicidade(nova_periodicidade.clone()),
+            self.b.alterar_periodicidade(nova_periodicidade),
+        );
+    }
+}
+
+// Status certo de prazo n: vale nos tempos 0 a n - 1 e falha no tempo n. Não usa idades, o
+// vetor de idades é vazio.
+#[derive(Debug, Clone)]
+pub struct StatusPrazoCerto {
+    prazo: u16,
+    periodicidade: Periodicidade,
+}
+
+impl StatusPrazoCerto {
+    pub fn new(prazo: u16, periodicidade: Periodicidade) -> Self {
+        return StatusPrazoCerto {
+            prazo,
+            periodicidade,
+        };
+    }
+
+    pub fn prazo(&self) -> u16 {
+        return self.prazo;
+    }
+}
+
+impl TabuaInterface for StatusPrazoCerto {
+    fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return 0;
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return Finite(self.prazo);
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return if t >= self.prazo.saturating_sub(1) {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return if t < self.prazo { 1.0 } else { 0.0 };
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        return StatusPrazoCerto::new(
+            alterar_prazo(self.prazo, &self.periodicidade, &nova_periodicidade),
+            nova_periodicidade,
+        );
+    }
+}
+
+// Status diferido: só passa a valer no tempo diferimento, quando vale se o status original
+// valer. Antes disso tpx e qx são zero, de modo que t_qx também é zero.
+pub struct StatusDiferido<A: TabuaInterface> {
+    status: A,
+    diferimento: u16,
+}
+
+impl<A: TabuaInterface> StatusDiferido<A> {
+    pub fn new(status: A, diferimento: u16) -> Self {
+        return StatusDiferido {
+            status,
+            diferimento,
+        };
+    }
+
+    pub fn diferimento(&self) -> u16 {
+        return self.diferimento;
+    }
+}
+
+impl<A: TabuaInterface> TabuaInterface for StatusDiferido<A> {
+    fn periodicidade(&self) -> &Periodicidade {
+        return self.status.periodicidade();
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return self.status.numero_decrementos();
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return self.status.numero_vidas();
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        return self.status.tempo_futuro_maximo(x);
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        if t < self.diferimento {
+            validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+            return 0.0;
+        }
+        return self.status.qx(x, t);
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        if t < self.diferimento {
+            validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+            return 0.0;
+        }
+        return self.status.tpx(x, t);
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        return StatusDiferido::new(
+            self.status
+                .alterar_periodicidade(nova_periodicidade.clone()),
+            alterar_prazo(
+                self.diferimento,
+                self.status.periodicidade(),
+                &nova_periodicidade,
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JurosConstante, JurosInterface, Tabua, TabuaMultiplasVidas};
+    use approx;
+
+    fn anuidade<T: TabuaInterface>(status: &T, x: &Vec<u16>, juros: &JurosConstante) -> f64 {
+        return (0..200)
+            .map(|t| juros.taxa_desconto(t) * status.tpx(x, t))
+            .sum();
+    }
+
+    fn criar_tabua(fator: f64) -> Tabua {
+        let qx = (0..60)
+            .map(|x| (fator * 1.1_f64.powi(x)).min(1.0))
+            .collect();
+        Tabua::new(qx, Periodicidade::Anual)
+    }
+
+    #[test]
+    fn conjunto_de_vidas_coincide_com_tabua_de_multiplas_vidas() {
+        let (tabua1, tabua2) = (criar_tabua(0.01), criar_tabua(0.005));
+        let status = StatusConjunto::new(tabua1.clone(), tabua2.clone());
+        let referencia = TabuaMultiplasVidas::new(vec![tabua1, tabua2], crate::First);
+        let x = vec![10, 20];
+
+        approx::assert_relative_eq!(status.tpx(&x, 7), referencia.tpx(&x, 7));
+        approx::assert_relative_eq!(status.qx(&x, 7), referencia.qx(&x, 7), epsilon = 1e-15);
+        assert_eq!(
+            status.tempo_futuro_maximo(&x),
+            referencia.tempo_futuro_maximo(&x)
+        );
+    }
+
+    #[test]
+    fn ultimo_sobrevivente_e_prazo_certo_formam_anuidade_garantida() {
+        // ä de x com n anos garantidos = ä_n + n|ä_x.
+        let tabua = criar_tabua(0.02);
+        let juros = JurosConstante::new(0.05, Periodicidade::Anual);
+        let status = StatusUltimoSobrevivente::new(
+            tabua.clone(),
+            StatusPrazoCerto::new(10, Periodicidade::Anual),
+        );
+        let diferida = StatusDiferido::new(tabua, 10);
+
+        let garantida = anuidade(&status, &vec![30], &juros);
+        let certa: f64 = (0..10).map(|t| juros.taxa_desconto(t)).sum();
+
+        approx::assert_relative_eq!(
+            garantida,
+            certa + anuidade(&diferida, &vec![30], &juros),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn status_aninhados_compoem_beneficio_com_reversao_limitada() {
+        // Enquanto ambos vivos e por no máximo 20 anos; depois, ao sobrevivente por até 10 anos.
+        let (tabua1, tabua2) = (criar_tabua(0.0005), criar_tabua(0.0004));
+        let conjunta_temporaria = StatusConjunto::new(
+            StatusConjunto::new(tabua1.clone(), tabua2.clone()),
+            StatusPrazoCerto::new(20, Periodicidade::Anual),
+        );
+        let sobrevivente_temporario = StatusConjunto::new(
+            StatusUltimoSobrevivente::new(tabua1.clone(), tabua2.clone()),
+            StatusPrazoCerto::new(30, Periodicidade::Anual),
+        );
+        let x = vec![40, 38];
+
+        assert_eq!(conjunta_temporaria.numero_vidas(), 2);
+        assert_eq!(conjunta_temporaria.tempo_futuro_maximo(&x), Finite(20));
+        approx::assert_relative_eq!(
+            conjunta_temporaria.tpx(&x, 5),
+            tabua1.tpx(&vec![40], 5) * tabua2.tpx(&vec![38], 5)
+        );
+        assert_eq!(conjunta_temporaria.tpx(&x, 20), 0.0);
+        let p1 = tabua1.tpx(&vec![40], 25);
+        let p2 = tabua2.tpx(&vec![38], 25);
+        approx::assert_relative_eq!(
+            sobrevivente_temporario.tpx(&x, 25),
+            p1 + p2 - p1 * p2,
+            epsilon = 1e-15
+        );
+        approx::assert_relative_eq!(
+            sobrevivente_temporario.t_qx(&x, 25),
+            sobrevivente_temporario.tpx(&x, 25) - sobrevivente_temporario.tpx(&x, 26),
+            epsilon = 1e-15
+        );
+    }
+
+    #[test]
+    fn alterar_periodicidade_converte_prazo_e_diferimento() {
+        let status = StatusDiferido::new(StatusPrazoCerto::new(2, Periodicidade::Anual), 1);
+
+        let mensal = status.alterar_periodicidade(Periodicidade::Mensal);
+
+        assert_eq!(mensal.diferimento(), 12);
+        assert_eq!(mensal.tpx(&vec![], 23), 1.0);
+        assert_eq!(mensal.tpx(&vec![], 24), 0.0);
+    }
+
+    #[test]
+    fn prazo_certo_aceita_o_ultimo_tempo() {
+        let status = StatusPrazoCerto::new(u16::MAX, Periodicidade::Anual);
+
+        assert_eq!(status.qx(&vec![], u16::MAX - 2), 0.0);
+        assert_eq!(status.qx(&vec![], u16::MAX - 1), 1.0);
+        assert_eq!(status.qx(&vec![], u16::MAX), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "O prazo não corresponde a um período completo")]
+    fn prazo_deve_corresponder_a_periodos_completos() {
+        StatusPrazoCerto::new(5, Periodicidade::Mensal).alterar_periodicidade(Periodicidade::Anual);
+    }
+
+    #[test]
+    #[should_panic(expected = "Todas as tabuas devem possuir a mesma periodicidade.")]
+    fn status_combinados_exigem_mesma_periodicidade() {
+        StatusConjunto::new(
+            criar_tabua(0.01),
+            StatusPrazoCerto::new(5, Periodicidade::Mensal),
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::alterar::alterar_periodicidade;
+use crate::alterar::{agravar_forca_qx, agravar_qx};
 use crate::interface::{validar_idades_tabuas, TabuaInterface};
 use crate::tabua_base::TabuaBase;
 use crate::Periodicidade;
@@ -18,6 +18,44 @@ pub fn extrair_tabua_base_e_periodicidade(tabuas: Vec<Tabua>) -> (Vec<TabuaBase>
     return (tabuas, periodicidade.clone());
 }
 
+pub(crate) fn normalizar_pesos(pesos: &Vec<f64>, quantidade_tabuas: usize) -> Vec<f64> {
+    if pesos.len() != quantidade_tabuas {
+        panic!("A quantidade de pesos deve ser igual à quantidade de tabuas.");
+    }
+    if pesos.iter().any(|peso| *peso < 0.0 || !peso.is_finite()) {
+        panic!("Os pesos devem ser finitos e maiores que ou iguais a zero.");
+    }
+    let soma: f64 = pesos.iter().sum();
+    if soma <= 0.0 {
+        panic!("A soma dos pesos deve ser maior que zero.");
+    }
+    return pesos.iter().map(|peso| peso / soma).collect();
+}
+
+// Tratamento das idades anteriores à idade inicial da tábua.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegraIdadeInferior {
+    Erro,
+    RepetirPrimeiroQx,
+    SemMortalidade,
+}
+
+fn idade_inicial_maxima(tabuas: &Vec<TabuaBase>) -> u16 {
+    return tabuas
+        .iter()
+        .map(|tabua| tabua.idade_inicial())
+        .max()
+        .expect("Deveria existir pelo menos uma tabua.");
+}
+
+fn idade_limite_maxima(tabuas: &Vec<TabuaBase>) -> usize {
+    return tabuas
+        .iter()
+        .map(|tabua| tabua.idade_limite())
+        .max()
+        .expect("Deveria existir pelo menos uma tabua.");
+}
+
 #[derive(Debug, Clone)]
 pub struct Tabua {
     tabua: TabuaBase,
@@ -35,6 +73,112 @@ impl Tabua {
             periodicidade,
         };
     }
+
+    // Tábua cujo primeiro qx se refere à idade_inicial, como as tábuas publicadas a partir dos
+    // 14 ou 18 anos.
+    pub fn a_partir_da_idade(
+        idade_inicial: u16,
+        qx: Vec<f64>,
+        periodicidade: Periodicidade,
+        regra_idade_inferior: RegraIdadeInferior,
+    ) -> Self {
+        return Tabua {
+            tabua: TabuaBase::a_partir_da_idade(idade_inicial, qx, regra_idade_inferior),
+            periodicidade,
+        };
+    }
+
+    pub(crate) fn a_partir_da_tabua_base(tabua: TabuaBase, periodicidade: Periodicidade) -> Self {
+        return Tabua {
+            tabua,
+            periodicidade,
+        };
+    }
+
+    pub fn idade_inicial(&self) -> u16 {
+        return self.tabua.idade_inicial();
+    }
+
+    pub fn regra_idade_inferior(&self) -> &RegraIdadeInferior {
+        return self.tabua.regra_idade_inferior();
+    }
+
+    pub fn agravar(&self, fator: f64) -> Self {
+        return Tabua::a_partir_da_tabua_base(
+            self.tabua.com_qx(agravar_qx(self.tabua.qx.clone(), fator)),
+            self.periodicidade.clone(),
+        );
+    }
+
+    // Agravamento da taxa central: qx' = 1 - (1 - qx)^fator.
+    pub fn agravar_forca(&self, fator: f64) -> Self {
+        return Tabua::a_partir_da_tabua_base(
+            self.tabua
+                .com_qx(agravar_forca_qx(self.tabua.qx.clone(), fator)),
+            self.periodicidade.clone(),
+        );
+    }
+
+    pub fn misturar_qx(tabuas: Vec<Tabua>, pesos: Vec<f64>) -> Self {
+        if tabuas.is_empty() {
+            panic!("A mistura deve possuir pelo menos uma tabua.");
+        }
+        let pesos = normalizar_pesos(&pesos, tabuas.len());
+        let (tabuas, periodicidade) = extrair_tabua_base_e_periodicidade(tabuas);
+
+        let idade_inicial = idade_inicial_maxima(&tabuas);
+        let qx = (idade_inicial as usize..idade_limite_maxima(&tabuas))
+            .map(|x| {
+                tabuas
+                    .iter()
+                    .zip(pesos.iter())
+                    .fold(0.0, |acc, (tabua, peso)| acc + peso * tabua.qx(x as u16, 0))
+            })
+            .collect();
+
+        return Tabua::a_partir_da_idade(
+            idade_inicial,
+            qx,
+            periodicidade,
+            tabuas[0].regra_idade_inferior().clone(),
+        );
+    }
+
+    // Os pesos se referem à composição na idade inicial da mistura, a maior entre as idades
+    // iniciais das tábuas. A partir daí, a participação de cada tábua é proporcional ao seu lx,
+    // ou seja, qx = sum(w * lx * qx) / sum(w * lx).
+    pub fn misturar_lx(tabuas: Vec<Tabua>, pesos: Vec<f64>) -> Self {
+        if tabuas.is_empty() {
+            panic!("A mistura deve possuir pelo menos uma tabua.");
+        }
+        let pesos = normalizar_pesos(&pesos, tabuas.len());
+        let (tabuas, periodicidade) = extrair_tabua_base_e_periodicidade(tabuas);
+
+        let idade_inicial = idade_inicial_maxima(&tabuas);
+        let mut qx = Vec::new();
+        for x in idade_inicial as usize..idade_limite_maxima(&tabuas) {
+            let (lx, dx) =
+                tabuas
+                    .iter()
+                    .zip(pesos.iter())
+                    .fold((0.0, 0.0), |(lx, dx), (tabua, peso)| {
+                        let lx_tabua = peso * tabua.tpx(idade_inicial, x as u16 - idade_inicial);
+                        (lx + lx_tabua, dx + lx_tabua * tabua.qx(x as u16, 0))
+                    });
+            if lx == 0.0 {
+                qx.push(1.0);
+                break;
+            }
+            qx.push(dx / lx);
+        }
+
+        return Tabua::a_partir_da_idade(
+            idade_inicial,
+            qx,
+            periodicidade,
+            tabuas[0].regra_idade_inferior().clone(),
+        );
+    }
 }
 
 impl TabuaInterface for Tabua {
@@ -66,18 +210,18 @@ impl TabuaInterface for Tabua {
     }
 
     fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
-        let qx = alterar_periodicidade(
-            self.tabua.qx.clone(),
+        let tabua = self.tabua.alterar_periodicidade(
             self.periodicidade.quantidade_periodos_1_ano() as usize,
             nova_periodicidade.quantidade_periodos_1_ano() as usize,
         );
-        return Tabua::new(qx, nova_periodicidade);
+        return Tabua::a_partir_da_tabua_base(tabua, nova_periodicidade);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx;
 
     fn criar_tabua() -> Tabua {
         return Tabua::new(vec![0.0, 0.1, 0.5, 0.8, 1.0], Periodicidade::Mensal);
@@ -142,4 +286,140 @@ mod tests {
 
         assert_eq!(tabua_alterada.periodicidade(), &Periodicidade::Anual);
     }
+
+    #[test]
+    fn agravar_multiplica_qx_da_tabua() {
+        let tabua = criar_tabua();
+
+        let tabua_agravada = tabua.agravar(1.5);
+
+        approx::assert_abs_diff_eq!(tabua_agravada.qx(&vec![1], 0), 0.15);
+        approx::assert_abs_diff_eq!(tabua_agravada.qx(&vec![3], 0), 1.0);
+        assert_eq!(tabua_agravada.periodicidade(), &Periodicidade::Mensal);
+    }
+
+    #[test]
+    fn misturar_qx_retorna_media_ponderada_dos_qx() {
+        let tabua1 = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+        let tabua2 = Tabua::new(vec![0.3, 0.4, 0.6, 1.0], Periodicidade::Anual);
+
+        let mistura = Tabua::misturar_qx(vec![tabua1, tabua2], vec![3.0, 1.0]);
+
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![0], 0), 0.15);
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![1], 0), 0.25);
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![2], 0), 0.45);
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![3], 0), 1.0);
+    }
+
+    #[test]
+    fn misturar_qx_estende_tabuas_mais_curtas() {
+        let tabua1 = Tabua::new(vec![0.1, 0.2], Periodicidade::Anual);
+        let tabua2 = Tabua::new(vec![0.3, 0.4, 0.6, 0.8], Periodicidade::Anual);
+
+        let mistura = Tabua::misturar_qx(vec![tabua1, tabua2], vec![1.0, 1.0]);
+
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![3], 0), 0.5);
+    }
+
+    #[test]
+    fn misturar_lx_retorna_lx_igual_a_soma_ponderada_dos_lx() {
+        let tabua1 = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+        let tabua2 = Tabua::new(vec![0.3, 0.4, 0.6, 0.9, 1.0], Periodicidade::Anual);
+        let pesos = vec![0.6, 0.4];
+
+        let mistura = Tabua::misturar_lx(vec![tabua1.clone(), tabua2.clone()], pesos.clone());
+
+        for t in 0..6 {
+            approx::assert_abs_diff_eq!(
+                mistura.tpx(&vec![0], t),
+                pesos[0] * tabua1.tpx(&vec![0], t) + pesos[1] * tabua2.tpx(&vec![0], t),
+                epsilon = 1e-12
+            );
+        }
+        assert_eq!(
+            mistura.tempo_futuro_maximo(&vec![0]),
+            Infinitable::Finite(5)
+        );
+    }
+
+    #[test]
+    fn misturar_lx_coincide_com_misturar_qx_na_idade_inicial() {
+        let tabua1 = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+        let tabua2 = Tabua::new(vec![0.3, 0.4, 0.6, 1.0], Periodicidade::Anual);
+
+        let mistura_lx = Tabua::misturar_lx(vec![tabua1.clone(), tabua2.clone()], vec![1.0, 1.0]);
+        let mistura_qx = Tabua::misturar_qx(vec![tabua1, tabua2], vec![1.0, 1.0]);
+
+        approx::assert_abs_diff_eq!(mistura_lx.qx(&vec![0], 0), mistura_qx.qx(&vec![0], 0));
+        assert!(mistura_lx.qx(&vec![1], 0) < mistura_qx.qx(&vec![1], 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "A quantidade de pesos deve ser igual à quantidade de tabuas.")]
+    fn misturar_qx_da_erro_se_quantidade_de_pesos_for_diferente_da_de_tabuas() {
+        Tabua::misturar_qx(vec![criar_tabua(), criar_tabua()], vec![1.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Todas as tabuas devem possuir a mesma periodicidade.")]
+    fn misturar_lx_da_erro_se_tabuas_tiverem_periodicidades_diferentes() {
+        let tabuas = vec![
+            Tabua::new(vec![0.0, 0.1, 0.5, 0.8, 1.0], Periodicidade::Mensal),
+            Tabua::new(vec![0.0, 0.1, 0.5, 0.8, 1.0], Periodicidade::Anual),
+        ];
+
+        Tabua::misturar_lx(tabuas, vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn tabua_a_partir_da_idade_nao_precisa_de_qx_ficticios() {
+        let tabua = Tabua::a_partir_da_idade(
+            14,
+            vec![0.1, 0.2, 1.0],
+            Periodicidade::Anual,
+            RegraIdadeInferior::Erro,
+        );
+
+        assert_eq!(tabua.idade_inicial(), 14);
+        assert_eq!(tabua.qx(&vec![15], 0), 0.2);
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![14]), Infinitable::Finite(3));
+        assert_eq!(tabua.agravar(2.0).qx(&vec![14], 0), 0.2);
+    }
+
+    #[test]
+    fn alterar_periodicidade_preserva_a_idade_inicial() {
+        let tabua = Tabua::a_partir_da_idade(
+            18,
+            vec![0.1, 0.2, 1.0],
+            Periodicidade::Anual,
+            RegraIdadeInferior::Erro,
+        );
+
+        let mensal = tabua.alterar_periodicidade(Periodicidade::Mensal);
+
+        assert_eq!(mensal.idade_inicial(), 216);
+        assert_eq!(
+            mensal
+                .alterar_periodicidade(Periodicidade::Anual)
+                .idade_inicial(),
+            18
+        );
+    }
+
+    #[test]
+    fn misturar_qx_comeca_na_maior_idade_inicial() {
+        let tabua1 = Tabua::new(vec![0.1, 0.2, 0.4, 1.0], Periodicidade::Anual);
+        let tabua2 = Tabua::a_partir_da_idade(
+            2,
+            vec![0.6, 1.0],
+            Periodicidade::Anual,
+            RegraIdadeInferior::Erro,
+        );
+
+        let mistura = Tabua::misturar_qx(vec![tabua1, tabua2], vec![0.5, 0.5]);
+
+        assert_eq!(mistura.idade_inicial(), 2);
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![2], 0), 0.5);
+        approx::assert_abs_diff_eq!(mistura.qx(&vec![3], 0), 1.0);
+    }
 }
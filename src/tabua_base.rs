@@ -1,9 +1,11 @@
+use crate::alterar::{alterar_idade_inicial, alterar_periodicidade};
+use crate::tabua::RegraIdadeInferior;
 use infinitable::Infinitable::{self, Finite, Infinity, NegativeInfinity};
 use std::cmp;
 
-fn calcular_lx(qx: &Vec<f64>, raiz: u16) -> Vec<f64> {
+pub(crate) fn calcular_lx(qx: &Vec<f64>, raiz: f64) -> Vec<f64> {
     let mut lx = Vec::new();
-    lx.push(raiz as f64);
+    lx.push(raiz);
     for i in 1..(qx.len() + 1) {
         lx.push(lx[i - 1] * (1.0 - qx[i - 1]));
         if lx[i] == 0.0 {
@@ -26,23 +28,94 @@ pub struct TabuaBase {
     pub qx: Vec<f64>,
     lx: Vec<f64>,
     w: Infinitable<u16>,
+    idade_inicial: u16,
+    regra_idade_inferior: RegraIdadeInferior,
 }
 
 impl TabuaBase {
     pub fn new(qx: Vec<f64>) -> TabuaBase {
-        let lx = calcular_lx(&qx, 10000);
+        return TabuaBase::a_partir_da_idade(0, qx, RegraIdadeInferior::Erro);
+    }
+
+    // qx[0] se refere à idade_inicial. Internamente as idades são deslocadas para começar em
+    // zero e a regra define o tratamento das idades anteriores.
+    pub fn a_partir_da_idade(
+        idade_inicial: u16,
+        qx: Vec<f64>,
+        regra_idade_inferior: RegraIdadeInferior,
+    ) -> TabuaBase {
+        if qx.is_empty() {
+            panic!("A tábua deve possuir pelo menos um qx.");
+        }
+        let lx = calcular_lx(&qx, 10000.0);
         let mut w = Infinity;
 
         if lx[lx.len() - 1] == 0.0 {
             w = Finite((lx.len() - 2) as u16);
         }
 
-        return TabuaBase { qx, lx, w };
+        return TabuaBase {
+            qx,
+            lx,
+            w,
+            idade_inicial,
+            regra_idade_inferior,
+        };
+    }
+
+    pub fn idade_inicial(&self) -> u16 {
+        return self.idade_inicial;
+    }
+
+    pub fn regra_idade_inferior(&self) -> &RegraIdadeInferior {
+        return &self.regra_idade_inferior;
+    }
+
+    // Primeira idade após o último qx informado.
+    pub fn idade_limite(&self) -> usize {
+        return self.idade_inicial as usize + self.qx.len();
+    }
+
+    pub(crate) fn com_qx(&self, qx: Vec<f64>) -> TabuaBase {
+        return TabuaBase::a_partir_da_idade(
+            self.idade_inicial,
+            qx,
+            self.regra_idade_inferior.clone(),
+        );
+    }
+
+    pub(crate) fn alterar_periodicidade(
+        &self,
+        periodicidade_atual: usize,
+        nova_periodicidade: usize,
+    ) -> TabuaBase {
+        return TabuaBase::a_partir_da_idade(
+            alterar_idade_inicial(self.idade_inicial, periodicidade_atual, nova_periodicidade),
+            alterar_periodicidade(self.qx.clone(), periodicidade_atual, nova_periodicidade),
+            self.regra_idade_inferior.clone(),
+        );
+    }
+
+    fn validar_idade(&self, x: u16) {
+        if x < self.idade_inicial && self.regra_idade_inferior == RegraIdadeInferior::Erro {
+            panic!(
+                "A idade {x} é inferior à idade inicial da tábua ({}).",
+                self.idade_inicial
+            );
+        }
+    }
+
+    fn qx_idade_inferior(&self) -> f64 {
+        return match self.regra_idade_inferior {
+            RegraIdadeInferior::Erro => panic!("A idade é inferior à idade inicial da tábua."),
+            RegraIdadeInferior::RepetirPrimeiroQx => self.qx[0],
+            RegraIdadeInferior::SemMortalidade => 0.0,
+        };
     }
 
     fn lx(&self, x: Infinitable<u16>) -> f64 {
         let limite_superior_x =
-            min_positive_finite(self.tempo_futuro_maximo(0), self.qx.len() as u16);
+            min_positive_finite(self.tempo_futuro_maximo_interno(0), self.qx.len() as u16);
 
         let x_trunc = min_positive_finite(x, limite_superior_x);
 
@@ -68,7 +141,7 @@ impl TabuaBase {
         return lx_ret;
     }
 
-    pub fn tempo_futuro_maximo(&self, x: u16) -> Infinitable<u16> {
+    fn tempo_futuro_maximo_interno(&self, x: u16) -> Infinitable<u16> {
         return match self.w {
             Finite(w) => {
                 if (w + 1) > x {
@@ -86,20 +159,22 @@ impl TabuaBase {
         return !self.w.is_finite();
     }
 
-    pub fn qx(&self, x: u16, t: u16) -> f64 {
-        let limite_superior_x =
-            min_positive_finite(self.tempo_futuro_maximo(0), (self.qx.len() - 1) as u16);
+    fn qx_interno(&self, x: u16, t: u16) -> f64 {
+        let limite_superior_x = min_positive_finite(
+            self.tempo_futuro_maximo_interno(0),
+            (self.qx.len() - 1) as u16,
+        );
         let x_trunc = cmp::min(x, limite_superior_x);
 
         let limite_superior_t = min_positive_finite(
-            self.tempo_futuro_maximo(x),
+            self.tempo_futuro_maximo_interno(x),
             self.qx.len() as u16 - x_trunc - 1,
         );
         let t_trunc = cmp::min(t, limite_superior_t);
         return self.qx[(x_trunc + t_trunc) as usize];
     }
 
-    pub fn tpx(&self, x: u16, t: u16) -> f64 {
+    fn tpx_interno(&self, x: u16, t: u16) -> f64 {
         if t == 0 {
             return 1.0;
         }
@@ -111,6 +186,42 @@ impl TabuaBase {
         return lxt / lx;
     }
 
+    pub fn tempo_futuro_maximo(&self, x: u16) -> Infinitable<u16> {
+        self.validar_idade(x);
+        if x >= self.idade_inicial {
+            return self.tempo_futuro_maximo_interno(x - self.idade_inicial);
+        }
+        if self.qx_idade_inferior() == 1.0 {
+            return Finite(1);
+        }
+        return match self.tempo_futuro_maximo_interno(0) {
+            Finite(tempo) => Finite(tempo + self.idade_inicial - x),
+            tempo => tempo,
+        };
+    }
+
+    pub fn qx(&self, x: u16, t: u16) -> f64 {
+        self.validar_idade(x);
+        if x >= self.idade_inicial {
+            return self.qx_interno(x - self.idade_inicial, t);
+        }
+        let anos_ate_idade_inicial = self.idade_inicial - x;
+        if t < anos_ate_idade_inicial {
+            return self.qx_idade_inferior();
+        }
+        return self.qx_interno(0, t - anos_ate_idade_inicial);
+    }
+
+    pub fn tpx(&self, x: u16, t: u16) -> f64 {
+        self.validar_idade(x);
+        if x >= self.idade_inicial {
+            return self.tpx_interno(x - self.idade_inicial, t);
+        }
+        let tempo_abaixo = cmp::min(t, self.idade_inicial - x);
+        let tpx_abaixo = (1.0 - self.qx_idade_inferior()).powi(tempo_abaixo as i32);
+        return tpx_abaixo * self.tpx_interno(0, t - tempo_abaixo);
+    }
+
     pub fn t_qx(&self, x: u16, t: u16) -> f64 {
         return self.qx(x, t) * self.tpx(x, t);
     }
@@ -288,4 +399,54 @@ mod tests {
         assert_eq!(tabua.t_qx(x, 2), 0.0);
         assert_eq!(tabua.t_qx(x, 3), 0.0);
     }
+
+    fn criar_tabua_a_partir_dos_18(regra: RegraIdadeInferior) -> TabuaBase {
+        TabuaBase::a_partir_da_idade(18, vec![0.1, 0.2, 0.4, 0.8, 1.0], regra)
+    }
+
+    #[test]
+    fn idade_inicial_desloca_o_indice_dos_qx() {
+        let tabua = criar_tabua_a_partir_dos_18(RegraIdadeInferior::Erro);
+
+        assert_eq!(tabua.qx(18, 0), 0.1);
+        assert_eq!(tabua.qx(19, 1), 0.4);
+        approx::assert_abs_diff_eq!(tabua.tpx(18, 2), 0.9 * 0.8);
+        assert_eq!(tabua.tempo_futuro_maximo(18), Finite(5));
+        assert_eq!(tabua.tempo_futuro_maximo(21), Finite(2));
+        assert_eq!(tabua.idade_limite(), 23);
+    }
+
+    #[test]
+    #[should_panic(expected = "A idade 17 é inferior à idade inicial da tábua (18).")]
+    fn idade_inferior_a_inicial_da_erro_por_padrao() {
+        criar_tabua_a_partir_dos_18(RegraIdadeInferior::Erro).qx(17, 1);
+    }
+
+    #[test]
+    fn idade_inferior_pode_repetir_o_primeiro_qx() {
+        let tabua = criar_tabua_a_partir_dos_18(RegraIdadeInferior::RepetirPrimeiroQx);
+
+        assert_eq!(tabua.qx(16, 0), 0.1);
+        assert_eq!(tabua.qx(16, 3), 0.2);
+        approx::assert_abs_diff_eq!(tabua.tpx(16, 3), 0.9 * 0.9 * 0.9);
+        assert_eq!(tabua.tempo_futuro_maximo(16), Finite(7));
+    }
+
+    #[test]
+    fn idade_inferior_pode_nao_ter_mortalidade() {
+        let tabua = criar_tabua_a_partir_dos_18(RegraIdadeInferior::SemMortalidade);
+
+        assert_eq!(tabua.qx(10, 0), 0.0);
+        approx::assert_abs_diff_eq!(tabua.tpx(10, 10), 0.9 * 0.8);
+    }
+
+    #[test]
+    fn alterar_periodicidade_converte_a_idade_inicial() {
+        let tabua = criar_tabua_a_partir_dos_18(RegraIdadeInferior::Erro);
+
+        let semestral = tabua.alterar_periodicidade(1, 2);
+
+        assert_eq!(semestral.idade_inicial(), 36);
+        assert_eq!(semestral.qx.len(), 10);
+    }
 }
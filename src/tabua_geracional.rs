@@ -0,0 +1,231 @@
+use crate::interface::{validar_idades_tabuas, TabuaInterface};
+use crate::Periodicidade;
+use crate::Tabua;
+use infinitable::Infinitable::{self, Finite, Infinity, NegativeInfinity};
+
+// Tábua bidimensional com uma tábua de período por ano calendário, a partir de ano_inicial.
+// A idade x se refere ao ano de avaliação, de modo que qx(x, t) é o qx da idade x + t na tábua
+// do ano em que o tempo t cai. Anos posteriores ao último informado repetem a última tábua.
+#[derive(Debug, Clone)]
+pub struct TabuaGeracional {
+    tabuas: Vec<Tabua>,
+    ano_inicial: i32,
+    ano_avaliacao: i32,
+    periodicidade: Periodicidade,
+}
+
+impl TabuaGeracional {
+    pub fn new(tabuas: Vec<Tabua>, ano_inicial: i32, ano_avaliacao: i32) -> Self {
+        if tabuas.is_empty() {
+            panic!("A tábua geracional deve possuir pelo menos uma tábua de período.");
+        }
+        let periodicidade = tabuas[0].periodicidade().clone();
+        if tabuas
+            .iter()
+            .any(|tabua| *tabua.periodicidade() != periodicidade)
+        {
+            panic!("Todas as tabuas devem possuir a mesma periodicidade.");
+        }
+        if ano_avaliacao < ano_inicial {
+            panic!("O ano de avaliação não pode ser anterior ao ano inicial da tábua geracional.");
+        }
+        return TabuaGeracional {
+            tabuas,
+            ano_inicial,
+            ano_avaliacao,
+            periodicidade,
+        };
+    }
+
+    pub fn tabuas(&self) -> &Vec<Tabua> {
+        return &self.tabuas;
+    }
+
+    pub fn ano_inicial(&self) -> i32 {
+        return self.ano_inicial;
+    }
+
+    pub fn ano_avaliacao(&self) -> i32 {
+        return self.ano_avaliacao;
+    }
+
+    pub fn com_ano_avaliacao(&self, ano_avaliacao: i32) -> Self {
+        return TabuaGeracional::new(self.tabuas.clone(), self.ano_inicial, ano_avaliacao);
+    }
+
+    pub fn tabua_do_ano(&self, ano: i32) -> &Tabua {
+        let indice = (ano - self.ano_inicial).clamp(0, self.tabuas.len() as i32 - 1);
+        return &self.tabuas[indice as usize];
+    }
+
+    fn indice_ano(&self, t: u16) -> usize {
+        let anos_decorridos = (t / self.periodicidade.quantidade_periodos_1_ano()) as usize;
+        return (self.ano_avaliacao - self.ano_inicial) as usize + anos_decorridos;
+    }
+
+    // Idades e tempos são somados em períodos e não podem ultrapassar o maior u16.
+    fn somar_periodos(a: u16, b: u16) -> u16 {
+        return a.checked_add(b).unwrap_or_else(|| {
+            panic!(
+                "A soma de {a} e {b} períodos excede a maior idade representável ({}).",
+                u16::MAX
+            )
+        });
+    }
+
+    fn qx_geracional(&self, x: u16, t: u16) -> f64 {
+        let indice = self.indice_ano(t).min(self.tabuas.len() - 1);
+        return self.tabuas[indice].qx(&vec![x], t);
+    }
+}
+
+impl TabuaInterface for TabuaGeracional {
+    fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return 1;
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        let mut t = 0;
+        loop {
+            if self.indice_ano(t) >= self.tabuas.len() - 1 {
+                let ultima = &self.tabuas[self.tabuas.len() - 1];
+                let idade = TabuaGeracional::somar_periodos(x[0], t);
+                return match ultima.tempo_futuro_maximo(&vec![idade]) {
+                    Finite(restante) => Finite(TabuaGeracional::somar_periodos(restante, t)),
+                    Infinity => Infinity,
+                    NegativeInfinity => panic!("O tempo futuro máximo não pode ser negativo."),
+                };
+            }
+            let proximo = TabuaGeracional::somar_periodos(t, 1);
+            if self.qx_geracional(x[0], t) == 1.0 {
+                return Finite(proximo);
+            }
+            t = proximo;
+        }
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return self.qx_geracional(x[0], t);
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        let mut tpx = 1.0;
+        for s in 0..t {
+            tpx *= 1.0 - self.qx_geracional(x[0], s);
+            if tpx == 0.0 {
+                break;
+            }
+        }
+        return tpx;
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        let tabuas = self
+            .tabuas
+            .iter()
+            .map(|tabua| tabua.alterar_periodicidade(nova_periodicidade.clone()))
+            .collect();
+        return TabuaGeracional::new(tabuas, self.ano_inicial, self.ano_avaliacao);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    fn criar_tabua_geracional() -> TabuaGeracional {
+        let tabuas = (0..3)
+            .map(|ano| {
+                Tabua::new(
+                    (0..5)
+                        .map(|x| 0.1 + 0.01 * x as f64 - 0.02 * ano as f64)
+                        .collect(),
+                    Periodicidade::Anual,
+                )
+            })
+            .collect();
+        TabuaGeracional::new(tabuas, 2020, 2020)
+    }
+
+    #[test]
+    fn qx_segue_a_diagonal_da_coorte() {
+        let tabua = criar_tabua_geracional();
+
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 0), 0.11);
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 1), 0.10);
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 2), 0.09);
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 3), 0.10);
+    }
+
+    #[test]
+    fn tpx_eh_produto_dos_px_da_coorte() {
+        let tabua = criar_tabua_geracional();
+
+        approx::assert_relative_eq!(tabua.tpx(&vec![1], 3), 0.89 * 0.90 * 0.91);
+    }
+
+    #[test]
+    fn ano_de_avaliacao_desloca_a_coorte() {
+        let tabua = criar_tabua_geracional().com_ano_avaliacao(2021);
+
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 0), 0.09);
+        approx::assert_relative_eq!(tabua.qx(&vec![1], 1), 0.08);
+    }
+
+    #[test]
+    fn tempo_futuro_maximo_eh_infinito_com_plato() {
+        let tabua = criar_tabua_geracional();
+
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Infinity);
+    }
+
+    #[test]
+    fn tempo_futuro_maximo_respeita_o_fechamento_da_ultima_tabua() {
+        let tabuas = vec![
+            Tabua::new(vec![0.1, 0.2, 0.3], Periodicidade::Anual),
+            Tabua::new(vec![0.1, 0.2, 1.0], Periodicidade::Anual),
+        ];
+        let tabua = TabuaGeracional::new(tabuas, 2020, 2020);
+
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Finite(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "excede a maior idade representável")]
+    fn tempo_futuro_maximo_da_erro_quando_a_idade_excede_o_limite() {
+        let tabuas = vec![
+            Tabua::new(vec![0.1], Periodicidade::Anual),
+            Tabua::new(vec![0.1], Periodicidade::Anual),
+        ];
+        let tabua = TabuaGeracional::new(tabuas, 2020, 2020);
+
+        tabua.tempo_futuro_maximo(&vec![u16::MAX]);
+    }
+
+    #[test]
+    fn alterar_periodicidade_avanca_o_ano_a_cada_ano_completo() {
+        let tabua = criar_tabua_geracional();
+        let mensal = tabua.alterar_periodicidade(Periodicidade::Mensal);
+
+        assert_eq!(mensal.periodicidade(), &Periodicidade::Mensal);
+        let tabuas_mensais: Vec<Tabua> = tabua
+            .tabuas()
+            .iter()
+            .map(|t| t.alterar_periodicidade(Periodicidade::Mensal))
+            .collect();
+        approx::assert_relative_eq!(mensal.qx(&vec![12], 11), tabuas_mensais[0].qx(&vec![23], 0));
+        approx::assert_relative_eq!(mensal.qx(&vec![12], 12), tabuas_mensais[1].qx(&vec![24], 0));
+    }
+}
@@ -1,73 +1,398 @@
-use crate::alterar::alterar_periodicidade;
+use crate::alterar::alterar_idade_inicial;
 use crate::interface::{validar_idades_tabuas, TabuaInterface};
-use crate::tabua::extrair_tabua_base_e_periodicidade;
+use crate::tabua::{extrair_tabua_base_e_periodicidade, RegraIdadeInferior};
 use crate::tabua_base::TabuaBase;
 use crate::Periodicidade;
 use crate::Tabua;
 use infinitable::Infinitable;
 
-fn qx2qxj(qx1: &f64, qx2: &f64, qx3: &f64) -> f64 {
-    return qx1 * (1.0 - 0.5 * (qx2 + qx3) + 1.0 / 3.0 * (qx2 * qx3));
+#[derive(Debug, Clone, PartialEq)]
+pub enum HipoteseMdt {
+    // UDD em cada tábua de decremento simples associada.
+    UddTabuasSimples,
+    // UDD na tábua de múltiplos decrementos: t_q^(j) = t q^(j) para 0 <= t <= 1.
+    UddMdt,
+    // Força de cada decremento constante dentro do período.
+    ForcaConstante,
 }
 
-fn converter_mdt(qx: Vec<f64>) -> Vec<f64> {
-    let tamanho = qx.len();
+// Coeficientes do polinômio prod(1 - s * q'_k) em s, para k diferente de j.
+fn coeficientes_sobrevivencia_outras(qx: &Vec<f64>, j: usize) -> Vec<f64> {
+    let mut coeficientes = vec![1.0];
+    for (k, q) in qx.iter().enumerate() {
+        if k == j {
+            continue;
+        }
+        let mut proximo = vec![0.0; coeficientes.len() + 1];
+        for (i, c) in coeficientes.iter().enumerate() {
+            proximo[i] += c;
+            proximo[i + 1] -= c * q;
+        }
+        coeficientes = proximo;
+    }
+    return coeficientes;
+}
+
+// q^(j) = q'_j * integral de 0 a 1 de prod_{k != j}(1 - s q'_k) ds.
+fn qx2qxj(qx: &Vec<f64>, j: usize) -> f64 {
+    let integral = coeficientes_sobrevivencia_outras(qx, j)
+        .iter()
+        .enumerate()
+        .fold(0.0, |acc, (i, c)| acc + c / (i + 1) as f64);
+    return qx[j] * integral;
+}
+
+// Sob UDD na MDT e sob força constante, q^(j) = q^(τ) ln p'_j / ln p^(τ). Se algum q'_j for 1,
+// as forças não são finitas e a divisão de q^(τ) = 1 entre as causas não é determinada pelos q'.
+// Por convenção ele é dividido igualmente entre os decrementos com q' igual a 1, que é o limite
+// da fórmula quando esses q' tendem a 1 juntos. Essa é uma limitação da hipótese: uma divisão
+// diferente, como q^(j) = 0.6 e 0.4, não pode ser representada pelos q'.
+fn qx2qxj_proporcional_log(qx: &Vec<f64>) -> Vec<f64> {
+    let certos = qx.iter().filter(|q| **q >= 1.0).count();
+    if certos > 0 {
+        return qx
+            .iter()
+            .map(|q| if *q >= 1.0 { 1.0 / certos as f64 } else { 0.0 })
+            .collect();
+    }
+    let ln_p_total: f64 = qx.iter().map(|q| (1.0 - q).ln()).sum();
+    if ln_p_total == 0.0 {
+        return vec![0.0; qx.len()];
+    }
+    let q_total = 1.0 - ln_p_total.exp();
+    return qx
+        .iter()
+        .map(|q| q_total * (1.0 - q).ln() / ln_p_total)
+        .collect();
+}
+
+fn inverter_proporcional_log(qx_dependentes: &Vec<f64>) -> Vec<f64> {
+    let q_total: f64 = qx_dependentes.iter().sum();
+    if q_total == 0.0 {
+        return vec![0.0; qx_dependentes.len()];
+    }
+    if q_total >= 1.0 {
+        return qx_dependentes
+            .iter()
+            .map(|q| if *q > 0.0 { 1.0 } else { 0.0 })
+            .collect();
+    }
+    return qx_dependentes
+        .iter()
+        .map(|q| 1.0 - (1.0 - q_total).powf(q / q_total))
+        .collect();
+}
+
+// Iteração de ponto fixo q'_j = q^(j) / integral de 0 a 1 de prod_{k != j}(1 - s q'_k) ds.
+fn inverter_udd_tabuas_simples(qx_dependentes: &Vec<f64>) -> Vec<f64> {
+    let mut qx = qx_dependentes.clone();
+    for _ in 0..1000 {
+        let proximo: Vec<f64> = (0..qx.len())
+            .map(|j| {
+                if qx_dependentes[j] == 0.0 {
+                    return 0.0;
+                }
+                let integral = coeficientes_sobrevivencia_outras(&qx, j)
+                    .iter()
+                    .enumerate()
+                    .fold(0.0, |acc, (i, c)| acc + c / (i + 1) as f64);
+                (qx_dependentes[j] / integral).min(1.0)
+            })
+            .collect();
+        let variacao = proximo
+            .iter()
+            .zip(qx.iter())
+            .fold(0.0_f64, |acc, (a, b)| acc.max((a - b).abs()));
+        qx = proximo;
+        if variacao < 1e-15 {
+            break;
+        }
+    }
+    return qx;
+}
 
-    if tamanho == 0 {
+// Inversa de converter_mdt: qx das tábuas de decremento simples associadas a partir dos qx
+// dependentes de cada causa. Retorna None quando não há solução, o que é verificado refazendo a
+// conversão direta. Sob UDD na MDT e sob força constante, uma idade com saída certa
+// (soma dos q^(j) igual a 1) retorna q' = 1 para as causas com q^(j) positivo: o q^(τ) e o tpx
+// são reproduzidos, mas a divisão entre as causas passa a seguir a convenção de
+// qx2qxj_proporcional_log.
+pub fn inverter_mdt(qx_dependentes: Vec<f64>, hipotese: &HipoteseMdt) -> Option<Vec<f64>> {
+    if qx_dependentes.is_empty() {
         panic!("A quantidade de decrementos não pode ser zero.");
     }
+    let soma: f64 = qx_dependentes.iter().sum();
+    if qx_dependentes.iter().any(|q| !(0.0..=1.0).contains(q)) || soma > 1.0 + 1e-12 {
+        return None;
+    }
 
-    if tamanho > 3 {
-        panic!("A quantidade de decrementos não pode ser maior que 3, obtido = {tamanho}.");
+    let saida_certa = (soma - 1.0).abs() <= 1e-12;
+    if saida_certa && *hipotese != HipoteseMdt::UddTabuasSimples {
+        return Some(inverter_proporcional_log(&qx_dependentes));
     }
 
-    let mut qx_mdt = vec![0.0; 3];
+    let qx = match hipotese {
+        HipoteseMdt::UddTabuasSimples => inverter_udd_tabuas_simples(&qx_dependentes),
+        HipoteseMdt::UddMdt | HipoteseMdt::ForcaConstante => {
+            inverter_proporcional_log(&qx_dependentes)
+        }
+    };
+    let erro = converter_mdt(qx.clone(), hipotese)
+        .iter()
+        .zip(qx_dependentes.iter())
+        .fold(0.0_f64, |acc, (a, b)| acc.max((a - b).abs()));
+    if erro > 1e-10 {
+        return None;
+    }
+    return Some(qx);
+}
 
-    for i in 0..3 {
-        qx_mdt[i] = qx2qxj(
-            qx.get(i % 3).unwrap_or(&0.0),
-            qx.get((i + 1) % 3).unwrap_or(&0.0),
-            qx.get((i + 2) % 3).unwrap_or(&0.0),
-        );
+// Converte os qx das tábuas de decremento simples associadas nos qx dependentes de cada causa.
+pub fn converter_mdt(qx: Vec<f64>, hipotese: &HipoteseMdt) -> Vec<f64> {
+    if qx.is_empty() {
+        panic!("A quantidade de decrementos não pode ser zero.");
     }
 
-    return qx_mdt;
+    return match hipotese {
+        HipoteseMdt::UddTabuasSimples => (0..qx.len()).map(|j| qx2qxj(&qx, j)).collect(),
+        HipoteseMdt::UddMdt | HipoteseMdt::ForcaConstante => qx2qxj_proporcional_log(&qx),
+    };
 }
 
 #[derive(Debug)]
 pub struct TabuaMDT {
     tabuas: Vec<TabuaBase>,
     periodicidade: Periodicidade,
+    hipotese: HipoteseMdt,
+    nomes_decrementos: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ResultadoInversaoMdt {
+    pub tabua: TabuaMDT,
+    // Idades em que os qx dependentes não correspondem a nenhum conjunto de qx independentes.
+    // Nelas os qx independentes são os da última iteração, limitados a [0, 1].
+    pub idades_sem_solucao: Vec<u16>,
 }
 
 impl TabuaMDT {
-    pub fn new(tabuas: Vec<Tabua>) -> Self {
+    // A hipótese define como os qx das tábuas de decremento simples associadas são convertidos
+    // nos qx dependentes de cada causa e como a sobrevivência se distribui dentro do período.
+    // Nos períodos inteiros o tpx é prod p'_j em qualquer hipótese; nas frações, usadas em
+    // alterar_periodicidade, ele depende da hipótese.
+    pub fn new(tabuas: Vec<Tabua>, hipotese: HipoteseMdt) -> Self {
         if tabuas.len() < 1 {
             panic!("Tabua MDT deve possuir pelo menos uma tabua.");
         }
-        if tabuas.len() > 3 {
-            panic!("Tabua MDT deve possuir no máximo três tabuas.");
-        }
 
         let (tabuas, periodicidade) = extrair_tabua_base_e_periodicidade(tabuas);
+        let nomes_decrementos = (1..=tabuas.len())
+            .map(|j| format!("decremento {j}"))
+            .collect();
 
         return TabuaMDT {
             tabuas,
             periodicidade,
+            hipotese,
+            nomes_decrementos,
         };
     }
 
-    fn qx_j(&self, x: &Vec<u16>, t: u16, j: usize) -> f64 {
-        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
-        if j > self.numero_decrementos() {
-            panic!("j não pode ser maior que o número de decrementos. j = {j}, número de decrementos = {}", self.numero_decrementos());
+    // qx_dependentes[j][i] é o qx dependente da causa j na idade idade_inicial + i.
+    pub fn a_partir_de_qx_dependentes(
+        idade_inicial: u16,
+        qx_dependentes: Vec<Vec<f64>>,
+        periodicidade: Periodicidade,
+        hipotese: HipoteseMdt,
+    ) -> ResultadoInversaoMdt {
+        if qx_dependentes.is_empty() {
+            panic!("Tabua MDT deve possuir pelo menos uma tabua.");
+        }
+        let tamanho = qx_dependentes[0].len();
+        if qx_dependentes.iter().any(|coluna| coluna.len() != tamanho) {
+            panic!("Todas as causas devem possuir a mesma quantidade de idades.");
+        }
+
+        let mut idades_sem_solucao = Vec::new();
+        let mut qx_independentes = vec![Vec::with_capacity(tamanho); qx_dependentes.len()];
+        for i in 0..tamanho {
+            let qx_idade: Vec<f64> = qx_dependentes.iter().map(|coluna| coluna[i]).collect();
+            let qx = match inverter_mdt(qx_idade.clone(), &hipotese) {
+                Some(qx) => qx,
+                None => {
+                    idades_sem_solucao.push(idade_inicial + i as u16);
+                    match hipotese {
+                        HipoteseMdt::UddTabuasSimples => inverter_udd_tabuas_simples(&qx_idade),
+                        _ => inverter_proporcional_log(&qx_idade),
+                    }
+                    .iter()
+                    .map(|q| q.clamp(0.0, 1.0))
+                    .collect()
+                }
+            };
+            for (coluna, q) in qx_independentes.iter_mut().zip(qx) {
+                coluna.push(q);
+            }
         }
 
+        let tabuas = qx_independentes
+            .into_iter()
+            .map(|qx| {
+                Tabua::a_partir_da_idade(
+                    idade_inicial,
+                    qx,
+                    periodicidade.clone(),
+                    RegraIdadeInferior::Erro,
+                )
+            })
+            .collect();
+        return ResultadoInversaoMdt {
+            tabua: TabuaMDT::new(tabuas, hipotese),
+            idades_sem_solucao,
+        };
+    }
+
+    pub fn hipotese(&self) -> &HipoteseMdt {
+        return &self.hipotese;
+    }
+
+    pub(crate) fn obter_tabuas_base(&self) -> &Vec<TabuaBase> {
+        return &self.tabuas;
+    }
+
+    // Nomeia os decrementos na ordem das tábuas, por exemplo "morte", "invalidez" e
+    // "cancelamento".
+    pub fn com_nomes_decrementos(mut self, nomes: Vec<String>) -> Self {
+        if nomes.len() != self.tabuas.len() {
+            panic!(
+                "A quantidade de nomes ({}) deve ser igual à de decrementos ({}).",
+                nomes.len(),
+                self.tabuas.len()
+            );
+        }
+        if (1..nomes.len()).any(|i| nomes[..i].contains(&nomes[i])) {
+            panic!("Os nomes dos decrementos devem ser distintos.");
+        }
+        self.nomes_decrementos = nomes;
+        return self;
+    }
+
+    pub fn nomes_decrementos(&self) -> &Vec<String> {
+        return &self.nomes_decrementos;
+    }
+
+    pub fn indice_decremento(&self, nome: &str) -> usize {
+        return self
+            .nomes_decrementos
+            .iter()
+            .position(|n| n == nome)
+            .unwrap_or_else(|| panic!("Decremento desconhecido: {nome}."));
+    }
+
+    // qx dependente de cada decremento no período t, com uma única conversão.
+    pub fn qx_decrementos(&self, x: &Vec<u16>, t: u16) -> Vec<f64> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
         let qx: Vec<f64> = (0..self.numero_decrementos())
             .map(|k| self.tabuas[k].qx(x[k], t))
             .collect();
 
-        return converter_mdt(qx)[j];
+        return converter_mdt(qx, &self.hipotese);
+    }
+
+    pub fn qx_j(&self, x: &Vec<u16>, t: u16, j: usize) -> f64 {
+        if j >= self.numero_decrementos() {
+            panic!(
+                "j deve ser menor que o número de decrementos. j = {j}, número de decrementos = {}",
+                self.numero_decrementos()
+            );
+        }
+        return self.qx_decrementos(x, t)[j];
+    }
+
+    // Probabilidade de sair pelo decremento j no período t, estando no grupo no início.
+    pub fn t_qx_j(&self, x: &Vec<u16>, t: u16, j: usize) -> f64 {
+        return self.tpx(x, t) * self.qx_j(x, t, j);
+    }
+
+    // Probabilidade de sair pelo decremento j antes do tempo t.
+    pub fn qx_acumulado_j(&self, x: &Vec<u16>, t: u16, j: usize) -> f64 {
+        return (0..t).map(|k| self.t_qx_j(x, k, j)).sum();
+    }
+
+    pub fn qx_decremento(&self, x: &Vec<u16>, t: u16, nome: &str) -> f64 {
+        return self.qx_j(x, t, self.indice_decremento(nome));
+    }
+
+    pub fn t_qx_decremento(&self, x: &Vec<u16>, t: u16, nome: &str) -> f64 {
+        return self.t_qx_j(x, t, self.indice_decremento(nome));
+    }
+
+    pub fn qx_acumulado_decremento(&self, x: &Vec<u16>, t: u16, nome: &str) -> f64 {
+        return self.qx_acumulado_j(x, t, self.indice_decremento(nome));
+    }
+
+    // Sobrevivência até a fração s (0 <= s <= 1) do período na tábua de decremento simples
+    // associada ao decremento j. Sob UDD na MDT as idades dos decrementos são pareadas, como
+    // quando x é a mesma idade para todos eles.
+    fn sobrevivencia_fracionada(&self, j: usize, idade: u16, s: f64) -> f64 {
+        let qx_j = self.tabuas[j].qx(idade, 0);
+        return match self.hipotese {
+            HipoteseMdt::UddTabuasSimples => 1.0 - s * qx_j,
+            // (1 - s q^(τ))^(q^(j) / q^(τ)), pois a razão entre as forças é constante no período.
+            HipoteseMdt::UddMdt => {
+                let qx: Vec<f64> = self.tabuas.iter().map(|t| t.qx(idade, 0)).collect();
+                let q_total = 1.0 - qx.iter().map(|q| 1.0 - q).product::<f64>();
+                if q_total == 0.0 {
+                    return 1.0;
+                }
+                let q_dependente = converter_mdt(qx, &self.hipotese)[j];
+                (1.0 - s * q_total).powf(q_dependente / q_total)
+            }
+            HipoteseMdt::ForcaConstante => (1.0 - qx_j).powf(s),
+        };
+    }
+
+    // Divide cada período da tábua associada ao decremento j em subperíodos. Na última idade de
+    // uma tábua com platô a divisão usa força constante, para que o qx repetido nas idades
+    // seguintes continue valendo para todos os subperíodos.
+    fn subdividir_tabua(
+        &self,
+        j: usize,
+        periodicidade_atual: usize,
+        nova_periodicidade: usize,
+    ) -> TabuaBase {
+        if !nova_periodicidade.is_multiple_of(periodicidade_atual) {
+            panic!("A nova periodicidade deve ser múltiplo da atual.");
+        }
+        let fator = nova_periodicidade / periodicidade_atual;
+        let tabua = &self.tabuas[j];
+        let ultima = tabua.qx.len() - 1;
+        let mut qx = Vec::with_capacity(tabua.qx.len() * fator);
+        for (i, q) in tabua.qx.iter().enumerate() {
+            let idade = tabua.idade_inicial() + i as u16;
+            for k in 0..fator {
+                if i == ultima && tabua.possui_fechamento_plato() {
+                    qx.push(1.0 - (1.0 - q).powf(1.0 / fator as f64));
+                    continue;
+                }
+                let inicio = self.sobrevivencia_fracionada(j, idade, k as f64 / fator as f64);
+                let fim = self.sobrevivencia_fracionada(j, idade, (k + 1) as f64 / fator as f64);
+                qx.push(if inicio > 0.0 {
+                    1.0 - fim / inicio
+                } else {
+                    1.0
+                });
+            }
+        }
+        return TabuaBase::a_partir_da_idade(
+            alterar_idade_inicial(
+                tabua.idade_inicial(),
+                periodicidade_atual,
+                nova_periodicidade,
+            ),
+            qx,
+            tabua.regra_idade_inferior().clone(),
+        );
     }
 }
 
@@ -107,8 +432,7 @@ impl TabuaInterface for TabuaMDT {
     }
 
     fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
-        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
-        return (0..self.numero_decrementos()).fold(0.0, |acc, j| acc + self.qx_j(x, t, j));
+        return self.qx_decrementos(x, t).iter().sum();
     }
 
     fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
@@ -120,21 +444,27 @@ impl TabuaInterface for TabuaMDT {
             .fold(1.0, |acc, (tabua, x)| acc * tabua.tpx(*x, t));
     }
 
+    // Ao reduzir a periodicidade, p'_j de cada novo período é o produto dos p'_j dos períodos
+    // agrupados, em qualquer hipótese. Ao aumentar, cada período é dividido segundo a
+    // sobrevivência fracionada da hipótese.
     fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
-        let tabuas: Vec<Tabua> = self
-            .tabuas
-            .iter()
-            .map(|tabua| {
-                let qx = alterar_periodicidade(
-                    tabua.qx.clone(),
-                    self.periodicidade.quantidade_periodos_1_ano() as usize,
-                    nova_periodicidade.quantidade_periodos_1_ano() as usize,
-                );
-                return Tabua::new(qx, nova_periodicidade.clone());
+        let periodicidade_atual = self.periodicidade.quantidade_periodos_1_ano() as usize;
+        let periodicidade_nova = nova_periodicidade.quantidade_periodos_1_ano() as usize;
+        let tabuas: Vec<Tabua> = (0..self.tabuas.len())
+            .map(|j| {
+                let tabua = if periodicidade_nova > periodicidade_atual
+                    && self.hipotese != HipoteseMdt::ForcaConstante
+                {
+                    self.subdividir_tabua(j, periodicidade_atual, periodicidade_nova)
+                } else {
+                    self.tabuas[j].alterar_periodicidade(periodicidade_atual, periodicidade_nova)
+                };
+                return Tabua::a_partir_da_tabua_base(tabua, nova_periodicidade.clone());
             })
             .collect();
 
-        return TabuaMDT::new(tabuas);
+        return TabuaMDT::new(tabuas, self.hipotese.clone())
+            .com_nomes_decrementos(self.nomes_decrementos.clone());
     }
 }
 
@@ -160,94 +490,297 @@ mod tests {
     #[test]
     fn tabua_mdt_pode_ser_criada_a_partir_de_outras_tabuas() {
         let tabua = criar_tabua_1dt_1();
-        TabuaMDT::new(vec![tabua.clone(), tabua.clone()]);
+        TabuaMDT::new(
+            vec![tabua.clone(), tabua.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
         assert!(true)
     }
 
     #[test]
     #[should_panic(expected = "Tabua MDT deve possuir pelo menos uma tabua.")]
     fn tabua_mdt_precisa_de_pelo_menos_1_tabua() {
-        TabuaMDT::new(vec![]);
+        TabuaMDT::new(vec![], HipoteseMdt::UddTabuasSimples);
     }
 
     #[test]
-    #[should_panic(expected = "Tabua MDT deve possuir no máximo três tabuas.")]
-    fn tabua_mdt_aceita_no_maximo_3_tabuas() {
+    fn tabua_mdt_aceita_mais_que_3_tabuas() {
         let tabua = criar_tabua_1dt_1();
-        TabuaMDT::new(vec![
-            tabua.clone(),
-            tabua.clone(),
-            tabua.clone(),
-            tabua.clone(),
-        ]);
+        let tabua_mdt = TabuaMDT::new(
+            vec![tabua.clone(), tabua.clone(), tabua.clone(), tabua.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
+
+        approx::assert_abs_diff_eq!(
+            tabua_mdt.qx(&vec![1; 4], 0),
+            1.0 - 0.9_f64.powi(4),
+            epsilon = 1e-12
+        );
     }
 
     #[test]
     #[should_panic(expected = "quantidade de decrementos não pode ser zero")]
     fn converter_mdt_nao_pode_ser_chamada_com_vetor_vazio() {
-        converter_mdt(vec![]);
+        converter_mdt(vec![], &HipoteseMdt::UddTabuasSimples);
+    }
+
+    #[test]
+    fn converter_mdt_retorna_um_qx_por_decremento() {
+        let result = converter_mdt(vec![0.1, 0.2], &HipoteseMdt::UddTabuasSimples);
+
+        assert_eq!(result.len(), 2);
+        approx::assert_abs_diff_eq!(result[1], 0.2 * (1.0 - 0.5 * 0.1));
     }
 
     #[test]
-    #[should_panic(expected = "quantidade de decrementos não pode ser maior que 3")]
-    fn converter_mdt_nao_pode_ser_chamada_com_vetor_com_mais_que_3_elementos() {
-        converter_mdt(vec![0.1, 0.2, 0.3, 0.4]);
+    fn converter_mdt_com_1_elemento_retorna_o_proprio_qx() {
+        for hipotese in [
+            HipoteseMdt::UddTabuasSimples,
+            HipoteseMdt::UddMdt,
+            HipoteseMdt::ForcaConstante,
+        ] {
+            approx::assert_abs_diff_eq!(converter_mdt(vec![0.1], &hipotese)[0], 0.1);
+        }
     }
 
     #[test]
-    fn converter_mdt_preenche_com_zero_quando_recebe_2_elementos() {
-        let result = converter_mdt(vec![0.1, 0.2]);
+    fn calculo_qxj_funciona_com_1_componente() {
+        let qx = 0.4;
+
+        let result = qx2qxj(&vec![qx], 0);
 
-        assert_eq!(result.len(), 3);
-        assert_eq!(result[2], 0.0);
+        assert_eq!(result, qx);
     }
 
     #[test]
-    fn converter_mdt_preenche_com_zero_quando_recebe_1_elemento() {
-        let result = converter_mdt(vec![0.1]);
+    fn calculo_qxj_funciona_com_2_componentes() {
+        let qx1 = 0.4;
+        let qx2 = 0.8;
+        let result = qx2qxj(&vec![qx1, qx2], 0);
 
-        assert_eq!(result.len(), 3);
-        assert_eq!(result[1], 0.0);
-        assert_eq!(result[2], 0.0);
+        approx::assert_abs_diff_eq!(result, qx1 * (1.0 - 0.5 * qx2));
     }
 
     #[test]
-    fn calculo_qxj_funciona_com_1_componente() {
-        let qx = &0.4;
+    fn calculo_qxj_funciona_com_3_componentes() {
+        let qx1 = 0.4;
+        let qx2 = 0.8;
+        let qx3 = 0.2;
 
-        let result = qx2qxj(qx, &0.0, &0.0);
+        let result = qx2qxj(&vec![qx1, qx2, qx3], 0);
 
-        assert_eq!(result, *qx);
+        approx::assert_abs_diff_eq!(
+            result,
+            qx1 * (1.0 - 0.5 * (qx2 + qx3) + 1.0 / 3.0 * (qx2 * qx3)),
+            epsilon = 1e-15
+        );
     }
 
     #[test]
-    fn calculo_qxj_funciona_com_2_componentes() {
-        let qx1 = &0.4;
-        let qx2 = &0.8;
-        let result = qx2qxj(qx1, qx2, &0.0);
+    fn soma_dos_qxj_eh_o_qx_total_em_todas_as_hipoteses() {
+        let qx = vec![0.1, 0.3, 0.05, 0.2, 0.6];
+        let q_total = 1.0 - qx.iter().fold(1.0, |acc, q| acc * (1.0 - q));
+
+        for hipotese in [
+            HipoteseMdt::UddTabuasSimples,
+            HipoteseMdt::UddMdt,
+            HipoteseMdt::ForcaConstante,
+        ] {
+            let soma: f64 = converter_mdt(qx.clone(), &hipotese).iter().sum();
+            approx::assert_abs_diff_eq!(soma, q_total, epsilon = 1e-12);
+        }
+    }
 
-        assert_eq!(result, qx1 * (1.0 - 0.5 * qx2));
+    #[test]
+    fn udd_na_mdt_reparte_pelo_log_da_sobrevivencia() {
+        let result = converter_mdt(vec![0.2, 0.5], &HipoteseMdt::UddMdt);
+
+        let ln_p_total = 0.8_f64.ln() + 0.5_f64.ln();
+        approx::assert_abs_diff_eq!(result[0], 0.6 * 0.8_f64.ln() / ln_p_total, epsilon = 1e-12);
     }
 
     #[test]
-    fn calculo_qxj_funciona_com_3_componentes() {
-        let qx1 = &0.4;
-        let qx2 = &0.8;
-        let qx3 = &0.2;
+    fn qx_igual_a_1_concentra_o_qx_total() {
+        let result = converter_mdt(vec![0.2, 1.0], &HipoteseMdt::ForcaConstante);
+
+        assert_eq!(result, vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn inverter_mdt_desfaz_converter_mdt() {
+        let qx = vec![0.1, 0.3, 0.05, 0.6];
+
+        for hipotese in [
+            HipoteseMdt::UddTabuasSimples,
+            HipoteseMdt::UddMdt,
+            HipoteseMdt::ForcaConstante,
+        ] {
+            let dependentes = converter_mdt(qx.clone(), &hipotese);
+            let independentes = inverter_mdt(dependentes, &hipotese).unwrap();
+            for (a, b) in independentes.iter().zip(qx.iter()) {
+                approx::assert_abs_diff_eq!(a, b, epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn inverter_mdt_sem_solucao_retorna_none() {
+        assert_eq!(inverter_mdt(vec![0.7, 0.6], &HipoteseMdt::UddMdt), None);
+        assert_eq!(
+            inverter_mdt(vec![0.5, 0.4, 0.2], &HipoteseMdt::UddTabuasSimples),
+            None
+        );
+    }
 
-        let result = qx2qxj(qx1, qx2, qx3);
+    #[test]
+    fn inverter_mdt_aceita_saida_certa_com_divisao_convencional() {
+        for hipotese in [HipoteseMdt::UddMdt, HipoteseMdt::ForcaConstante] {
+            let independentes = inverter_mdt(vec![0.6, 0.4, 0.0], &hipotese).unwrap();
+
+            assert_eq!(independentes, vec![1.0, 1.0, 0.0]);
+            assert_eq!(converter_mdt(independentes, &hipotese), vec![0.5, 0.5, 0.0]);
+        }
+    }
 
+    #[test]
+    fn tabua_mdt_a_partir_de_qx_dependentes_reproduz_as_taxas() {
+        let resultado = TabuaMDT::a_partir_de_qx_dependentes(
+            60,
+            vec![vec![0.02, 0.03, 0.7], vec![0.1, 0.12, 0.5]],
+            Periodicidade::Anual,
+            HipoteseMdt::UddTabuasSimples,
+        );
+
+        assert_eq!(resultado.idades_sem_solucao, vec![62]);
+        let tabua = resultado.tabua;
+        approx::assert_abs_diff_eq!(tabua.qx_j(&vec![61, 61], 0, 0), 0.03, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(tabua.qx_j(&vec![61, 61], 0, 1), 0.12, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(tabua.qx(&vec![60, 60], 0), 0.12, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn decrementos_podem_ser_consultados_pelo_nome() {
+        let tabua_mdt = TabuaMDT::new(
+            vec![criar_tabua_1dt_1(), criar_tabua_1dt_2()],
+            HipoteseMdt::UddTabuasSimples,
+        )
+        .com_nomes_decrementos(vec!["morte".to_string(), "cancelamento".to_string()]);
+        let x = vec![1, 1];
+
+        assert_eq!(tabua_mdt.indice_decremento("cancelamento"), 1);
         assert_eq!(
-            result,
-            qx1 * (1.0 - 0.5 * (qx2 + qx3) + 1.0 / 3.0 * (qx2 * qx3))
+            tabua_mdt.qx_decremento(&x, 1, "cancelamento"),
+            tabua_mdt.qx_j(&x, 1, 1)
+        );
+        approx::assert_abs_diff_eq!(
+            tabua_mdt.t_qx_decremento(&x, 1, "morte"),
+            0.9 * 0.8 * 0.5 * (1.0 - 0.5 * 0.4)
+        );
+        assert_eq!(
+            tabua_mdt
+                .alterar_periodicidade(Periodicidade::Mensal)
+                .nomes_decrementos(),
+            &vec!["morte".to_string(), "cancelamento".to_string()]
         );
     }
 
+    #[test]
+    fn qx_acumulados_somam_a_probabilidade_de_saida() {
+        let tabua_mdt = TabuaMDT::new(
+            vec![criar_tabua_1dt_1(), criar_tabua_1dt_2()],
+            HipoteseMdt::UddTabuasSimples,
+        );
+        let x = vec![1, 1];
+
+        let acumulado = tabua_mdt.qx_acumulado_j(&x, 3, 0) + tabua_mdt.qx_acumulado_j(&x, 3, 1);
+
+        approx::assert_abs_diff_eq!(acumulado, 1.0 - tabua_mdt.tpx(&x, 3), epsilon = 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "Decremento desconhecido: invalidez.")]
+    fn decremento_desconhecido_da_erro() {
+        let tabua_mdt = TabuaMDT::new(vec![criar_tabua_1dt_1()], HipoteseMdt::UddTabuasSimples)
+            .com_nomes_decrementos(vec!["morte".to_string()]);
+        tabua_mdt.qx_decremento(&vec![1], 0, "invalidez");
+    }
+
+    #[test]
+    #[should_panic(expected = "j deve ser menor que o número de decrementos")]
+    fn qx_j_da_erro_quando_j_nao_existe() {
+        let tabua_mdt = TabuaMDT::new(vec![criar_tabua_1dt_1()], HipoteseMdt::UddTabuasSimples);
+        tabua_mdt.qx_j(&vec![1], 0, 1);
+    }
+
+    #[test]
+    fn hipotese_altera_a_reparticao_entre_as_causas_mas_nao_o_tpx() {
+        let morte = Tabua::new(vec![0.3, 0.6], Periodicidade::Anual);
+        let saida = Tabua::new(vec![0.2, 0.5], Periodicidade::Anual);
+        let udd_simples = TabuaMDT::new(
+            vec![morte.clone(), saida.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
+        let forca_constante = TabuaMDT::new(vec![morte, saida], HipoteseMdt::ForcaConstante);
+        let x = vec![0, 0];
+
+        approx::assert_abs_diff_eq!(udd_simples.qx_j(&x, 1, 0), 0.6 * 0.75);
+        approx::assert_abs_diff_eq!(
+            forca_constante.qx_j(&x, 1, 0),
+            0.8 * 0.4_f64.ln() / 0.2_f64.ln(),
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(udd_simples.qx(&x, 1), forca_constante.qx(&x, 1));
+        approx::assert_abs_diff_eq!(udd_simples.tpx(&x, 2), forca_constante.tpx(&x, 2));
+    }
+
+    #[test]
+    fn alterar_periodicidade_divide_o_periodo_segundo_a_hipotese() {
+        let tabuas = vec![
+            Tabua::new(vec![0.1, 0.3, 1.0], Periodicidade::Anual),
+            Tabua::new(vec![0.2, 0.4, 1.0], Periodicidade::Anual),
+        ];
+        let semestral = |hipotese| {
+            TabuaMDT::new(tabuas.clone(), hipotese).alterar_periodicidade(Periodicidade::Semestral)
+        };
+        let udd_simples = semestral(HipoteseMdt::UddTabuasSimples);
+        let udd_mdt = semestral(HipoteseMdt::UddMdt);
+        let forca_constante = semestral(HipoteseMdt::ForcaConstante);
+        let x = vec![0, 0];
+
+        // Primeiro semestre: p'_j = 1 - q'_j / 2, (1 - q^(τ) / 2)^(q^(j) / q^(τ)) e p'_j^(1/2).
+        approx::assert_abs_diff_eq!(udd_simples.tpx(&x, 1), 0.95 * 0.9, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(udd_mdt.tpx(&x, 1), 1.0 - 0.5 * 0.28, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(forca_constante.tpx(&x, 1), 0.72_f64.sqrt(), epsilon = 1e-12);
+
+        let q_dependente = 0.28 * 0.9_f64.ln() / 0.72_f64.ln();
+        approx::assert_abs_diff_eq!(
+            udd_simples.qx_j(&x, 0, 0),
+            0.05 * (1.0 - 0.1 / 2.0),
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(udd_mdt.qx_j(&x, 0, 0), 0.5 * q_dependente, epsilon = 1e-12);
+        approx::assert_abs_diff_eq!(
+            forca_constante.qx_j(&x, 0, 0),
+            (1.0 - 0.72_f64.sqrt()) * 0.9_f64.ln() / 0.72_f64.ln(),
+            epsilon = 1e-12
+        );
+
+        // Nos períodos inteiros da tábua original as hipóteses coincidem.
+        for tabua in [&udd_simples, &udd_mdt, &forca_constante] {
+            approx::assert_abs_diff_eq!(tabua.tpx(&x, 2), 0.72, epsilon = 1e-12);
+            approx::assert_abs_diff_eq!(tabua.tpx(&x, 4), 0.72 * 0.42, epsilon = 1e-12);
+        }
+        assert_eq!(udd_mdt.hipotese(), &HipoteseMdt::UddMdt);
+    }
+
     #[test]
     fn tpx_eh_produto_do_tpx_de_cada_tabua() {
         let tabua1 = criar_tabua_1dt_1();
         let tabua2 = criar_tabua_1dt_2();
-        let tabua_mdt = TabuaMDT::new(vec![tabua1.clone(), tabua2.clone()]);
+        let tabua_mdt = TabuaMDT::new(
+            vec![tabua1.clone(), tabua2.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
 
         let x = vec![2, 1];
         let t = 2;
@@ -264,7 +797,7 @@ mod tests {
     #[should_panic(expected = "O vetor de idades é incompatível")]
     fn tpx_falha_quando_x_nao_tem_tamanho_correto() {
         let tabua = criar_tabua_1dt_1();
-        let tabua_mdt = TabuaMDT::new(vec![tabua]);
+        let tabua_mdt = TabuaMDT::new(vec![tabua], HipoteseMdt::UddTabuasSimples);
 
         let x = vec![2, 1, 3];
         let t = 2;
@@ -276,7 +809,7 @@ mod tests {
     fn qx_retorna_a_soma_de_qxj() {
         let tabua1 = criar_tabua_1dt_1();
         let tabua2 = criar_tabua_1dt_2();
-        let tabua_mdt = TabuaMDT::new(vec![tabua1, tabua2]);
+        let tabua_mdt = TabuaMDT::new(vec![tabua1, tabua2], HipoteseMdt::UddTabuasSimples);
 
         let x = vec![2, 1];
         let t = 2;
@@ -292,7 +825,10 @@ mod tests {
     fn tempo_futuro_maximo_retorna_o_menor_dos_tempos_futuros_de_cada_tabua() {
         let tabua1 = criar_tabua_1dt_1();
         let tabua2 = criar_tabua_1dt_plato();
-        let tabua_mdt = TabuaMDT::new(vec![tabua1.clone(), tabua2.clone()]);
+        let tabua_mdt = TabuaMDT::new(
+            vec![tabua1.clone(), tabua2.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
 
         let x = vec![2, 1];
         let result = tabua_mdt.tempo_futuro_maximo(&x);
@@ -307,7 +843,7 @@ mod tests {
     #[should_panic(expected = "O vetor de idades é incompatível")]
     fn tempo_futuro_max_falha_quando_x_nao_tem_tamanho_correto() {
         let tabua = criar_tabua_1dt_1();
-        let tabua_mdt = TabuaMDT::new(vec![tabua]);
+        let tabua_mdt = TabuaMDT::new(vec![tabua], HipoteseMdt::UddTabuasSimples);
 
         let x = vec![2, 1, 3];
 
@@ -318,7 +854,10 @@ mod tests {
     fn alterar_periodicidade_muda_periodicidade_da_tabua() {
         let tabua1 = criar_tabua_1dt_1();
         let tabua2 = criar_tabua_1dt_2();
-        let tabua_mdt = TabuaMDT::new(vec![tabua1.clone(), tabua2.clone()]);
+        let tabua_mdt = TabuaMDT::new(
+            vec![tabua1.clone(), tabua2.clone()],
+            HipoteseMdt::UddTabuasSimples,
+        );
 
         assert_eq!(tabua_mdt.periodicidade(), &Periodicidade::Mensal);
 
@@ -326,4 +865,44 @@ mod tests {
 
         assert_eq!(tabua_alterada.periodicidade(), &Periodicidade::Anual);
     }
+
+    #[test]
+    fn tabuas_com_idade_inicial_sao_combinadas_pela_idade() {
+        let morte = Tabua::a_partir_da_idade(
+            2,
+            vec![0.2, 0.4, 0.7, 1.0],
+            Periodicidade::Mensal,
+            crate::RegraIdadeInferior::Erro,
+        );
+        let tabua_mdt = TabuaMDT::new(
+            vec![morte, criar_tabua_1dt_2()],
+            HipoteseMdt::UddTabuasSimples,
+        );
+
+        let qx = tabua_mdt.qx(&vec![3, 3], 0);
+
+        approx::assert_abs_diff_eq!(qx, 0.4 * (1.0 - 0.5 * 0.7) + 0.7 * (1.0 - 0.5 * 0.4));
+        assert_eq!(
+            tabua_mdt
+                .alterar_periodicidade(Periodicidade::Mensal)
+                .qx(&vec![3, 3], 0),
+            qx
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "inferior à idade inicial")]
+    fn idades_abaixo_da_idade_inicial_dao_erro() {
+        let morte = Tabua::a_partir_da_idade(
+            2,
+            vec![0.2, 0.4, 0.7, 1.0],
+            Periodicidade::Mensal,
+            crate::RegraIdadeInferior::Erro,
+        );
+        TabuaMDT::new(
+            vec![morte, criar_tabua_1dt_2()],
+            HipoteseMdt::UddTabuasSimples,
+        )
+        .qx(&vec![1, 1], 0);
+    }
 }
@@ -0,0 +1,204 @@
+use crate::interface::{validar_idades_tabuas, TabuaInterface};
+use crate::tabua::{extrair_tabua_base_e_periodicidade, normalizar_pesos};
+use crate::tabua_base::TabuaBase;
+use crate::Periodicidade;
+use crate::Tabua;
+use infinitable::Infinitable;
+
+// Os pesos representam a composição da população na idade de entrada. Conforme as
+// subpopulações morrem em ritmos diferentes, a composição é atualizada pela sobrevivência
+// de cada uma até o tempo t.
+#[derive(Debug, Clone)]
+pub struct TabuaMistura {
+    tabuas: Vec<TabuaBase>,
+    pesos: Vec<f64>,
+    periodicidade: Periodicidade,
+}
+
+impl TabuaMistura {
+    pub fn new(tabuas: Vec<Tabua>, pesos: Vec<f64>) -> Self {
+        if tabuas.is_empty() {
+            panic!("Tabua de mistura deve possuir pelo menos uma tabua.");
+        }
+        let pesos = normalizar_pesos(&pesos, tabuas.len());
+        let (tabuas, periodicidade) = extrair_tabua_base_e_periodicidade(tabuas);
+
+        return TabuaMistura {
+            tabuas,
+            pesos,
+            periodicidade,
+        };
+    }
+
+    pub fn pesos(&self) -> &Vec<f64> {
+        return &self.pesos;
+    }
+
+    pub fn pesos_condicionais(&self, x: &Vec<u16>, t: u16) -> Vec<f64> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        let sobrevivencia: Vec<f64> = self
+            .tabuas
+            .iter()
+            .zip(self.pesos.iter())
+            .map(|(tabua, peso)| peso * tabua.tpx(x[0], t))
+            .collect();
+        let total: f64 = sobrevivencia.iter().sum();
+        if total == 0.0 {
+            return vec![0.0; self.tabuas.len()];
+        }
+        return sobrevivencia.iter().map(|s| s / total).collect();
+    }
+}
+
+impl TabuaInterface for TabuaMistura {
+    fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    fn numero_decrementos(&self) -> usize {
+        return 1;
+    }
+
+    fn numero_vidas(&self) -> usize {
+        return 1;
+    }
+
+    fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return self
+            .tabuas
+            .iter()
+            .zip(self.pesos.iter())
+            .filter(|(_, peso)| **peso > 0.0)
+            .map(|(tabua, _)| tabua.tempo_futuro_maximo(x[0]))
+            .max()
+            .expect("Tabua de mistura deveria possuir uma ou mais tábuas com peso positivo.");
+    }
+
+    fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        let pesos = self.pesos_condicionais(x, t);
+        if pesos.iter().all(|peso| *peso == 0.0) {
+            return 1.0;
+        }
+        return self
+            .tabuas
+            .iter()
+            .zip(pesos.iter())
+            .fold(0.0, |acc, (tabua, peso)| acc + peso * tabua.qx(x[0], t));
+    }
+
+    fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
+        validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
+        return self
+            .tabuas
+            .iter()
+            .zip(self.pesos.iter())
+            .fold(0.0, |acc, (tabua, peso)| acc + peso * tabua.tpx(x[0], t));
+    }
+
+    fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
+        let tabuas: Vec<Tabua> = self
+            .tabuas
+            .iter()
+            .map(|tabua| {
+                let tabua = tabua.alterar_periodicidade(
+                    self.periodicidade.quantidade_periodos_1_ano() as usize,
+                    nova_periodicidade.quantidade_periodos_1_ano() as usize,
+                );
+                return Tabua::a_partir_da_tabua_base(tabua, nova_periodicidade.clone());
+            })
+            .collect();
+
+        return TabuaMistura::new(tabuas, self.pesos.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    fn criar_tabua_nao_fumante() -> Tabua {
+        Tabua::new(vec![0.01, 0.02, 0.05, 0.2, 1.0], Periodicidade::Anual)
+    }
+
+    fn criar_tabua_fumante() -> Tabua {
+        Tabua::new(vec![0.05, 0.1, 0.3, 0.6, 0.9], Periodicidade::Anual)
+    }
+
+    fn criar_tabua_mistura() -> TabuaMistura {
+        TabuaMistura::new(
+            vec![criar_tabua_nao_fumante(), criar_tabua_fumante()],
+            vec![0.7, 0.3],
+        )
+    }
+
+    #[test]
+    fn tpx_eh_soma_ponderada_das_sobrevivencias() {
+        let tabua = criar_tabua_mistura();
+        let nao_fumante = criar_tabua_nao_fumante();
+        let fumante = criar_tabua_fumante();
+
+        for t in 0..6 {
+            approx::assert_abs_diff_eq!(
+                tabua.tpx(&vec![1], t),
+                0.7 * nao_fumante.tpx(&vec![1], t) + 0.3 * fumante.tpx(&vec![1], t)
+            );
+        }
+    }
+
+    #[test]
+    fn qx_eh_consistente_com_tpx() {
+        let tabua = criar_tabua_mistura();
+        let x = vec![0];
+
+        for t in 0..8 {
+            approx::assert_abs_diff_eq!(
+                tabua.tpx(&x, t + 1),
+                tabua.tpx(&x, t) * (1.0 - tabua.qx(&x, t)),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn pesos_condicionais_migram_para_subpopulacao_mais_saudavel() {
+        let tabua = criar_tabua_mistura();
+
+        let pesos_0 = tabua.pesos_condicionais(&vec![0], 0);
+        let pesos_3 = tabua.pesos_condicionais(&vec![0], 3);
+
+        approx::assert_abs_diff_eq!(pesos_0[0], 0.7);
+        assert!(pesos_3[0] > pesos_0[0]);
+        approx::assert_abs_diff_eq!(pesos_3.iter().sum::<f64>(), 1.0);
+    }
+
+    #[test]
+    fn tempo_futuro_maximo_eh_o_maior_entre_as_tabuas() {
+        let tabua = criar_tabua_mistura();
+
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Infinitable::Infinity);
+
+        let tabua = TabuaMistura::new(vec![criar_tabua_nao_fumante()], vec![1.0]);
+
+        assert_eq!(tabua.tempo_futuro_maximo(&vec![0]), Infinitable::Finite(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "O vetor de idades é incompatível")]
+    fn qx_falha_quando_x_nao_tem_tamanho_correto() {
+        let tabua = criar_tabua_mistura();
+
+        tabua.qx(&vec![0, 1], 0);
+    }
+
+    #[test]
+    fn alterar_periodicidade_muda_periodicidade_da_tabua() {
+        let tabua = criar_tabua_mistura();
+
+        let tabua_alterada = tabua.alterar_periodicidade(Periodicidade::Semestral);
+
+        assert_eq!(tabua_alterada.periodicidade(), &Periodicidade::Semestral);
+        assert_eq!(tabua_alterada.pesos(), tabua.pesos());
+    }
+}
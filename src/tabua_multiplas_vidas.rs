@@ -1,4 +1,3 @@
-use crate::alterar::alterar_periodicidade;
 use crate::interface::{validar_idades_tabuas, TabuaInterface};
 use crate::tabua::extrair_tabua_base_e_periodicidade;
 use crate::tabua_base::TabuaBase;
@@ -6,10 +5,14 @@ use crate::Periodicidade;
 use crate::Tabua;
 use infinitable::Infinitable;
 
-#[derive(Clone)]
+// First: todas as vidas vivas; Last: pelo menos uma viva. PeloMenos(k) e Exatamente(k) contam
+// as vidas vivas, supondo vidas independentes.
+#[derive(Clone, Debug, PartialEq)]
 pub enum StatusVidasConjuntas {
     First,
     Last,
+    PeloMenos(usize),
+    Exatamente(usize),
 }
 
 pub struct TabuaMultiplasVidas {
@@ -18,9 +21,54 @@ pub struct TabuaMultiplasVidas {
     periodicidade: Periodicidade,
 }
 
+fn combinacoes(n: usize, k: usize) -> f64 {
+    return (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64);
+}
+
+// S_j = soma, sobre os subconjuntos de j vidas, do produto das probabilidades de sobrevivência.
+fn somas_simetricas(probabilidades: &Vec<f64>) -> Vec<f64> {
+    let mut somas = vec![0.0; probabilidades.len() + 1];
+    somas[0] = 1.0;
+    for (i, p) in probabilidades.iter().enumerate() {
+        for j in (1..=i + 1).rev() {
+            somas[j] += p * somas[j - 1];
+        }
+    }
+    return somas;
+}
+
+// Inclusão-exclusão: P[exatamente k] = sum_{j>=k} (-1)^(j-k) C(j, k) S_j e
+// P[pelo menos k] = sum_{j>=k} (-1)^(j-k) C(j-1, k-1) S_j.
+fn probabilidade_exatamente(k: usize, probabilidades: &Vec<f64>) -> f64 {
+    let somas = somas_simetricas(probabilidades);
+    return (k..somas.len()).fold(0.0, |acc, j| {
+        let sinal = if (j - k).is_multiple_of(2) { 1.0 } else { -1.0 };
+        acc + sinal * combinacoes(j, k) * somas[j]
+    });
+}
+
+fn probabilidade_pelo_menos(k: usize, probabilidades: &Vec<f64>) -> f64 {
+    let somas = somas_simetricas(probabilidades);
+    return (k..somas.len()).fold(0.0, |acc, j| {
+        let sinal = if (j - k).is_multiple_of(2) { 1.0 } else { -1.0 };
+        acc + sinal * combinacoes(j - 1, k - 1) * somas[j]
+    });
+}
+
 impl TabuaMultiplasVidas {
     pub fn new(tabuas: Vec<Tabua>, status_vidas_conjuntas: StatusVidasConjuntas) -> Self {
         let (tabuas, periodicidade) = extrair_tabua_base_e_periodicidade(tabuas);
+        match status_vidas_conjuntas {
+            StatusVidasConjuntas::PeloMenos(k) | StatusVidasConjuntas::Exatamente(k)
+                if k == 0 || k > tabuas.len() =>
+            {
+                panic!(
+                    "A quantidade de vidas do status deve estar entre 1 e {}, obtido = {k}.",
+                    tabuas.len()
+                );
+            }
+            _ => {}
+        }
 
         return TabuaMultiplasVidas {
             tabuas,
@@ -28,6 +76,33 @@ impl TabuaMultiplasVidas {
             periodicidade,
         };
     }
+
+    pub(crate) fn obter_tabuas_base(&self) -> &Vec<TabuaBase> {
+        return &self.tabuas;
+    }
+
+    pub fn status_vidas_conjuntas(&self) -> &StatusVidasConjuntas {
+        return &self.status_vidas_conjuntas;
+    }
+
+    fn sobrevivencias(&self, x: &Vec<u16>, t: u16) -> Vec<f64> {
+        return self
+            .tabuas
+            .iter()
+            .zip(x.iter())
+            .map(|(tabua, idade)| tabua.tpx(*idade, t))
+            .collect();
+    }
+
+    // Quantidade mínima de vidas vivas para o status, quando ele é do tipo "pelo menos".
+    fn minimo_vidas(&self) -> Option<usize> {
+        return match self.status_vidas_conjuntas {
+            StatusVidasConjuntas::First => Some(self.tabuas.len()),
+            StatusVidasConjuntas::Last => Some(1),
+            StatusVidasConjuntas::PeloMenos(k) => Some(k),
+            StatusVidasConjuntas::Exatamente(_) => None,
+        };
+    }
 }
 
 impl TabuaInterface for TabuaMultiplasVidas {
@@ -43,53 +118,72 @@ impl TabuaInterface for TabuaMultiplasVidas {
         return self.tabuas.len();
     }
 
+    // O status deixa de ser possível quando morre a k-ésima vida de maior tempo futuro máximo.
     fn tempo_futuro_maximo(&self, x: &Vec<u16>) -> Infinitable<u16> {
         validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
 
-        let tempos = self
+        let mut tempos: Vec<Infinitable<u16>> = self
             .tabuas
             .iter()
             .zip(x.iter())
-            .map(|(tabua, idade)| tabua.tempo_futuro_maximo(*idade));
+            .map(|(tabua, idade)| tabua.tempo_futuro_maximo(*idade))
+            .collect();
+        tempos.sort_by(|a, b| b.cmp(a));
 
-        let result = match self.status_vidas_conjuntas {
-            StatusVidasConjuntas::First => tempos.min(),
-            StatusVidasConjuntas::Last => tempos.max(),
+        let k = match self.status_vidas_conjuntas {
+            StatusVidasConjuntas::Exatamente(k) => k,
+            _ => self.minimo_vidas().unwrap(),
         };
-
-        return result.expect("TabuaMultiplasVidas deveria possuir uma ou mais tábuas.");
+        return tempos[k - 1];
     }
 
+    // Para "pelo menos k", qx = 1 - tpx(t + 1) / tpx(t). Para "exatamente k", qx é a
+    // probabilidade de alguma das k vidas vivas em t morrer no período.
     fn qx(&self, x: &Vec<u16>, t: u16) -> f64 {
         validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
 
-        let iter = self.tabuas.iter().zip(x.iter());
+        if self.status_vidas_conjuntas == StatusVidasConjuntas::First {
+            let iter = self.tabuas.iter().zip(x.iter());
+            let iter_fold = iter.fold(1.0, |acc, (tabua, idade)| acc * (1.0 - tabua.qx(*idade, t)));
+            return 1.0 - iter_fold;
+        }
 
-        match self.status_vidas_conjuntas {
-            StatusVidasConjuntas::First => {
-                let iter_fold =
-                    iter.fold(1.0, |acc, (tabua, idade)| acc * (1.0 - tabua.qx(*idade, t)));
-                return 1.0 - iter_fold;
-            }
-            StatusVidasConjuntas::Last => {
-                return iter.fold(1.0, |acc, (tabua, idade)| acc * tabua.qx(*idade, t));
-            }
+        let tpx = self.tpx(x, t);
+        if tpx == 0.0 {
+            return 1.0;
         }
+        let permanencia = match self.status_vidas_conjuntas {
+            StatusVidasConjuntas::Exatamente(k) => {
+                // Coeficiente de z^k em prod((1 - tpx_i) + z * t+1px_i).
+                let mut coeficientes = vec![0.0; self.tabuas.len() + 1];
+                coeficientes[0] = 1.0;
+                let atuais = self.sobrevivencias(x, t);
+                let seguintes = self.sobrevivencias(x, t + 1);
+                for (i, (atual, seguinte)) in atuais.iter().zip(seguintes.iter()).enumerate() {
+                    for j in (0..=i + 1).rev() {
+                        let anterior = if j > 0 { coeficientes[j - 1] } else { 0.0 };
+                        coeficientes[j] = coeficientes[j] * (1.0 - atual) + anterior * seguinte;
+                    }
+                }
+                coeficientes[k]
+            }
+            _ => self.tpx(x, t + 1),
+        };
+        return (1.0 - permanencia / tpx).clamp(0.0, 1.0);
     }
 
+    // Probabilidade de o status valer no tempo t. Para "exatamente k" ela não é necessariamente
+    // decrescente em t, pois o status pode passar a valer com a morte de outras vidas.
     fn tpx(&self, x: &Vec<u16>, t: u16) -> f64 {
         validar_idades_tabuas(x, self.numero_decrementos(), self.numero_vidas());
 
-        let iter = self.tabuas.iter().zip(x.iter());
-
-        match self.status_vidas_conjuntas {
-            StatusVidasConjuntas::First => {
-                return iter.fold(1.0, |acc, (tabua, idade)| acc * tabua.tpx(*idade, t));
-            }
-            StatusVidasConjuntas::Last => {
-                return (1..=t).fold(1.0, |acc, t| acc * 1.0 - self.qx(x, t - 1));
-            }
-        }
+        let sobrevivencias = self.sobrevivencias(x, t);
+        return match self.status_vidas_conjuntas {
+            StatusVidasConjuntas::First => sobrevivencias.iter().product(),
+            StatusVidasConjuntas::Exatamente(k) => probabilidade_exatamente(k, &sobrevivencias),
+            _ => probabilidade_pelo_menos(self.minimo_vidas().unwrap(), &sobrevivencias)
+                .clamp(0.0, 1.0),
+        };
     }
 
     fn alterar_periodicidade(&self, nova_periodicidade: Periodicidade) -> Self {
@@ -97,12 +191,11 @@ impl TabuaInterface for TabuaMultiplasVidas {
             .tabuas
             .iter()
             .map(|tabua| {
-                let qx = alterar_periodicidade(
-                    tabua.qx.clone(),
+                let tabua = tabua.alterar_periodicidade(
                     self.periodicidade.quantidade_periodos_1_ano() as usize,
                     nova_periodicidade.quantidade_periodos_1_ano() as usize,
                 );
-                return Tabua::new(qx, nova_periodicidade.clone());
+                return Tabua::a_partir_da_tabua_base(tabua, nova_periodicidade.clone());
             })
             .collect();
 
@@ -137,41 +230,95 @@ mod tests {
     }
 
     #[test]
-    fn tpx_retorna_produto_acumulado_de_um_menos_qx_quando_status_eh_last() {
-        let tabua = criar_tabua_1_vida_1();
+    fn tpx_eh_a_probabilidade_de_pelo_menos_uma_vida_viva_quando_status_eh_last() {
+        let tabua1 = criar_tabua_1_vida_1();
+        let tabua2 = criar_tabua_1_vida_2();
         let tabua_multiplas_vidas = TabuaMultiplasVidas::new(
-            vec![tabua.clone(), tabua.clone()],
+            vec![tabua1.clone(), tabua2.clone()],
             StatusVidasConjuntas::Last,
         );
 
-        let x = vec![0, 0];
-        let t = 2;
+        let x = vec![0, 1];
+        let t = 3;
 
         let result = tabua_multiplas_vidas.tpx(&x, t);
 
-        approx::assert_relative_eq!(
-            result,
-            (1.0 - tabua_multiplas_vidas.qx(&x, 0)) * (1.0 - tabua_multiplas_vidas.qx(&x, 1))
-        );
+        let p1 = tabua1.tpx(&vec![x[0]], t);
+        let p2 = tabua2.tpx(&vec![x[1]], t);
+        approx::assert_relative_eq!(result, p1 + p2 - p1 * p2, epsilon = 1e-15);
     }
 
     #[test]
-    fn qx_retorna_o_produto_de_qx_de_cada_tabua_quando_status_eh_last() {
-        let tabua = criar_tabua_1_vida_1();
-        let tabua_multiplas_vidas = TabuaMultiplasVidas::new(
-            vec![tabua.clone(), tabua.clone()],
-            StatusVidasConjuntas::Last,
-        );
+    fn qx_eh_coerente_com_tpx_quando_status_eh_last() {
+        let tabua1 = criar_tabua_1_vida_1();
+        let tabua2 = criar_tabua_1_vida_2();
+        let tabua_multiplas_vidas =
+            TabuaMultiplasVidas::new(vec![tabua1, tabua2], StatusVidasConjuntas::Last);
 
-        let x = vec![0, 0];
+        let x = vec![0, 1];
         let t = 2;
 
         let result = tabua_multiplas_vidas.qx(&x, t);
 
         approx::assert_relative_eq!(
             result,
-            tabua.qx(&vec![x[0]], t) * tabua.qx(&vec![x[1]], t),
-            epsilon = f64::EPSILON
+            1.0 - tabua_multiplas_vidas.tpx(&x, t + 1) / tabua_multiplas_vidas.tpx(&x, t),
+            epsilon = 1e-15
+        );
+        approx::assert_relative_eq!(
+            tabua_multiplas_vidas.t_qx(&x, t),
+            tabua_multiplas_vidas.tpx(&x, t) - tabua_multiplas_vidas.tpx(&x, t + 1),
+            epsilon = 1e-15
+        );
+    }
+
+    #[test]
+    fn status_k_de_n_por_inclusao_e_exclusao() {
+        let tabua = Tabua::new(vec![0.1, 0.2, 0.3], Periodicidade::Anual);
+        let tabuas = vec![tabua.clone(), tabua.clone(), tabua];
+        let x = vec![0, 1, 2];
+        let p: Vec<f64> = vec![0.9, 0.8, 0.7];
+        let exatamente_2 =
+            p[0] * p[1] * (1.0 - p[2]) + p[0] * (1.0 - p[1]) * p[2] + (1.0 - p[0]) * p[1] * p[2];
+        let todas = p[0] * p[1] * p[2];
+
+        let pelo_menos =
+            TabuaMultiplasVidas::new(tabuas.clone(), StatusVidasConjuntas::PeloMenos(2));
+        let exatamente =
+            TabuaMultiplasVidas::new(tabuas.clone(), StatusVidasConjuntas::Exatamente(2));
+        let last = TabuaMultiplasVidas::new(tabuas, StatusVidasConjuntas::Last);
+
+        approx::assert_relative_eq!(exatamente.tpx(&x, 1), exatamente_2, epsilon = 1e-15);
+        approx::assert_relative_eq!(pelo_menos.tpx(&x, 1), exatamente_2 + todas, epsilon = 1e-15);
+        approx::assert_relative_eq!(last.tpx(&x, 1), 1.0 - 0.1 * 0.2 * 0.3, epsilon = 1e-15);
+    }
+
+    #[test]
+    fn qx_do_status_exatamente_k_considera_a_morte_de_alguma_vida_viva() {
+        let tabua1 = Tabua::new(vec![0.1, 0.5], Periodicidade::Anual);
+        let tabua2 = Tabua::new(vec![0.2, 0.4], Periodicidade::Anual);
+        let tabua_multiplas_vidas =
+            TabuaMultiplasVidas::new(vec![tabua1, tabua2], StatusVidasConjuntas::Exatamente(1));
+
+        let x = vec![0, 0];
+
+        let exatamente_1 = 0.9 * 0.2 + 0.1 * 0.8;
+        let permanece = 0.9 * 0.5 * 0.2 + 0.1 * 0.8 * 0.6;
+        approx::assert_relative_eq!(tabua_multiplas_vidas.tpx(&x, 1), exatamente_1);
+        approx::assert_relative_eq!(
+            tabua_multiplas_vidas.qx(&x, 1),
+            1.0 - permanece / exatamente_1,
+            epsilon = 1e-15
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A quantidade de vidas do status deve estar entre 1 e 2")]
+    fn status_k_de_n_exige_k_valido() {
+        let tabua = criar_tabua_1_vida_1();
+        TabuaMultiplasVidas::new(
+            vec![tabua.clone(), tabua],
+            StatusVidasConjuntas::PeloMenos(3),
         );
     }
 
@@ -277,4 +424,31 @@ mod tests {
 
         assert_eq!(tabua_alterada.periodicidade(), &Periodicidade::Anual);
     }
+
+    #[test]
+    fn tabuas_com_idade_inicial_respeitam_o_deslocamento() {
+        let tabua_deslocada = Tabua::a_partir_da_idade(
+            10,
+            vec![0.0, 0.1, 0.5, 0.8, 1.0],
+            Periodicidade::Mensal,
+            crate::RegraIdadeInferior::Erro,
+        );
+        let tabua_multiplas_vidas = TabuaMultiplasVidas::new(
+            vec![tabua_deslocada, criar_tabua_1_vida_2()],
+            StatusVidasConjuntas::First,
+        );
+        let referencia = TabuaMultiplasVidas::new(
+            vec![criar_tabua_1_vida_1(), criar_tabua_1_vida_2()],
+            StatusVidasConjuntas::First,
+        );
+
+        assert_eq!(
+            tabua_multiplas_vidas.qx(&vec![11, 1], 1),
+            referencia.qx(&vec![1, 1], 1)
+        );
+        assert_eq!(
+            tabua_multiplas_vidas.tempo_futuro_maximo(&vec![11, 1]),
+            referencia.tempo_futuro_maximo(&vec![1, 1])
+        );
+    }
 }
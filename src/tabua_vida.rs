@@ -0,0 +1,366 @@
+use crate::interface::TabuaInterface;
+use crate::tabua_base::calcular_lx;
+use crate::Periodicidade;
+use crate::Tabua;
+use crate::TabuaMDT;
+
+// Colunas clássicas de uma tábua de vida, com idades e expectativas em períodos da
+// periodicidade da tábua. Lx e e̊x supõem distribuição uniforme das mortes (UDD) dentro de cada
+// período, de modo que ex = e̊x - 1/2. Quando a tábua termina em platô, a última idade é aberta:
+// ela acumula todas as idades seguintes, com o último qx repetido indefinidamente.
+#[derive(Debug, Clone)]
+pub struct TabuaVida {
+    periodicidade: Periodicidade,
+    raiz: f64,
+    idades: Vec<u16>,
+    lx: Vec<f64>,
+    dx: Vec<f64>,
+    qx: Vec<f64>,
+    px: Vec<f64>,
+    lx_medio: Vec<f64>,
+    tx: Vec<f64>,
+    ex: Vec<f64>,
+    ex_completa: Vec<f64>,
+    mx: Vec<f64>,
+    dx_decrementos: Vec<Vec<f64>>,
+    nomes_decrementos: Vec<String>,
+    possui_idade_aberta: bool,
+}
+
+fn validar_raiz(raiz: f64) {
+    if !raiz.is_finite() || raiz <= 0.0 {
+        panic!("A raiz da tábua de vida deve ser finita e positiva, obtido = {raiz}.");
+    }
+}
+
+impl TabuaVida {
+    pub fn a_partir_da_tabua(tabua: &Tabua, raiz: f64) -> Self {
+        validar_raiz(raiz);
+        let tabua_base = tabua.obter_tabua_base();
+        let idades = (tabua_base.idade_inicial() as usize..tabua_base.idade_limite())
+            .map(|x| x as u16)
+            .collect();
+        let qx_decrementos = tabua_base.qx.iter().map(|q| vec![*q]).collect();
+        return TabuaVida::montar(
+            tabua.periodicidade().clone(),
+            raiz,
+            idades,
+            tabua_base.qx.clone(),
+            qx_decrementos,
+            Vec::new(),
+        );
+    }
+
+    // A mesma idade é usada em todos os decrementos, da maior idade inicial até a última idade
+    // informada na tábua mais longa. O qx total é 1 - p, com p o produto das sobrevivências.
+    pub fn a_partir_da_tabua_mdt(tabua: &TabuaMDT, raiz: f64) -> Self {
+        validar_raiz(raiz);
+        let tabuas = tabua.obter_tabuas_base();
+        let idade_inicial = tabuas.iter().map(|t| t.idade_inicial()).max().unwrap();
+        let idade_limite = tabuas.iter().map(|t| t.idade_limite()).max().unwrap();
+        let n = tabua.numero_decrementos();
+
+        let idades: Vec<u16> = (idade_inicial as usize..idade_limite)
+            .map(|x| x as u16)
+            .collect();
+        let qx = idades
+            .iter()
+            .map(|x| 1.0 - tabua.tpx(&vec![*x; n], 1))
+            .collect();
+        let qx_decrementos = idades
+            .iter()
+            .map(|x| tabua.qx_decrementos(&vec![*x; n], 0))
+            .collect();
+        return TabuaVida::montar(
+            tabua.periodicidade().clone(),
+            raiz,
+            idades,
+            qx,
+            qx_decrementos,
+            tabua.nomes_decrementos().clone(),
+        );
+    }
+
+    fn montar(
+        periodicidade: Periodicidade,
+        raiz: f64,
+        mut idades: Vec<u16>,
+        mut qx: Vec<f64>,
+        mut qx_decrementos: Vec<Vec<f64>>,
+        nomes_decrementos: Vec<String>,
+    ) -> Self {
+        // A tábua termina na primeira idade com qx igual a 1.
+        if let Some(ultima) = qx.iter().position(|q| *q >= 1.0) {
+            idades.truncate(ultima + 1);
+            qx.truncate(ultima + 1);
+            qx_decrementos.truncate(ultima + 1);
+        }
+        let possui_idade_aberta = qx[qx.len() - 1] < 1.0;
+        let tamanho = qx.len();
+
+        let mut lx = calcular_lx(&qx, raiz);
+        lx.resize(tamanho, 0.0);
+        let px: Vec<f64> = qx.iter().map(|q| 1.0 - q).collect();
+
+        let mut dx: Vec<f64> = (0..tamanho).map(|i| lx[i] * qx[i]).collect();
+        let mut lx_medio: Vec<f64> = (0..tamanho).map(|i| lx[i] - 0.5 * dx[i]).collect();
+        let mut dx_decrementos: Vec<Vec<f64>> = (0..tamanho)
+            .map(|i| qx_decrementos[i].iter().map(|q| lx[i] * q).collect())
+            .collect();
+        if possui_idade_aberta {
+            // Soma geométrica dos períodos seguintes com o último qx.
+            let i = tamanho - 1;
+            let q = qx[i];
+            lx_medio[i] = lx[i] * (1.0 - 0.5 * q) / q;
+            dx[i] = lx[i];
+            dx_decrementos[i] = qx_decrementos[i].iter().map(|qj| lx[i] * qj / q).collect();
+        }
+
+        let mut tx = vec![0.0; tamanho];
+        let mut acumulado = 0.0;
+        for i in (0..tamanho).rev() {
+            acumulado += lx_medio[i];
+            tx[i] = acumulado;
+        }
+
+        let ex_completa: Vec<f64> = (0..tamanho)
+            .map(|i| if lx[i] == 0.0 { 0.0 } else { tx[i] / lx[i] })
+            .collect();
+        let ex = ex_completa
+            .iter()
+            .zip(lx.iter())
+            .map(|(e, l)| if *l == 0.0 { 0.0 } else { e - 0.5 })
+            .collect();
+        let mx = (0..tamanho)
+            .map(|i| {
+                if lx_medio[i] == 0.0 {
+                    0.0
+                } else {
+                    dx[i] / lx_medio[i]
+                }
+            })
+            .collect();
+
+        return TabuaVida {
+            periodicidade,
+            raiz,
+            idades,
+            lx,
+            dx,
+            qx,
+            px,
+            lx_medio,
+            tx,
+            ex,
+            ex_completa,
+            mx,
+            dx_decrementos,
+            nomes_decrementos,
+            possui_idade_aberta,
+        };
+    }
+
+    pub fn periodicidade(&self) -> &Periodicidade {
+        return &self.periodicidade;
+    }
+
+    pub fn raiz(&self) -> f64 {
+        return self.raiz;
+    }
+
+    pub fn idades(&self) -> &Vec<u16> {
+        return &self.idades;
+    }
+
+    pub fn lx(&self) -> &Vec<f64> {
+        return &self.lx;
+    }
+
+    pub fn dx(&self) -> &Vec<f64> {
+        return &self.dx;
+    }
+
+    pub fn qx(&self) -> &Vec<f64> {
+        return &self.qx;
+    }
+
+    pub fn px(&self) -> &Vec<f64> {
+        return &self.px;
+    }
+
+    // Lx: tempo vivido pelo grupo entre x e x + 1.
+    pub fn lx_medio(&self) -> &Vec<f64> {
+        return &self.lx_medio;
+    }
+
+    pub fn tx(&self) -> &Vec<f64> {
+        return &self.tx;
+    }
+
+    pub fn ex(&self) -> &Vec<f64> {
+        return &self.ex;
+    }
+
+    pub fn ex_completa(&self) -> &Vec<f64> {
+        return &self.ex_completa;
+    }
+
+    pub fn mx(&self) -> &Vec<f64> {
+        return &self.mx;
+    }
+
+    // d_x^(j) por idade; em tábuas de um único decremento há apenas a coluna de dx.
+    pub fn dx_decrementos(&self) -> &Vec<Vec<f64>> {
+        return &self.dx_decrementos;
+    }
+
+    // Nomes dos decrementos da tábua MDT; vazio em tábuas de um único decremento.
+    pub fn nomes_decrementos(&self) -> &Vec<String> {
+        return &self.nomes_decrementos;
+    }
+
+    pub fn possui_idade_aberta(&self) -> bool {
+        return self.possui_idade_aberta;
+    }
+
+    // Uma linha de cabeçalho e uma linha por idade; a idade aberta recebe o sufixo "+".
+    pub fn exportar(&self, separador: char) -> String {
+        let numero_decrementos = self.dx_decrementos[0].len();
+        let mut cabecalho = [
+            "idade", "lx", "dx", "qx", "px", "Lx", "Tx", "ex", "e̊x", "mx",
+        ]
+        .iter()
+        .map(|coluna| coluna.to_string())
+        .collect::<Vec<String>>();
+        if numero_decrementos > 1 {
+            cabecalho.extend(
+                self.nomes_decrementos
+                    .iter()
+                    .map(|nome| format!("dx({nome})")),
+            );
+        }
+
+        let mut linhas = vec![cabecalho.join(&separador.to_string())];
+        for i in 0..self.idades.len() {
+            let mut idade = self.idades[i].to_string();
+            if self.possui_idade_aberta && i == self.idades.len() - 1 {
+                idade.push('+');
+            }
+            let mut campos = vec![idade];
+            campos.extend(
+                [
+                    self.lx[i],
+                    self.dx[i],
+                    self.qx[i],
+                    self.px[i],
+                    self.lx_medio[i],
+                    self.tx[i],
+                    self.ex[i],
+                    self.ex_completa[i],
+                    self.mx[i],
+                ]
+                .iter()
+                .map(|valor| valor.to_string()),
+            );
+            if numero_decrementos > 1 {
+                campos.extend(self.dx_decrementos[i].iter().map(|valor| valor.to_string()));
+            }
+            linhas.push(campos.join(&separador.to_string()));
+        }
+        return linhas.join("\n") + "\n";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx;
+
+    #[test]
+    fn colunas_de_tabua_completa() {
+        let tabua = Tabua::new(vec![0.1, 0.5, 1.0], Periodicidade::Anual);
+
+        let vida = TabuaVida::a_partir_da_tabua(&tabua, 1000.0);
+
+        assert_eq!(vida.lx(), &vec![1000.0, 900.0, 450.0]);
+        assert_eq!(vida.dx(), &vec![100.0, 450.0, 450.0]);
+        assert_eq!(vida.lx_medio(), &vec![950.0, 675.0, 225.0]);
+        assert_eq!(vida.tx(), &vec![1850.0, 900.0, 225.0]);
+        approx::assert_abs_diff_eq!(vida.ex_completa()[0], 1.85);
+        approx::assert_abs_diff_eq!(vida.ex()[0], 1.35);
+        approx::assert_abs_diff_eq!(vida.mx()[1], 450.0 / 675.0);
+        assert!(!vida.possui_idade_aberta());
+    }
+
+    #[test]
+    fn expectativa_curta_coincide_com_soma_dos_tpx() {
+        let tabua = Tabua::a_partir_da_idade(
+            60,
+            vec![0.02, 0.04, 0.08, 0.16, 0.3],
+            Periodicidade::Anual,
+            crate::RegraIdadeInferior::Erro,
+        );
+
+        let vida = TabuaVida::a_partir_da_tabua(&tabua, 1.0);
+
+        let ex: f64 = (1..2000).map(|t| tabua.tpx(&vec![61], t)).sum();
+        assert_eq!(vida.idades()[1], 61);
+        assert!(vida.possui_idade_aberta());
+        approx::assert_relative_eq!(vida.ex()[1], ex, epsilon = 1e-10);
+        approx::assert_relative_eq!(vida.mx()[4], 0.3 / 0.85, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn tabua_mdt_reparte_dx_entre_os_decrementos() {
+        let morte = Tabua::new(vec![0.1, 0.2, 1.0], Periodicidade::Anual);
+        let invalidez = Tabua::new(vec![0.05, 0.05, 0.05], Periodicidade::Anual);
+        let mdt = TabuaMDT::new(vec![morte, invalidez], crate::HipoteseMdt::UddTabuasSimples);
+
+        let vida = TabuaVida::a_partir_da_tabua_mdt(&mdt, 1000.0);
+
+        for i in 0..vida.idades().len() {
+            let soma: f64 = vida.dx_decrementos()[i].iter().sum();
+            approx::assert_relative_eq!(soma, vida.dx()[i], epsilon = 1e-10);
+        }
+        approx::assert_abs_diff_eq!(vida.lx()[1], 1000.0 * 0.9 * 0.95, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(
+            vida.dx_decrementos()[2][1],
+            vida.lx()[2] * 0.05 * 0.5,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn exportar_gera_cabecalho_e_uma_linha_por_idade() {
+        let tabua = Tabua::new(vec![0.5, 0.5], Periodicidade::Anual);
+
+        let texto = TabuaVida::a_partir_da_tabua(&tabua, 100.0).exportar(';');
+        let linhas: Vec<&str> = texto.lines().collect();
+
+        assert_eq!(linhas[0], "idade;lx;dx;qx;px;Lx;Tx;ex;e̊x;mx");
+        assert_eq!(linhas.len(), 3);
+        assert!(linhas[2].starts_with("1+;50;50;0.5;0.5;75;"));
+    }
+
+    #[test]
+    fn exportar_identifica_os_decrementos_pelo_nome() {
+        let morte = Tabua::new(vec![0.1, 1.0], Periodicidade::Anual);
+        let invalidez = Tabua::new(vec![0.05, 0.05], Periodicidade::Anual);
+        let mdt = TabuaMDT::new(vec![morte, invalidez], crate::HipoteseMdt::UddTabuasSimples)
+            .com_nomes_decrementos(vec!["morte".to_string(), "invalidez".to_string()]);
+
+        let texto = TabuaVida::a_partir_da_tabua_mdt(&mdt, 100.0).exportar(';');
+
+        assert_eq!(
+            texto.lines().next().unwrap(),
+            "idade;lx;dx;qx;px;Lx;Tx;ex;e̊x;mx;dx(morte);dx(invalidez)"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "A raiz da tábua de vida deve ser finita e positiva")]
+    fn raiz_deve_ser_positiva() {
+        let tabua = Tabua::new(vec![0.5, 1.0], Periodicidade::Anual);
+        TabuaVida::a_partir_da_tabua(&tabua, 0.0);
+    }
+}